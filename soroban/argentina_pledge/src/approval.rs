@@ -0,0 +1,74 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{
+    errors::Error,
+    storage_types::{ApprovalData, DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD},
+};
+
+pub fn read_approval(e: &Env, id: u64) -> Option<Address> {
+    let key = DataKey::Approval(id);
+    match e.storage().temporary().get::<DataKey, ApprovalData>(&key) {
+        Some(approval) if approval.expiration_ledger >= e.ledger().sequence() => {
+            Some(approval.spender)
+        }
+        _ => None,
+    }
+}
+
+pub fn write_approval(e: &Env, id: u64, spender: Address, expiration_ledger: u32) {
+    // an expiration ledger in the past revokes the approval
+    if expiration_ledger < e.ledger().sequence() {
+        remove_approval(e, id);
+        return;
+    }
+    let key = DataKey::Approval(id);
+    let approval = ApprovalData {
+        spender,
+        expiration_ledger,
+    };
+    e.storage().temporary().set(&key, &approval);
+    let live_for = expiration_ledger - e.ledger().sequence();
+    e.storage().temporary().extend_ttl(&key, live_for, live_for);
+}
+
+pub fn remove_approval(e: &Env, id: u64) {
+    let key = DataKey::Approval(id);
+    e.storage().temporary().remove(&key);
+}
+
+pub fn read_operator(e: &Env, owner: Address, operator: Address) -> bool {
+    let key = DataKey::Operator(owner, operator);
+    match e.storage().persistent().get::<DataKey, bool>(&key) {
+        Some(approved) => {
+            e.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            approved
+        }
+        None => false,
+    }
+}
+
+pub fn write_operator(e: &Env, owner: Address, operator: Address, approved: bool) {
+    let key = DataKey::Operator(owner, operator);
+    if !approved {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage().persistent().set(&key, &approved);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn check_spender(e: &Env, spender: &Address, owner: &Address, id: u64) {
+    if read_approval(e, id).as_ref() == Some(spender) {
+        return;
+    }
+    if read_operator(e, owner.clone(), spender.clone()) {
+        return;
+    }
+    panic_with_error!(e, Error::NotAuthorized);
+}
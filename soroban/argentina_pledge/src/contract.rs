@@ -1,18 +1,31 @@
 use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, BytesN, Env, Vec};
 
 use crate::{
-    admin::{has_admin, read_admin, write_admin},
+    admin::{
+        has_admin, is_pending_admin_expired, read_admin, read_pending_admin, remove_pending_admin,
+        write_admin, write_pending_admin,
+    },
+    approval::{check_spender, read_approval, read_operator, write_approval, write_operator},
     balance::{increment_supply, read_supply},
     errors::Error,
     event,
     ext_token::{read_ext_token, write_ext_token},
     interface::TokenizedCertificateTrait,
     owner::{check_owner, read_owner, write_owner},
-    storage_types::{ExtTokenInfo, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD},
+    pause::{
+        check_exit_allowed, check_not_paused, is_paused, read_exits_allowed, write_exits_allowed,
+        write_paused,
+    },
+    roles::{check_role, has_role, write_role, ALL_ROLES},
+    storage_types::{
+        ExtTokenInfo, PendingAdmin, Role, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD,
+        STORAGE_VERSION,
+    },
     token_data::{
         read_amount, read_file_hashes, read_redeem_time, write_amount, write_file_hashes,
         write_redeem_time,
     },
+    upgrade::{migrate, read_version, write_version},
 };
 
 #[contract]
@@ -25,6 +38,9 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
             panic!("already initialized")
         }
         write_admin(&e, &admin);
+        for role in ALL_ROLES {
+            write_role(&e, role, &admin, true);
+        }
         if ext_token_decimals > u8::MAX.into() {
             panic!("Decimal must fit in a u8");
         }
@@ -34,10 +50,11 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
                 address: ext_token_address,
                 decimals: ext_token_decimals,
             },
-        )
+        );
+        write_version(&e, STORAGE_VERSION);
     }
 
-    fn set_admin(e: Env, new_admin: Address) {
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>) {
         let admin = read_admin(&e);
         admin.require_auth();
 
@@ -45,14 +62,201 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        if let Some(expiration_time) = expiration_time {
+            if expiration_time <= e.ledger().timestamp() {
+                panic_with_error!(&e, Error::NotPermitted);
+            }
+        }
+        write_pending_admin(
+            &e,
+            &PendingAdmin {
+                address: new_admin.clone(),
+                expiration_time,
+            },
+        );
+        event::propose_admin(&e, admin, new_admin, expiration_time);
+    }
+
+    fn accept_admin(e: Env) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        if is_pending_admin_expired(&e, &pending_admin) {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let new_admin = pending_admin.address;
+        new_admin.require_auth();
+
+        // the roles of the outgoing admin pass to the new admin, so the old key keeps none of them
+        let admin = read_admin(&e);
+        for role in ALL_ROLES {
+            if has_role(&e, role, &admin) {
+                write_role(&e, role, &admin, false);
+                write_role(&e, role, &new_admin, true);
+            }
+        }
         write_admin(&e, &new_admin);
-        event::set_admin(&e, admin, new_admin)
+        remove_pending_admin(&e);
+        event::set_admin(&e, admin, new_admin);
+    }
+
+    fn cancel_admin_proposal(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        remove_pending_admin(&e);
+        event::cancel_admin_proposal(&e, admin, pending_admin.address);
+    }
+
+    fn pending_admin(e: Env) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        match read_pending_admin(&e) {
+            Some(pending_admin) if !is_pending_admin_expired(&e, &pending_admin) => {
+                Some(pending_admin.address)
+            }
+            _ => None,
+        }
+    }
+
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    fn migrate(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if read_version(&e) >= STORAGE_VERSION {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        // storage written before versioning predates roles, so the admin is granted the roles it held implicitly
+        if read_version(&e) < 1 {
+            for role in ALL_ROLES {
+                write_role(&e, role, &admin, true);
+            }
+        }
+        migrate(&e);
     }
 
-    fn mint(e: Env, amount: u64, redeem_time: u64, file_hashes: Vec<BytesN<32>>) -> u64 {
+    fn version(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_version(&e)
+    }
+
+    fn grant_role(e: Env, role: Role, account: Address) {
         let admin = read_admin(&e);
         admin.require_auth();
 
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_role(&e, role, &account, true);
+        event::grant_role(&e, admin, role, account);
+    }
+
+    fn revoke_role(e: Env, role: Role, account: Address) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_role(&e, role, &account, false);
+        event::revoke_role(&e, admin, role, account);
+    }
+
+    fn has_role(e: Env, role: Role, account: Address) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        has_role(&e, role, &account)
+    }
+
+    fn pause(e: Env, caller: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&e, true);
+        event::pause(&e, caller);
+    }
+
+    fn unpause(e: Env, caller: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&e, false);
+        event::unpause(&e, caller);
+    }
+
+    fn is_paused(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        is_paused(&e)
+    }
+
+    fn set_exits_allowed(e: Env, caller: Address, allowed: bool) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_exits_allowed(&e, allowed);
+    }
+
+    fn exits_allowed(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_exits_allowed(&e)
+    }
+
+    fn mint(
+        e: Env,
+        caller: Address,
+        amount: u64,
+        redeem_time: u64,
+        file_hashes: Vec<BytesN<32>>,
+    ) -> u64 {
+        check_role(&e, Role::Minter, &caller);
+        caller.require_auth();
+
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -80,7 +284,53 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
+        check_owner(&e, &from, id);
+        write_owner(&e, id, Some(to.clone()));
+        event::transfer(&e, from, to, id);
+    }
+
+    fn approve(e: Env, owner: Address, spender: Address, id: u64, expiration_ledger: u32) {
+        owner.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_owner(&e, &owner, id);
+        write_approval(&e, id, spender.clone(), expiration_ledger);
+        event::approve(&e, owner, spender, id, expiration_ledger);
+    }
+
+    fn set_approval_for_all(e: Env, owner: Address, operator: Address, approved: bool) {
+        owner.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        write_operator(&e, owner.clone(), operator.clone(), approved);
+        event::approve_all(&e, owner, operator, approved);
+    }
+
+    fn get_approved(e: Env, id: u64) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_approval(&e, id)
+    }
+
+    fn is_approved_for_all(e: Env, owner: Address, operator: Address) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_operator(&e, owner, operator)
+    }
+
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, id: u64) {
+        spender.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
         check_owner(&e, &from, id);
+        check_spender(&e, &spender, &from, id);
         write_owner(&e, id, Some(to.clone()));
         event::transfer(&e, from, to, id);
     }
@@ -90,6 +340,7 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
         check_owner(&e, &e.current_contract_address(), id);
 
         // Transfer USDC from "from" to the contract address
@@ -109,6 +360,7 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
         check_owner(&e, &to, id);
         if e.ledger().timestamp() < read_redeem_time(&e, id) {
             panic_with_error!(&e, Error::NotRedeemable);
@@ -11,4 +11,5 @@ pub enum Error {
     NotOwned = 5,
     NotRedeemable = 6,
     SizeLimitExceeded = 7,
+    Paused = 8,
 }
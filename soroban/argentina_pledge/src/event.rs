@@ -1,3 +1,4 @@
+use crate::storage_types::Role;
 use soroban_sdk::{symbol_short, Address, Env};
 
 pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
@@ -5,11 +6,46 @@ pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
     e.events().publish(topics, new_admin);
 }
 
+pub(crate) fn propose_admin(
+    e: &Env,
+    admin: Address,
+    new_admin: Address,
+    expiration_time: Option<u64>,
+) {
+    let topics = (symbol_short!("propose"), admin);
+    e.events().publish(topics, (new_admin, expiration_time));
+}
+
+pub(crate) fn cancel_admin_proposal(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("unpropose"), admin);
+    e.events().publish(topics, new_admin);
+}
+
+pub(crate) fn grant_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("grant"), role, account);
+    e.events().publish(topics, admin);
+}
+
+pub(crate) fn revoke_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("revoke"), role, account);
+    e.events().publish(topics, admin);
+}
+
 pub(crate) fn transfer(e: &Env, from: Address, to: Address, id: u64) {
     let topics = (symbol_short!("transfer"), from, to);
     e.events().publish(topics, id);
 }
 
+pub(crate) fn approve(e: &Env, owner: Address, spender: Address, id: u64, expiration_ledger: u32) {
+    let topics = (symbol_short!("approve"), owner, spender);
+    e.events().publish(topics, (id, expiration_ledger));
+}
+
+pub(crate) fn approve_all(e: &Env, owner: Address, operator: Address, approved: bool) {
+    let topics = (symbol_short!("appr_all"), owner, operator);
+    e.events().publish(topics, approved);
+}
+
 pub(crate) fn mint(e: &Env, to: Address, id: u64) {
     let topics = (symbol_short!("mint"), to);
     e.events().publish(topics, id);
@@ -19,3 +55,13 @@ pub(crate) fn burn(e: &Env, from: Address, id: u64) {
     let topics = (symbol_short!("burn"), from);
     e.events().publish(topics, id);
 }
+
+pub(crate) fn pause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("pause"), caller);
+    e.events().publish(topics, ());
+}
+
+pub(crate) fn unpause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("unpause"), caller);
+    e.events().publish(topics, ());
+}
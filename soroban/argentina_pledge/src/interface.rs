@@ -1,5 +1,7 @@
 use soroban_sdk::{Address, BytesN, Env, Vec};
 
+use crate::storage_types::Role;
+
 pub trait TokenizedCertificateTrait {
     // --------------------------------------------------------------------------------
     // Admin interface
@@ -8,25 +10,102 @@ pub trait TokenizedCertificateTrait {
     /// Initialize the contract with "admin" as administrator
     fn initialize(e: Env, admin: Address, ext_token_address: Address, ext_token_decimals: u32);
 
-    /// If "admin" is the administrator, set the administrator to "new_admin".
+    /// Propose "new_admin" as the next administrator. Can only be called by the admin. Replaces any pending proposal.
+    /// If "expiration_time" is set, the proposal can only be accepted until that Unix timestamp.
+    /// Emit event with topics = ["propose", admin: Address], data = [new_admin: Address, expiration_time: Option<u64>]
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>);
+
+    /// Called by the proposed administrator to become the administrator. The roles held by the previous administrator are moved to the new one.
     /// Emit event with topics = ["set_admin", admin: Address], data = [new_admin: Address]
-    fn set_admin(e: Env, new_admin: Address);
+    fn accept_admin(e: Env);
+
+    /// Withdraw the pending administrator proposal. Can only be called by the admin.
+    /// Emit event with topics = ["unpropose", admin: Address], data = [new_admin: Address]
+    fn cancel_admin_proposal(e: Env);
+
+    /// Get the proposed administrator, if a proposal is pending and has not expired.
+    fn pending_admin(e: Env) -> Option<Address>;
+
+    /// Replace the contract code with the uploaded WASM identified by "new_wasm_hash". Can only be called by the admin.
+    /// Storage is kept as is; call migrate() afterwards if the new code uses a newer storage layout.
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>);
+
+    /// Rewrite storage written by an older version of the contract to the current layout. Can only be called by the admin.
+    /// Will fail if the storage is already up to date.
+    fn migrate(e: Env);
+
+    /// Returns the version of the storage layout
+    fn version(e: Env) -> u32;
+
+    /// Grant "role" to "account". Can only be called by the admin. The admin holds every role after initialization.
+    /// Emit event with topics = ["grant", role: Role, account: Address], data = [admin: Address]
+    fn grant_role(e: Env, role: Role, account: Address);
+
+    /// Revoke "role" from "account". Can only be called by the admin.
+    /// Emit event with topics = ["revoke", role: Role, account: Address], data = [admin: Address]
+    fn revoke_role(e: Env, role: Role, account: Address);
+
+    /// Check whether "account" holds "role".
+    fn has_role(e: Env, role: Role, account: Address) -> bool;
+
+    /// Stop the state-changing entry points until unpause() is called. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["pause", caller: Address], data = []
+    fn pause(e: Env, caller: Address);
+
+    /// Resume normal operation after a pause. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["unpause", caller: Address], data = []
+    fn unpause(e: Env, caller: Address);
+
+    /// Check whether the contract is paused.
+    fn is_paused(e: Env) -> bool;
+
+    /// Set whether redeem() remain available while the contract is paused. "caller" must hold the Guardian role.
+    /// Exits are allowed by default, so that users can always withdraw their funds.
+    fn set_exits_allowed(e: Env, caller: Address, allowed: bool);
+
+    /// Check whether redeem() remain available while the contract is paused.
+    fn exits_allowed(e: Env) -> bool;
 
     // --------------------------------------------------------------------------------
     // Token interface
     // --------------------------------------------------------------------------------
 
-    /// Admin calls this function. Minted TC belongs to contract.
+    /// "caller" must hold the Minter role. Minted TC belongs to contract.
     /// "amount" is the value of the TC in terms of ext_token.
     /// redeem_time is a Unix timestamp representing the date after which the TC can be redeemed.
     /// file_hashes contains the hashes of each relevant file uploaded when creating the order on the platform's backend.
     /// Emit event with topics = ["mint", to: Address], data = [id: u64]
-    fn mint(e: Env, amount: u64, redeem_time: u64, file_hashes: Vec<BytesN<32>>) -> u64;
+    fn mint(
+        e: Env,
+        caller: Address,
+        amount: u64,
+        redeem_time: u64,
+        file_hashes: Vec<BytesN<32>>,
+    ) -> u64;
 
     /// Transfer token 'id' between specified 'from' and 'to' addresses
     /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: u64]
     fn transfer(e: Env, from: Address, to: Address, id: u64);
 
+    /// Allow "spender" to transfer TC "id" on behalf of "owner" until "expiration_ledger". Replaces any previous approval for "id".
+    /// An "expiration_ledger" lower than the current ledger sequence removes the approval. Approvals are cleared when the TC changes owner.
+    /// Emit event with topics = ["approve", owner: Address, spender: Address], data = [id: u64, expiration_ledger: u32]
+    fn approve(e: Env, owner: Address, spender: Address, id: u64, expiration_ledger: u32);
+
+    /// Allow or disallow "operator" to transfer any TC of "owner".
+    /// Emit event with topics = ["appr_all", owner: Address, operator: Address], data = [approved: bool]
+    fn set_approval_for_all(e: Env, owner: Address, operator: Address, approved: bool);
+
+    /// Returns the address approved to transfer a given TC, if any
+    fn get_approved(e: Env, id: u64) -> Option<Address>;
+
+    /// Returns whether "operator" may transfer any TC of "owner"
+    fn is_approved_for_all(e: Env, owner: Address, operator: Address) -> bool;
+
+    /// Transfer TC 'id' from 'from' to 'to' on behalf of 'from'. 'spender' must be approved for 'id' or be an operator of 'from'.
+    /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: u64]
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, id: u64);
+
     /// Transfers USDC to the contract address, and transfers ownership of the TC to the caller.
     fn pledge(e: Env, from: Address, id: u64);
 
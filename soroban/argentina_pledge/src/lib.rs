@@ -1,6 +1,7 @@
 #![no_std]
 
 mod admin;
+mod approval;
 mod balance;
 mod contract;
 mod errors;
@@ -8,9 +9,12 @@ mod event;
 mod ext_token;
 mod interface;
 mod owner;
+mod pause;
+mod roles;
 mod storage_types;
 mod test;
 mod test_util;
 mod token_data;
+mod upgrade;
 
 pub use crate::contract::TokenizedCertificateClient;
@@ -1,12 +1,14 @@
 use soroban_sdk::{panic_with_error, Address, Env};
 
 use crate::{
+    approval::remove_approval,
     errors::Error,
     storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD},
 };
 
 pub fn write_owner(e: &Env, id: u64, owner: Option<Address>) {
     let key = DataKey::Owner(id);
+    remove_approval(e, id);
     match owner {
         Some(owner) => {
             e.storage().persistent().set(&key, &owner);
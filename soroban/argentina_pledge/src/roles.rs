@@ -0,0 +1,43 @@
+use crate::errors::Error;
+use crate::storage_types::{DataKey, Role, ROLE_BUMP_AMOUNT, ROLE_LIFETIME_THRESHOLD};
+use soroban_sdk::{panic_with_error, Address, Env};
+
+// every role, granted to the admin at initialization and when migrating unversioned storage
+pub const ALL_ROLES: [Role; 5] = [
+    Role::Minter,
+    Role::Compliance,
+    Role::Treasury,
+    Role::Risk,
+    Role::Guardian,
+];
+
+pub fn has_role(e: &Env, role: Role, account: &Address) -> bool {
+    let key = DataKey::Role(role, account.clone());
+    match e.storage().persistent().get::<DataKey, bool>(&key) {
+        Some(granted) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, ROLE_LIFETIME_THRESHOLD, ROLE_BUMP_AMOUNT);
+            granted
+        }
+        None => false,
+    }
+}
+
+pub fn write_role(e: &Env, role: Role, account: &Address, granted: bool) {
+    let key = DataKey::Role(role, account.clone());
+    if !granted {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage().persistent().set(&key, &granted);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, ROLE_LIFETIME_THRESHOLD, ROLE_BUMP_AMOUNT);
+}
+
+pub fn check_role(e: &Env, role: Role, account: &Address) {
+    if !has_role(e, role, account) {
+        panic_with_error!(e, Error::NotAuthorized);
+    }
+}
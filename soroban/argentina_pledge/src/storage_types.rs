@@ -7,6 +7,12 @@ pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_I
 pub(crate) const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+pub(crate) const ROLE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ROLE_LIFETIME_THRESHOLD: u32 = ROLE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// version of the storage layout, to be bumped along with a migration step whenever the layout changes
+pub(crate) const STORAGE_VERSION: u32 = 1;
+
 #[derive(Clone)]
 #[contracttype]
 pub struct ExtTokenInfo {
@@ -14,6 +20,38 @@ pub struct ExtTokenInfo {
     pub decimals: u32,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct ApprovalData {
+    pub spender: Address,
+    pub expiration_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub address: Address,
+    // Unix timestamp after which the proposal can no longer be accepted
+    pub expiration_time: Option<u64>,
+}
+
+// privileged duties that the admin can hand out to separate accounts
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    // mints TCs
+    Minter = 0,
+    // unused by this contract, kept so role ids match the other contracts
+    Compliance = 1,
+    // unused by this contract, kept so role ids match the other contracts
+    Treasury = 2,
+    // unused by this contract, kept so role ids match the other contracts
+    Risk = 3,
+    // pauses the contract and controls exits
+    Guardian = 4,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -24,4 +62,11 @@ pub enum DataKey {
     Amount(u64),
     RedeemTime(u64),
     Owner(u64),
+    Approval(u64),
+    Operator(Address, Address),
+    Version,
+    PendingAdmin,
+    Role(Role, Address),
+    Paused,
+    ExitsAllowed,
 }
@@ -1,10 +1,15 @@
 #![cfg(test)]
-use soroban_sdk::{testutils::Address as _, vec, Address, Env, Error};
+use soroban_sdk::{
+    symbol_short, testutils::Address as _, testutils::Events, vec, Address, Env, Error, IntoVal,
+};
 
 use crate::contract::{TokenizedCertificate, TokenizedCertificateClient};
 use crate::errors::Error as ContractError;
+use crate::roles::ALL_ROLES;
+use crate::storage_types::{DataKey, Role};
 use crate::test_util::{
-    pad_bytes_32, set_ledger_timestamp, setup_test_tc_contract, setup_test_token,
+    pad_bytes_32, set_ledger_sequence, set_ledger_timestamp, setup_test_tc_contract,
+    setup_test_token, tc_contract, upload_rebuilt_wasm,
 };
 
 #[test]
@@ -20,6 +25,66 @@ fn test_initialize() {
     assert_eq!(client.get_ext_token(), (token_client.address, 0));
 }
 
+#[test]
+fn test_admin_handover() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    // the admin does not change until the proposal is accepted
+    let new_admin = Address::generate(&e);
+    tc_client.propose_admin(&new_admin, &None);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("propose"), admin.clone()).into_val(&e)
+            );
+            let data: (Address, Option<u64>) = data.into_val(&e);
+            assert_eq!(data, (new_admin.clone(), None));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(tc_client.pending_admin(), Some(new_admin.clone()));
+    tc_client.cancel_admin_proposal();
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("unpropose"), admin.clone()).into_val(&e)
+            );
+            let data: Address = data.into_val(&e);
+            assert_eq!(data, new_admin.clone());
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(tc_client.pending_admin(), None);
+    let res = tc_client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+
+    tc_client.propose_admin(&new_admin, &None);
+    tc_client.accept_admin();
+    assert_eq!(e.auths()[0].0, new_admin.clone());
+    assert_eq!(tc_client.pending_admin(), None);
+
+    // the roles of the old admin move to the new admin
+    for role in ALL_ROLES {
+        assert!(!tc_client.has_role(&role, &admin));
+        assert!(tc_client.has_role(&role, &new_admin));
+    }
+
+    // the new admin manages the roles
+    tc_client.grant_role(&Role::Minter, &new_admin);
+    assert_eq!(e.auths()[0].0, new_admin);
+}
+
 #[test]
 fn test_mint() {
     let e = Env::default();
@@ -29,6 +94,7 @@ fn test_mint() {
     e.mock_all_auths();
 
     tc_client.mint(
+        &admin,
         &1000000,
         &1641024000,
         &vec![
@@ -59,6 +125,53 @@ fn test_mint() {
     );
 }
 
+#[test]
+fn test_roles() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    // the admin holds every role after initialization
+    assert!(tc_client.has_role(&Role::Minter, &admin));
+
+    let minter = Address::generate(&e);
+    let res = tc_client.try_mint(&minter, &1000000, &1641024000, &vec![&e]);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+
+    tc_client.grant_role(&Role::Minter, &minter);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("grant"), Role::Minter, minter.clone()).into_val(&e)
+            );
+            let admin_decoded: Address = data.into_val(&e);
+            assert_eq!(admin, admin_decoded);
+        }
+        None => panic!("The event is not published"),
+    }
+    tc_client.mint(&minter, &1000000, &1641024000, &vec![&e]);
+    assert_eq!(e.auths()[0].0, minter.clone());
+
+    // a revoked minter can no longer mint
+    tc_client.revoke_role(&Role::Minter, &minter);
+    assert!(!tc_client.has_role(&Role::Minter, &minter));
+    let res = tc_client.try_mint(&minter, &1000000, &1641024000, &vec![&e]);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+}
+
 #[test]
 fn test_mint_too_early() {
     let e = Env::default();
@@ -71,7 +184,7 @@ fn test_mint_too_early() {
     set_ledger_timestamp(&e, timestamp);
 
     let redeem_time = timestamp - 86400;
-    let res = tc_client.try_mint(&1000000, &redeem_time, &vec![&e]);
+    let res = tc_client.try_mint(&admin, &1000000, &redeem_time, &vec![&e]);
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
@@ -88,7 +201,7 @@ fn test_pledge() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
     assert_eq!(tc_client.get_owner(&0), tc_client.address);
 
     let user = Address::generate(&e);
@@ -105,7 +218,7 @@ fn test_pledge_insufficient_balance() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
     assert_eq!(tc_client.get_owner(&0), tc_client.address);
 
     let user = Address::generate(&e);
@@ -123,7 +236,7 @@ fn test_transfer() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
     assert_eq!(tc_client.get_owner(&0), tc_client.address);
 
     let user = Address::generate(&e);
@@ -145,7 +258,7 @@ fn test_transfer_not_owned() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
     assert_eq!(tc_client.get_owner(&0), tc_client.address);
 
     // try to transfer while the contract still owns TC #0
@@ -161,6 +274,91 @@ fn test_transfer_not_owned() {
     assert_eq!(tc_client.get_owner(&0), tc_client.address);
 }
 
+#[test]
+fn test_approve_and_transfer_from() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
+    let user = Address::generate(&e);
+    token_admin_client.mint(&user.clone(), &10000000);
+    tc_client.pledge(&user.clone(), &0);
+    assert_eq!(tc_client.get_approved(&0), None);
+
+    // a spender without approval cannot transfer the TC
+    let spender = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let res = tc_client.try_transfer_from(&spender, &user, &user2, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+
+    set_ledger_sequence(&e, 100);
+    tc_client.approve(&user, &spender, &0, &200);
+    assert_eq!(tc_client.get_approved(&0), Some(spender.clone()));
+    tc_client.transfer_from(&spender, &user, &user2, &0);
+    assert_eq!(tc_client.get_owner(&0), user2);
+
+    // the approval is cleared once the TC changes owner
+    assert_eq!(tc_client.get_approved(&0), None);
+
+    // the approval lapses after the expiration ledger
+    tc_client.approve(&user2, &spender, &0, &200);
+    set_ledger_sequence(&e, 201);
+    assert_eq!(tc_client.get_approved(&0), None);
+    let res = tc_client.try_transfer_from(&spender, &user2, &user, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+}
+
+#[test]
+fn test_set_approval_for_all() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
+    let user = Address::generate(&e);
+    token_admin_client.mint(&user.clone(), &10000000);
+    tc_client.pledge(&user.clone(), &0);
+    tc_client.pledge(&user.clone(), &1);
+
+    let operator = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    assert!(!tc_client.is_approved_for_all(&user, &operator));
+    tc_client.set_approval_for_all(&user, &operator, &true);
+    assert!(tc_client.is_approved_for_all(&user, &operator));
+    tc_client.transfer_from(&operator, &user, &user2, &0);
+    tc_client.transfer_from(&operator, &user, &user2, &1);
+    assert_eq!(tc_client.get_owner(&0), user2);
+    assert_eq!(tc_client.get_owner(&1), user2);
+
+    // operator approval does not extend to TCs of other owners
+    let res = tc_client.try_transfer_from(&operator, &user2, &user, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+
+    tc_client.set_approval_for_all(&user, &operator, &false);
+    assert!(!tc_client.is_approved_for_all(&user, &operator));
+}
+
 #[test]
 fn test_redeem_too_early() {
     let e = Env::default();
@@ -169,7 +367,7 @@ fn test_redeem_too_early() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
 
     let user = Address::generate(&e);
     token_admin_client.mint(&user.clone(), &10000000);
@@ -196,7 +394,7 @@ fn test_redeem() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
 
     let user = Address::generate(&e);
     token_admin_client.mint(&user, &10000000);
@@ -212,6 +410,62 @@ fn test_redeem() {
     assert_eq!(tc_client.try_get_owner(&0).is_ok(), false);
 }
 
+#[test]
+fn test_pause() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
+    let user = Address::generate(&e);
+    token_admin_client.mint(&user, &10000000);
+    tc_client.pledge(&user, &0);
+
+    // only a guardian can pause
+    let guardian = Address::generate(&e);
+    let res = tc_client.try_pause(&guardian);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    tc_client.grant_role(&Role::Guardian, &guardian);
+    tc_client.pause(&guardian);
+    assert!(tc_client.is_paused());
+
+    // pledges and transfers fail while paused
+    let res = tc_client.try_pledge(&user, &1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    let user2 = Address::generate(&e);
+    let res = tc_client.try_transfer(&user, &user2, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+
+    // redeem stays available unless the guardian closes the exits
+    set_ledger_timestamp(&e, 1641024001);
+    tc_client.set_exits_allowed(&guardian, &false);
+    let res = tc_client.try_redeem(&user, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    tc_client.set_exits_allowed(&guardian, &true);
+    tc_client.redeem(&user, &0);
+    assert_eq!(token_client.balance(&user), 10000000);
+
+    tc_client.unpause(&guardian);
+    assert!(!tc_client.is_paused());
+}
+
 #[test]
 fn test_redeem_not_owned() {
     let e = Env::default();
@@ -220,7 +474,7 @@ fn test_redeem_not_owned() {
     let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
     e.mock_all_auths();
 
-    tc_client.mint(&1000000, &1641024000, &vec![&e]);
+    tc_client.mint(&admin, &1000000, &1641024000, &vec![&e]);
 
     let user = Address::generate(&e);
     token_admin_client.mint(&user.clone(), &10000000);
@@ -236,3 +490,110 @@ fn test_redeem_not_owned() {
         )))
     );
 }
+
+#[test]
+fn test_version() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+    assert_eq!(tc_client.version(), 1);
+
+    // storage written by the current version needs no migration
+    let res = tc_client.try_migrate();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+}
+
+#[test]
+fn test_upgrade() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    tc_client.mint(
+        &admin,
+        &1000000,
+        &1641024000,
+        &vec![&e, pad_bytes_32(&e, b"a")],
+    );
+    tc_client.mint(&admin, &2000000, &1641024000, &vec![&e]);
+    let user = Address::generate(&e);
+    token_admin_client.mint(&user, &10000000);
+    tc_client.pledge(&user, &0);
+
+    // move to the release build, then to a build with another hash
+    let wasm_hash = e.deployer().upload_contract_wasm(tc_contract::WASM);
+    tc_client.upgrade(&wasm_hash);
+    let rebuilt_hash = upload_rebuilt_wasm(&e, tc_contract::WASM);
+    assert_ne!(wasm_hash, rebuilt_hash);
+    tc_client.upgrade(&rebuilt_hash);
+
+    // existing certificates and balances survive the upgrades
+    assert_eq!(tc_client.version(), 1);
+    assert_eq!(tc_client.get_owner(&0), user);
+    assert_eq!(tc_client.get_amount(&0), 1000000);
+    assert_eq!(
+        tc_client.get_file_hashes(&0),
+        vec![&e, pad_bytes_32(&e, b"a")]
+    );
+    assert_eq!(tc_client.get_owner(&1), tc_client.address);
+    assert_eq!(tc_client.get_amount(&1), 2000000);
+    assert_eq!(token_client.balance(&tc_client.address), 1000000);
+    assert_eq!(token_client.balance(&user), 9000000);
+
+    // the pledged certificate can still be redeemed
+    set_ledger_timestamp(&e, 1641024001);
+    tc_client.redeem(&user, &0);
+    assert_eq!(token_client.balance(&user), 10000000);
+}
+
+#[test]
+fn test_migrate() {
+    let e = Env::default();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let tc_client = setup_test_tc_contract(&e, &admin, &token_client.address, &0);
+    e.mock_all_auths();
+
+    tc_client.mint(
+        &admin,
+        &1000000,
+        &1641024000,
+        &vec![&e, pad_bytes_32(&e, b"a")],
+    );
+    let user = Address::generate(&e);
+    token_admin_client.mint(&user.clone(), &10000000);
+    tc_client.pledge(&user.clone(), &0);
+
+    // storage written before versioning has no version
+    e.as_contract(&tc_client.address, || {
+        e.storage().instance().remove(&DataKey::Version);
+        for role in ALL_ROLES {
+            e.storage()
+                .persistent()
+                .remove(&DataKey::Role(role, admin.clone()));
+        }
+    });
+    assert_eq!(tc_client.version(), 0);
+
+    assert!(!tc_client.has_role(&Role::Guardian, &admin));
+    tc_client.migrate();
+    for role in ALL_ROLES {
+        assert!(tc_client.has_role(&role, &admin));
+    }
+    assert_eq!(tc_client.version(), 1);
+    assert_eq!(tc_client.get_owner(&0), user);
+    assert_eq!(tc_client.get_amount(&0), 1000000);
+    assert_eq!(
+        tc_client.get_file_hashes(&0),
+        vec![&e, pad_bytes_32(&e, b"a")]
+    );
+}
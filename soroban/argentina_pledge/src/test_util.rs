@@ -1,7 +1,13 @@
 #![cfg(any(test, feature = "testutils"))]
 
 use crate::contract::{TokenizedCertificate, TokenizedCertificateClient};
-use soroban_sdk::{testutils::Ledger, token, Address, BytesN, Env};
+use soroban_sdk::{testutils::Ledger, token, Address, Bytes, BytesN, Env};
+
+pub mod tc_contract {
+    soroban_sdk::contractimport!(
+        file = "target/wasm32-unknown-unknown/release/argentina_pledge.wasm"
+    );
+}
 
 pub fn setup_test_tc_contract<'a>(
     e: &Env,
@@ -36,3 +42,14 @@ pub fn pad_bytes_32(e: &Env, data: &[u8]) -> BytesN<32> {
     padded[..data.len()].copy_from_slice(data);
     BytesN::from_array(e, &padded)
 }
+
+pub fn set_ledger_sequence(e: &Env, sequence: u32) {
+    e.ledger().with_mut(|li| li.sequence_number = sequence);
+}
+
+// uploads "wasm" with an extra custom section, so that the same code gets a new hash and an upgrade to it swaps the executable
+pub fn upload_rebuilt_wasm(e: &Env, wasm: &[u8]) -> BytesN<32> {
+    let mut rebuilt = Bytes::from_slice(e, wasm);
+    rebuilt.extend_from_slice(&[0, 9, 7, b'u', b'p', b'g', b'r', b'a', b'd', b'e', 1]);
+    e.deployer().upload_contract_wasm(rebuilt)
+}
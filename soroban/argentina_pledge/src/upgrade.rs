@@ -0,0 +1,19 @@
+use soroban_sdk::Env;
+
+use crate::storage_types::{DataKey, STORAGE_VERSION};
+
+// contracts deployed before versioning was introduced have no stored version
+pub fn read_version(e: &Env) -> u32 {
+    let key = DataKey::Version;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_version(e: &Env, version: u32) {
+    let key = DataKey::Version;
+    e.storage().instance().set(&key, &version);
+}
+
+pub fn migrate(e: &Env) {
+    // version 1 only added new keys (approvals and operators), so older storage is kept as is
+    write_version(e, STORAGE_VERSION);
+}
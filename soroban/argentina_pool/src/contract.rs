@@ -1,15 +1,37 @@
 use crate::{
-    admin::{has_admin, read_admin, write_admin},
+    admin::{
+        has_admin, is_pending_admin_expired, read_admin, read_pending_admin, remove_pending_admin,
+        write_admin, write_pending_admin,
+    },
     errors::Error,
+    event,
     ext_token::{read_ext_token, write_ext_token},
     interface::LiquidityPoolTrait,
     loan::{
-        increment_supply, is_whitelisted, read_fee_percent, read_loan, read_supply, read_whitelist,
-        write_fee_percent, write_loan, write_whitelist, Loan, LoanStatus,
+        holds_collateral, increment_supply, is_whitelisted, read_fee_bps, read_grace_period,
+        read_interest_model, read_liquidation_fee_bps, read_loan, read_max_ltv_bps, read_supply,
+        read_whitelist, write_fee_bps, write_grace_period, write_holds_collateral,
+        write_interest_model, write_liquidation_fee_bps, write_loan, write_max_ltv_bps,
+        write_whitelist, InterestModel, Loan, LoanStatus,
+    },
+    pause::{
+        check_exit_allowed, check_not_paused, is_paused, read_exits_allowed, write_exits_allowed,
+        write_paused,
+    },
+    pool::{
+        amount_for_shares, has_share_token, read_pool_balance, read_pool_lent, read_share_token,
+        read_total_assets, read_total_shares, read_treasury_fees, shares_for_deposit,
+        write_pool_balance, write_pool_lent, write_share_token, write_total_shares,
+        write_treasury_fees,
+    },
+    roles::{check_role, has_role, write_role, ALL_ROLES},
+    storage_types::{
+        PendingAdmin, Role, TokenInfo, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD,
+        SECONDS_PER_YEAR, STORAGE_VERSION,
     },
-    storage_types::{TokenInfo, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD},
+    upgrade::{migrate, read_version, write_version},
 };
-use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Env, Vec};
+use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, BytesN, Env, Vec};
 
 mod tc_contract {
     soroban_sdk::contractimport!(
@@ -22,13 +44,16 @@ pub struct LiquidityPool;
 
 #[contractimpl]
 impl LiquidityPoolTrait for LiquidityPool {
-    fn initialize(e: Env, admin: Address, ext_token_address: Address, fee_percent: u32) {
+    fn initialize(e: Env, admin: Address, ext_token_address: Address, fee_bps: u32) {
         if has_admin(&e) {
             panic!("already initialized")
         }
         let ext_token_decimals = token::Client::new(&e, &ext_token_address).decimals();
 
         write_admin(&e, &admin);
+        for role in ALL_ROLES {
+            write_role(&e, role, &admin, true);
+        }
         if ext_token_decimals > u8::MAX.into() {
             panic!("Decimal must fit in a u8");
         }
@@ -40,10 +65,11 @@ impl LiquidityPoolTrait for LiquidityPool {
                 decimals: ext_token_decimals,
             },
         );
-        write_fee_percent(&e, fee_percent);
+        write_fee_bps(&e, fee_bps);
+        write_version(&e, STORAGE_VERSION);
     }
 
-    fn set_admin(e: Env, new_admin: Address) {
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>) {
         let admin = read_admin(&e);
         admin.require_auth();
 
@@ -51,10 +77,115 @@ impl LiquidityPoolTrait for LiquidityPool {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        if let Some(expiration_time) = expiration_time {
+            if expiration_time <= e.ledger().timestamp() {
+                panic_with_error!(&e, Error::NotPermitted);
+            }
+        }
+        write_pending_admin(
+            &e,
+            &PendingAdmin {
+                address: new_admin.clone(),
+                expiration_time,
+            },
+        );
+        event::propose_admin(&e, admin, new_admin, expiration_time);
+    }
+
+    fn accept_admin(e: Env) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        if is_pending_admin_expired(&e, &pending_admin) {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let new_admin = pending_admin.address;
+        new_admin.require_auth();
+
+        // the roles of the outgoing admin pass to the new admin, so the old key keeps none of them
+        let admin = read_admin(&e);
+        for role in ALL_ROLES {
+            if has_role(&e, role, &admin) {
+                write_role(&e, role, &admin, false);
+                write_role(&e, role, &new_admin, true);
+            }
+        }
         write_admin(&e, &new_admin);
+        remove_pending_admin(&e);
+        event::set_admin(&e, admin, new_admin);
+    }
+
+    fn cancel_admin_proposal(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        remove_pending_admin(&e);
+        event::cancel_admin_proposal(&e, admin, pending_admin.address);
+    }
+
+    fn pending_admin(e: Env) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        match read_pending_admin(&e) {
+            Some(pending_admin) if !is_pending_admin_expired(&e, &pending_admin) => {
+                Some(pending_admin.address)
+            }
+            _ => None,
+        }
+    }
+
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    fn migrate(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if read_version(&e) >= STORAGE_VERSION {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        // storage written before versioning predates roles, so the admin is granted the roles it held implicitly
+        if read_version(&e) < 1 {
+            for role in ALL_ROLES {
+                write_role(&e, role, &admin, true);
+            }
+        }
+        migrate(&e);
     }
 
-    fn set_fee_percent(e: Env, new_fee_percentage: u32) {
+    fn version(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_version(&e)
+    }
+
+    fn grant_role(e: Env, role: Role, account: Address) {
         let admin = read_admin(&e);
         admin.require_auth();
 
@@ -62,13 +193,101 @@ impl LiquidityPoolTrait for LiquidityPool {
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
-        write_fee_percent(&e, new_fee_percentage);
+        write_role(&e, role, &account, true);
+        event::grant_role(&e, admin, role, account);
     }
 
-    fn add_whitelisted_tc(e: Env, tc_address: Address) {
+    fn revoke_role(e: Env, role: Role, account: Address) {
         let admin = read_admin(&e);
         admin.require_auth();
 
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_role(&e, role, &account, false);
+        event::revoke_role(&e, admin, role, account);
+    }
+
+    fn has_role(e: Env, role: Role, account: Address) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        has_role(&e, role, &account)
+    }
+
+    fn pause(e: Env, caller: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&e, true);
+        event::pause(&e, caller);
+    }
+
+    fn unpause(e: Env, caller: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&e, false);
+        event::unpause(&e, caller);
+    }
+
+    fn is_paused(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        is_paused(&e)
+    }
+
+    fn set_exits_allowed(e: Env, caller: Address, allowed: bool) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_exits_allowed(&e, allowed);
+    }
+
+    fn exits_allowed(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_exits_allowed(&e)
+    }
+
+    fn set_fee_percent(e: Env, caller: Address, new_fee_bps: u32) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_fee_bps(&e, new_fee_bps);
+    }
+
+    fn set_interest_model(e: Env, caller: Address, interest_model: InterestModel) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_interest_model(&e, interest_model);
+    }
+
+    fn add_whitelisted_tc(e: Env, caller: Address, tc_address: Address) {
+        check_role(&e, Role::Compliance, &caller);
+        caller.require_auth();
+
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -81,9 +300,9 @@ impl LiquidityPoolTrait for LiquidityPool {
         write_whitelist(&e, tc_whitelist);
     }
 
-    fn remove_whitelisted_tc(e: Env, tc_address: Address) {
-        let admin = read_admin(&e);
-        admin.require_auth();
+    fn remove_whitelisted_tc(e: Env, caller: Address, tc_address: Address) {
+        check_role(&e, Role::Compliance, &caller);
+        caller.require_auth();
 
         e.storage()
             .instance()
@@ -106,40 +325,70 @@ impl LiquidityPoolTrait for LiquidityPool {
         whitelist.keys()
     }
 
-    fn create_loan_offer(e: Env, from: Address, tc_address: Address, tc_id: u64) -> u64 {
+    fn create_loan_offer(
+        e: Env,
+        from: Address,
+        tc_address: Address,
+        tc_id: u64,
+        term: u64,
+        principal: i128,
+    ) -> u64 {
         from.require_auth();
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
 
-        if !is_whitelisted(&e, tc_address.clone()) {
-            panic_with_error!(&e, Error::TCNotWhitelisted);
-        }
-        let offer_id = read_supply(&e);
-        let tc_amount = i128::from(tc_contract::Client::new(&e, &tc_address).get_amount(&tc_id));
+        let loan = new_loan(&e, from.clone(), tc_address, tc_id, term, principal);
         // lock in funds from caller (potential creditor)
-        transfer_scaled(&e, from.clone(), e.current_contract_address(), tc_amount, 0);
-        let request = Loan {
-            borrower: from.clone(),
-            creditor: from.clone(),
-            amount: i128::from(tc_amount),
+        transfer_scaled(&e, from, e.current_contract_address(), principal);
+        return store_loan_offer(&e, loan);
+    }
+
+    fn create_pool_loan_offer(
+        e: Env,
+        caller: Address,
+        tc_address: Address,
+        tc_id: u64,
+        term: u64,
+        principal: i128,
+    ) -> u64 {
+        check_role(&e, Role::Risk, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
+
+        // the smart contract is the creditor, lending from the pooled balance
+        let loan = new_loan(
+            &e,
+            e.current_contract_address(),
             tc_address,
             tc_id,
-            fee_percent: read_fee_percent(&e),
-            status: LoanStatus::Pending,
-        };
-
-        write_loan(&e, offer_id, request);
-        increment_supply(&e);
-        return offer_id;
+            term,
+            principal,
+        );
+        let amount = scale(&e, principal);
+        let balance = read_pool_balance(&e);
+        if amount > balance {
+            panic_with_error!(&e, Error::InsufficientLiquidity);
+        }
+        write_pool_balance(&e, balance - amount);
+        write_pool_lent(&e, read_pool_lent(&e) + amount);
+        return store_loan_offer(&e, loan);
     }
 
     fn cancel_loan_offer(e: Env, offer_id: u64) {
         let mut loan = read_loan(&e, offer_id);
+        if is_pool_loan(&e, &loan) {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
         loan.creditor.require_auth();
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
 
         if loan.status != LoanStatus::Pending {
             panic_with_error!(&e, Error::InvalidStatus);
@@ -150,19 +399,43 @@ impl LiquidityPoolTrait for LiquidityPool {
             &e,
             e.current_contract_address(),
             loan.creditor.clone(),
-            loan.amount,
-            0,
+            loan.principal,
         );
 
         loan.status = LoanStatus::Closed;
         write_loan(&e, offer_id, loan);
     }
 
+    fn cancel_pool_loan_offer(e: Env, caller: Address, offer_id: u64) {
+        check_role(&e, Role::Risk, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut loan = read_loan(&e, offer_id);
+        if !is_pool_loan(&e, &loan) {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        if loan.status != LoanStatus::Pending {
+            panic_with_error!(&e, Error::InvalidStatus);
+        }
+
+        // return the principal to the pooled balance
+        let amount = scale(&e, loan.principal);
+        write_pool_lent(&e, read_pool_lent(&e) - amount);
+        write_pool_balance(&e, read_pool_balance(&e) + amount);
+
+        loan.status = LoanStatus::Closed;
+        write_loan(&e, offer_id, loan);
+    }
+
     fn accept_loan_offer(e: Env, from: Address, offer_id: u64) {
         from.require_auth();
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
 
         let mut loan = read_loan(&e, offer_id);
         if loan.status != LoanStatus::Pending {
@@ -181,18 +454,26 @@ impl LiquidityPoolTrait for LiquidityPool {
             &e,
             e.current_contract_address(),
             from.clone(),
-            loan.amount,
-            0,
+            loan.principal,
         );
 
-        // update loan info
-        loan.borrower = from;
+        // update loan info, interest accrues from now on
+        loan.borrower = from.clone();
         loan.status = LoanStatus::Active;
+        loan.start_time = e.ledger().timestamp();
+        loan.outstanding_principal = scale(&e, loan.principal);
+        loan.accrued_interest = initial_interest(&e, &loan);
+        loan.accrual_time = loan.start_time;
+        loan.due_date = match loan.start_time.checked_add(loan.term) {
+            Some(due_date) => due_date,
+            None => panic_with_error!(&e, Error::IntegerOverflow),
+        };
+        event::accept_loan_offer(&e, from, offer_id, loan.principal, loan.collateral_value);
         write_loan(&e, offer_id, loan);
     }
 
     fn payoff_loan(e: Env, offer_id: u64) {
-        let mut loan = read_loan(&e, offer_id);
+        let loan = read_loan(&e, offer_id);
         if loan.status != LoanStatus::Active {
             panic_with_error!(&e, Error::InvalidStatus);
         }
@@ -200,32 +481,43 @@ impl LiquidityPoolTrait for LiquidityPool {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
 
-        // transfer liquidity tokens from caller (borrower) to creditor
-        // pool fee_percent is the additional percentage fee needed to pay off the loan.
-        transfer_scaled(
-            &e,
-            loan.borrower.clone(),
-            loan.creditor.clone(),
-            loan.amount,
-            loan.fee_percent,
-        );
+        // the whole outstanding balance is repaid
+        repay_loan(&e, offer_id, loan, i128::MAX);
+    }
 
-        // transfer the TC from smart contract to borrower
-        tc_contract::Client::new(&e, &loan.tc_address).transfer(
-            &e.current_contract_address(),
-            &loan.borrower,
-            &loan.tc_id,
-        );
+    fn repay(e: Env, offer_id: u64, amount: i128) -> i128 {
+        let loan = read_loan(&e, offer_id);
+        if loan.status != LoanStatus::Active {
+            panic_with_error!(&e, Error::InvalidStatus);
+        }
+        loan.borrower.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
 
-        // update loan info
-        loan.status = LoanStatus::Closed;
-        write_loan(&e, offer_id, loan);
+        if amount <= 0 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        repay_loan(&e, offer_id, loan, amount)
     }
 
-    fn default_loan(e: Env, offer_id: u64) {
-        let admin = read_admin(&e);
-        admin.require_auth();
+    fn get_outstanding(e: Env, offer_id: u64) -> i128 {
+        let mut loan = read_loan(&e, offer_id);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        if loan.status != LoanStatus::Active {
+            return 0;
+        }
+        accrue_interest(&e, &mut loan);
+        outstanding_amount(&loan)
+    }
+
+    fn default_loan(e: Env, caller: Address, offer_id: u64) {
+        caller.require_auth();
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -234,31 +526,358 @@ impl LiquidityPoolTrait for LiquidityPool {
             panic_with_error!(&e, Error::InvalidStatus);
         }
 
-        // transfer the TC from smart contract to creditor
-        tc_contract::Client::new(&e, &loan.tc_address).transfer(
-            &e.current_contract_address(),
-            &loan.creditor,
-            &loan.tc_id,
-        );
+        // anyone can declare a default, but only once the grace period after the due date has passed
+        let grace_end = loan.due_date.saturating_add(loan.grace_period);
+        if e.ledger().timestamp() <= grace_end {
+            panic_with_error!(&e, Error::LoanNotDue);
+        }
+
+        if is_pool_loan(&e, &loan) {
+            // the smart contract keeps the TC, and the principal stays in the pool's assets
+            // until liquidate_loan() realizes the recovery
+        } else if !holds_collateral(&e, &loan.creditor) {
+            // transfer the TC from smart contract to creditor, unless the creditor
+            // has the smart contract keep it for liquidation
+            tc_contract::Client::new(&e, &loan.tc_address).transfer(
+                &e.current_contract_address(),
+                &loan.creditor,
+                &loan.tc_id,
+            );
+        }
 
         // update loan info
         loan.status = LoanStatus::Defaulted;
         write_loan(&e, offer_id, loan);
     }
 
+    fn liquidate_loan(e: Env, offer_id: u64) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
+
+        let mut loan = read_loan(&e, offer_id);
+        if loan.status != LoanStatus::Defaulted {
+            panic_with_error!(&e, Error::InvalidStatus);
+        }
+        let tc_client = tc_contract::Client::new(&e, &loan.tc_address);
+        if tc_client.get_owner(&loan.tc_id) != e.current_contract_address() {
+            panic_with_error!(&e, Error::NotOwned);
+        }
+        if e.ledger().timestamp() < tc_client.get_redeem_time(&loan.tc_id) {
+            panic_with_error!(&e, Error::NotRedeemable);
+        }
+        // the TC must pay out in the external token of the pool
+        let ext_token = token::Client::new(&e, &read_ext_token(&e).address);
+        if tc_client.get_ext_token().0 != ext_token.address {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+
+        let balance = ext_token.balance(&e.current_contract_address());
+        tc_client.redeem(&e.current_contract_address(), &loan.tc_id);
+        let recovery = ext_token.balance(&e.current_contract_address()) - balance;
+
+        let fee = if is_pool_loan(&e, &loan) {
+            // the pool is the creditor and recovers the whole amount in place of the principal
+            write_pool_lent(&e, read_pool_lent(&e) - loan.outstanding_principal);
+            write_pool_balance(&e, read_pool_balance(&e) + recovery);
+            0
+        } else {
+            // the pool keeps its fee and the rest goes to the creditor
+            let fee = match recovery.checked_mul(i128::from(read_liquidation_fee_bps(&e))) {
+                Some(fee) => fee / 10000,
+                None => panic_with_error!(&e, Error::IntegerOverflow),
+            };
+            // without shareholders, the fee would go to whoever deposits first
+            if read_total_shares(&e) > 0 {
+                write_pool_balance(&e, read_pool_balance(&e) + fee);
+            } else {
+                write_treasury_fees(&e, read_treasury_fees(&e) + fee);
+            }
+            ext_token.transfer(
+                &e.current_contract_address(),
+                &loan.creditor,
+                &(recovery - fee),
+            );
+            fee
+        };
+
+        event::liquidate_loan(&e, loan.creditor.clone(), offer_id, recovery, fee);
+        loan.status = LoanStatus::Liquidated;
+        loan.recovery = recovery;
+        write_loan(&e, offer_id, loan);
+        recovery
+    }
+
+    fn set_hold_collateral(e: Env, creditor: Address, hold: bool) {
+        creditor.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_holds_collateral(&e, &creditor, hold);
+    }
+
+    fn holds_collateral(e: Env, creditor: Address) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        holds_collateral(&e, &creditor)
+    }
+
+    fn set_liquidation_fee_bps(e: Env, caller: Address, fee_bps: u32) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if fee_bps > 10000 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        write_liquidation_fee_bps(&e, fee_bps);
+    }
+
+    fn get_liquidation_fee_bps(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_liquidation_fee_bps(&e)
+    }
+
+    fn get_treasury_fees(e: Env) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_treasury_fees(&e)
+    }
+
+    fn withdraw_treasury_fees(e: Env, caller: Address, to: Address) -> i128 {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let amount = read_treasury_fees(&e);
+        if amount > 0 {
+            write_treasury_fees(&e, 0);
+            token::Client::new(&e, &read_ext_token(&e).address).transfer(
+                &e.current_contract_address(),
+                &to,
+                &amount,
+            );
+        }
+        event::withdraw_treasury_fees(&e, to, amount);
+        amount
+    }
+
+    fn get_loan_recovery(e: Env, offer_id: u64) -> i128 {
+        let loan = read_loan(&e, offer_id);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        loan.recovery
+    }
+
+    fn set_grace_period(e: Env, caller: Address, grace_period: u64) {
+        check_role(&e, Role::Risk, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_grace_period(&e, grace_period);
+    }
+
+    fn set_max_ltv_bps(e: Env, caller: Address, max_ltv_bps: u32) {
+        check_role(&e, Role::Risk, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if max_ltv_bps > 10000 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        write_max_ltv_bps(&e, max_ltv_bps);
+    }
+
+    fn get_max_ltv_bps(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_max_ltv_bps(&e)
+    }
+
+    fn get_grace_period(e: Env) -> u64 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_grace_period(&e)
+    }
+
+    fn set_share_token(e: Env, caller: Address, share_token: Address) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        // shares already minted would be stranded by a new token
+        if has_share_token(&e) {
+            panic_with_error!(&e, Error::NotEmpty);
+        }
+        write_share_token(&e, &share_token);
+    }
+
+    fn get_share_token(e: Env) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_share_token(&e)
+    }
+
+    fn deposit(e: Env, from: Address, amount: i128) -> i128 {
+        from.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
+
+        let share_token = match read_share_token(&e) {
+            Some(share_token) => share_token,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        if amount <= 0 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let total_shares = read_total_shares(&e);
+        let total_assets = read_total_assets(&e);
+        // nothing backs the shares of a pool that lost all of its assets
+        if total_shares > 0 && total_assets <= 0 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let shares = match shares_for_deposit(amount, total_shares, total_assets) {
+            Some(shares) => shares,
+            None => panic_with_error!(&e, Error::IntegerOverflow),
+        };
+        if shares == 0 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+
+        token::Client::new(&e, &read_ext_token(&e).address).transfer(
+            &from,
+            &e.current_contract_address(),
+            &amount,
+        );
+        token::StellarAssetClient::new(&e, &share_token).mint(&from, &shares);
+        write_pool_balance(&e, read_pool_balance(&e) + amount);
+        write_total_shares(&e, total_shares + shares);
+
+        event::deposit(&e, from, amount, shares);
+        shares
+    }
+
+    fn withdraw(e: Env, to: Address, shares: i128) -> i128 {
+        to.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
+
+        let share_token = match read_share_token(&e) {
+            Some(share_token) => share_token,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        let total_shares = read_total_shares(&e);
+        if shares <= 0 || shares > total_shares {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let amount = match amount_for_shares(shares, total_shares, read_total_assets(&e)) {
+            Some(amount) => amount,
+            None => panic_with_error!(&e, Error::IntegerOverflow),
+        };
+        // funds lent out cannot be withdrawn until they are paid back
+        let balance = read_pool_balance(&e);
+        if amount > balance {
+            panic_with_error!(&e, Error::InsufficientLiquidity);
+        }
+
+        token::Client::new(&e, &share_token).burn(&to, &shares);
+        write_total_shares(&e, total_shares - shares);
+        write_pool_balance(&e, balance - amount);
+        token::Client::new(&e, &read_ext_token(&e).address).transfer(
+            &e.current_contract_address(),
+            &to,
+            &amount,
+        );
+
+        event::withdraw(&e, to, shares, amount);
+        amount
+    }
+
+    fn get_total_shares(e: Env) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_total_shares(&e)
+    }
+
+    fn get_pool_balance(e: Env) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_pool_balance(&e)
+    }
+
+    fn get_total_assets(e: Env) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_total_assets(&e)
+    }
+
+    fn get_loan_due_date(e: Env, offer_id: u64) -> u64 {
+        let loan = read_loan(&e, offer_id);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        loan.due_date
+    }
+
     fn get_loan_fee(e: Env, offer_id: u64) -> u32 {
         let loan = read_loan(&e, offer_id);
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        loan.fee_percent
+        loan.fee_bps
     }
 
     fn get_pool_fee(e: Env) -> u32 {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        read_fee_percent(&e)
+        read_fee_bps(&e)
+    }
+
+    fn get_loan_interest_model(e: Env, offer_id: u64) -> InterestModel {
+        let loan = read_loan(&e, offer_id);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        loan.interest_model
+    }
+
+    fn get_interest_model(e: Env) -> InterestModel {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_interest_model(&e)
     }
 
     fn get_loan_tc(e: Env, offer_id: u64) -> (Address, u64) {
@@ -298,15 +917,7 @@ impl LiquidityPoolTrait for LiquidityPool {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let scaled_amount = calculate_scaled_amount_with_interest(
-            loan.amount,
-            read_ext_token(&e).decimals,
-            loan.fee_percent,
-        );
-        match scaled_amount {
-            Some(scaled_amount) => scaled_amount,
-            None => panic_with_error!(&e, Error::IntegerOverflow),
-        }
+        payoff_amount(&e, &loan)
     }
 
     fn get_loan_amount(e: Env, offer_id: u64) -> i128 {
@@ -314,7 +925,15 @@ impl LiquidityPoolTrait for LiquidityPool {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        loan.amount
+        loan.principal
+    }
+
+    fn get_loan_collateral_value(e: Env, offer_id: u64) -> i128 {
+        let loan = read_loan(&e, offer_id);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        loan.collateral_value
     }
 
     fn get_loan_status(e: Env, offer_id: u64) -> u32 {
@@ -326,28 +945,225 @@ impl LiquidityPoolTrait for LiquidityPool {
     }
 }
 
-fn transfer_scaled(e: &Env, from: Address, to: Address, amount: i128, added_percentage: u32) {
-    let ext_token = read_ext_token(&e);
-    let scaled_amount =
-        calculate_scaled_amount_with_interest(amount, ext_token.decimals, added_percentage);
+// Validates a loan offer for "principal" whole external tokens against the TC.
+// The caller is responsible for funding the offer before storing it.
+fn new_loan(
+    e: &Env,
+    creditor: Address,
+    tc_address: Address,
+    tc_id: u64,
+    term: u64,
+    principal: i128,
+) -> Loan {
+    if !is_whitelisted(e, tc_address.clone()) {
+        panic_with_error!(e, Error::TCNotWhitelisted);
+    }
+    if term == 0 {
+        panic_with_error!(e, Error::NotPermitted);
+    }
+    if principal <= 0 {
+        panic_with_error!(e, Error::NotPermitted);
+    }
+    let collateral_value = i128::from(tc_contract::Client::new(e, &tc_address).get_amount(&tc_id));
+    // the principal may be at most the pool's max LTV of the TC's value
+    match collateral_value.checked_mul(i128::from(read_max_ltv_bps(e))) {
+        Some(max_principal) if principal <= max_principal / 10000 => {}
+        _ => panic_with_error!(e, Error::LTVExceeded),
+    }
+    Loan {
+        borrower: creditor.clone(),
+        creditor,
+        principal,
+        collateral_value,
+        tc_address,
+        tc_id,
+        fee_bps: read_fee_bps(e),
+        status: LoanStatus::Pending,
+        interest_model: read_interest_model(e),
+        start_time: 0,
+        term,
+        grace_period: read_grace_period(e),
+        due_date: 0,
+        recovery: 0,
+        outstanding_principal: 0,
+        accrued_interest: 0,
+        accrual_time: 0,
+        interest_remainder: 0,
+    }
+}
+
+fn store_loan_offer(e: &Env, loan: Loan) -> u64 {
+    let offer_id = read_supply(e);
+    event::create_loan_offer(
+        e,
+        loan.creditor.clone(),
+        offer_id,
+        loan.principal,
+        loan.collateral_value,
+    );
+    write_loan(e, offer_id, loan);
+    increment_supply(e);
+    offer_id
+}
+
+// loans funded from the pooled balance have the smart contract as their creditor
+fn is_pool_loan(e: &Env, loan: &Loan) -> bool {
+    loan.creditor == e.current_contract_address()
+}
+
+// amount in the smallest unit of the external token
+pub(crate) fn scale(e: &Env, amount: i128) -> i128 {
+    match amount.checked_mul(10i128.pow(read_ext_token(e).decimals)) {
+        Some(scaled_amount) => scaled_amount,
+        None => panic_with_error!(e, Error::IntegerOverflow),
+    }
+}
+
+fn transfer_scaled(e: &Env, from: Address, to: Address, amount: i128) {
+    token::Client::new(e, &read_ext_token(e).address).transfer(&from, &to, &scale(e, amount));
+}
+
+// amount of external tokens needed to pay off a loan at the current ledger timestamp
+fn payoff_amount(e: &Env, loan: &Loan) -> i128 {
+    if loan.status == LoanStatus::Active {
+        let mut loan = loan.clone();
+        accrue_interest(e, &mut loan);
+        return outstanding_amount(&loan);
+    }
+    // interest only accrues while the loan is active
+    let scaled_amount = calculate_scaled_amount_with_interest(
+        loan.principal,
+        read_ext_token(e).decimals,
+        loan.fee_bps,
+        loan.interest_model,
+        0,
+    );
     match scaled_amount {
-        Some(scaled_amount) => {
-            token::Client::new(&e, &ext_token.address).transfer(&from, &to, &scaled_amount);
+        Some(scaled_amount) => scaled_amount,
+        None => panic_with_error!(e, Error::IntegerOverflow),
+    }
+}
+
+// interest owed as soon as a loan is accepted: the whole fee of a Flat loan, nothing for an Accrued loan
+pub(crate) fn initial_interest(e: &Env, loan: &Loan) -> i128 {
+    if loan.interest_model != InterestModel::Flat {
+        return 0;
+    }
+    let scaled_amount = calculate_scaled_amount_with_interest(
+        loan.principal,
+        read_ext_token(e).decimals,
+        loan.fee_bps,
+        InterestModel::Flat,
+        0,
+    );
+    match scaled_amount {
+        Some(scaled_amount) => scaled_amount - scale(e, loan.principal),
+        None => panic_with_error!(e, Error::IntegerOverflow),
+    }
+}
+
+// Adds the interest of an Accrued loan on its outstanding principal since the last accrual.
+// Fractions of a unit are carried in interest_remainder, so that they are only rounded up once, when the loan is settled.
+fn accrue_interest(e: &Env, loan: &mut Loan) {
+    let now = e.ledger().timestamp();
+    if loan.interest_model == InterestModel::Accrued {
+        let denominator = 10000 * i128::from(SECONDS_PER_YEAR);
+        let numerator = loan
+            .outstanding_principal
+            .checked_mul(i128::from(loan.fee_bps))
+            .and_then(|amount| {
+                amount.checked_mul(i128::from(now.saturating_sub(loan.accrual_time)))
+            })
+            .and_then(|amount| amount.checked_add(loan.interest_remainder));
+        let accrued_interest = numerator
+            .and_then(|numerator| loan.accrued_interest.checked_add(numerator / denominator));
+        match (numerator, accrued_interest) {
+            (Some(numerator), Some(accrued_interest)) => {
+                loan.accrued_interest = accrued_interest;
+                loan.interest_remainder = numerator % denominator;
+            }
+            _ => panic_with_error!(e, Error::IntegerOverflow),
         }
-        None => panic_with_error!(&e, Error::IntegerOverflow),
     }
+    loan.accrual_time = now;
+}
+
+// balance owed on an active loan, with the fraction of a unit of interest rounded up in the creditor's favour
+fn outstanding_amount(loan: &Loan) -> i128 {
+    loan.outstanding_principal + loan.accrued_interest + i128::from(loan.interest_remainder > 0)
 }
 
-fn calculate_scaled_amount_with_interest(
+// Transfers up to "amount" of the outstanding balance from the borrower to the creditor, interest first.
+// Closes the loan and returns the TC once nothing is outstanding. Returns the outstanding balance left.
+fn repay_loan(e: &Env, offer_id: u64, mut loan: Loan, amount: i128) -> i128 {
+    accrue_interest(e, &mut loan);
+    let amount = amount.min(outstanding_amount(&loan));
+    let principal_paid =
+        (amount - amount.min(loan.accrued_interest)).min(loan.outstanding_principal);
+    let interest_paid = amount - principal_paid;
+
+    // transfer liquidity tokens from caller (borrower) to creditor
+    token::Client::new(e, &read_ext_token(e).address).transfer(
+        &loan.borrower,
+        &loan.creditor,
+        &amount,
+    );
+    if interest_paid > loan.accrued_interest {
+        // the fraction of a unit was rounded up and paid with the rest of the balance
+        loan.interest_remainder = 0;
+    }
+    loan.accrued_interest = (loan.accrued_interest - interest_paid).max(0);
+    loan.outstanding_principal -= principal_paid;
+
+    // the principal and interest of pool loans return to the pooled balance
+    if is_pool_loan(e, &loan) {
+        write_pool_lent(e, read_pool_lent(e) - principal_paid);
+        write_pool_balance(e, read_pool_balance(e) + amount);
+    }
+
+    let outstanding = outstanding_amount(&loan);
+    if outstanding == 0 {
+        // transfer the TC from smart contract to borrower
+        tc_contract::Client::new(e, &loan.tc_address).transfer(
+            &e.current_contract_address(),
+            &loan.borrower,
+            &loan.tc_id,
+        );
+        loan.status = LoanStatus::Closed;
+    }
+
+    event::repay(
+        e,
+        loan.borrower.clone(),
+        offer_id,
+        interest_paid,
+        principal_paid,
+        outstanding,
+    );
+    write_loan(e, offer_id, loan);
+    outstanding
+}
+
+// Scales "amount" to the external token's decimals and adds the interest at "fee_bps".
+// A Flat fee is charged once, an Accrued fee is an annual rate charged pro rata over "elapsed" seconds.
+// The interest is rounded up, so that the creditor never receives less than the rate.
+pub(crate) fn calculate_scaled_amount_with_interest(
     amount: i128,
     decimals: u32,
-    added_percentage: u32,
+    fee_bps: u32,
+    interest_model: InterestModel,
+    elapsed: u64,
 ) -> Option<i128> {
-    if added_percentage == 0 {
-        return amount.checked_mul(10i128.pow(decimals));
-    }
-    amount
-        .checked_mul(10i128.pow(decimals))?
-        .checked_mul(100 + i128::from(added_percentage))?
-        .checked_div(100)
+    let scaled_amount = amount.checked_mul(10i128.checked_pow(decimals)?)?;
+    let (numerator, denominator) = match interest_model {
+        InterestModel::Flat => (scaled_amount.checked_mul(i128::from(fee_bps))?, 10000),
+        InterestModel::Accrued => (
+            scaled_amount
+                .checked_mul(i128::from(fee_bps))?
+                .checked_mul(i128::from(elapsed))?,
+            10000 * i128::from(SECONDS_PER_YEAR),
+        ),
+    };
+    let interest = numerator.checked_add(denominator - 1)? / denominator;
+    scaled_amount.checked_add(interest)
 }
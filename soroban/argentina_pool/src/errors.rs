@@ -12,4 +12,9 @@ pub enum Error {
     InvalidStatus = 6,
     IntegerOverflow = 7,
     TCNotWhitelisted = 8,
+    Paused = 9,
+    LoanNotDue = 10,
+    LTVExceeded = 11,
+    InsufficientLiquidity = 12,
+    NotRedeemable = 13,
 }
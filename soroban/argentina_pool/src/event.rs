@@ -0,0 +1,101 @@
+use crate::storage_types::Role;
+use soroban_sdk::{symbol_short, Address, Env};
+
+pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("set_admin"), admin);
+    e.events().publish(topics, new_admin);
+}
+
+pub(crate) fn propose_admin(
+    e: &Env,
+    admin: Address,
+    new_admin: Address,
+    expiration_time: Option<u64>,
+) {
+    let topics = (symbol_short!("propose"), admin);
+    e.events().publish(topics, (new_admin, expiration_time));
+}
+
+pub(crate) fn cancel_admin_proposal(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("unpropose"), admin);
+    e.events().publish(topics, new_admin);
+}
+
+pub(crate) fn grant_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("grant"), role, account);
+    e.events().publish(topics, admin);
+}
+
+pub(crate) fn revoke_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("revoke"), role, account);
+    e.events().publish(topics, admin);
+}
+
+pub(crate) fn pause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("pause"), caller);
+    e.events().publish(topics, ());
+}
+
+pub(crate) fn unpause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("unpause"), caller);
+    e.events().publish(topics, ());
+}
+
+pub(crate) fn create_loan_offer(
+    e: &Env,
+    creditor: Address,
+    offer_id: u64,
+    principal: i128,
+    collateral_value: i128,
+) {
+    let topics = (symbol_short!("offer"), creditor);
+    e.events()
+        .publish(topics, (offer_id, principal, collateral_value));
+}
+
+pub(crate) fn accept_loan_offer(
+    e: &Env,
+    borrower: Address,
+    offer_id: u64,
+    principal: i128,
+    collateral_value: i128,
+) {
+    let topics = (symbol_short!("accept"), borrower);
+    e.events()
+        .publish(topics, (offer_id, principal, collateral_value));
+}
+
+pub(crate) fn deposit(e: &Env, from: Address, amount: i128, shares: i128) {
+    let topics = (symbol_short!("deposit"), from);
+    e.events().publish(topics, (amount, shares));
+}
+
+pub(crate) fn withdraw(e: &Env, to: Address, shares: i128, amount: i128) {
+    let topics = (symbol_short!("withdraw"), to);
+    e.events().publish(topics, (shares, amount));
+}
+
+pub(crate) fn liquidate_loan(e: &Env, creditor: Address, offer_id: u64, recovery: i128, fee: i128) {
+    let topics = (symbol_short!("liquidate"), creditor);
+    e.events().publish(topics, (offer_id, recovery, fee));
+}
+
+pub(crate) fn withdraw_treasury_fees(e: &Env, to: Address, amount: i128) {
+    let topics = (symbol_short!("fees"), to);
+    e.events().publish(topics, amount);
+}
+
+pub(crate) fn repay(
+    e: &Env,
+    borrower: Address,
+    offer_id: u64,
+    interest_paid: i128,
+    principal_paid: i128,
+    outstanding: i128,
+) {
+    let topics = (symbol_short!("repay"), borrower);
+    e.events().publish(
+        topics,
+        (offer_id, interest_paid, principal_paid, outstanding),
+    );
+}
@@ -1,4 +1,6 @@
-use soroban_sdk::{Address, Env, Vec};
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+use crate::{loan::InterestModel, storage_types::Role};
 
 pub trait LiquidityPoolTrait {
     // --------------------------------------------------------------------------------
@@ -7,52 +9,230 @@ pub trait LiquidityPoolTrait {
 
     /// Initialize the contract with "admin" as administrator.
     /// ext_token_address specifies a token to exchange for the TCs. The ext token does not necessarily need to be the same as the TC's ext token; for example, it could be a liquidity pool token.
-    /// fee_percent is the default fee for paying off loans, expressed as a percentage.
-    fn initialize(e: Env, admin: Address, ext_token_address: Address, fee_percent: u32);
+    /// fee_bps is the default fee for paying off loans, expressed in basis points.
+    fn initialize(e: Env, admin: Address, ext_token_address: Address, fee_bps: u32);
+
+    /// Propose "new_admin" as the next administrator. Can only be called by the admin. Replaces any pending proposal.
+    /// If "expiration_time" is set, the proposal can only be accepted until that Unix timestamp.
+    /// Emit event with topics = ["propose", admin: Address], data = [new_admin: Address, expiration_time: Option<u64>]
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>);
 
-    /// If "admin" is the administrator, set the administrator to "new_admin".
+    /// Called by the proposed administrator to become the administrator. The roles held by the previous administrator are moved to the new one.
     /// Emit event with topics = ["set_admin", admin: Address], data = [new_admin: Address]
-    fn set_admin(e: Env, new_admin: Address);
+    fn accept_admin(e: Env);
+
+    /// Withdraw the pending administrator proposal. Can only be called by the admin.
+    /// Emit event with topics = ["unpropose", admin: Address], data = [new_admin: Address]
+    fn cancel_admin_proposal(e: Env);
+
+    /// Get the proposed administrator, if a proposal is pending and has not expired.
+    fn pending_admin(e: Env) -> Option<Address>;
+
+    /// Replace the contract code with the uploaded WASM identified by "new_wasm_hash". Can only be called by the admin.
+    /// Storage is kept as is; call migrate() afterwards if the new code uses a newer storage layout.
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>);
+
+    /// Rewrite storage written by an older version of the contract to the current layout. Can only be called by the admin.
+    /// Will fail if the storage is already up to date.
+    fn migrate(e: Env);
+
+    /// Get the version of the storage layout.
+    fn version(e: Env) -> u32;
+
+    /// Grant "role" to "account". Can only be called by the admin. The admin holds every role after initialization.
+    /// Emit event with topics = ["grant", role: Role, account: Address], data = [admin: Address]
+    fn grant_role(e: Env, role: Role, account: Address);
+
+    /// Revoke "role" from "account". Can only be called by the admin.
+    /// Emit event with topics = ["revoke", role: Role, account: Address], data = [admin: Address]
+    fn revoke_role(e: Env, role: Role, account: Address);
+
+    /// Check whether "account" holds "role".
+    fn has_role(e: Env, role: Role, account: Address) -> bool;
+
+    /// Stop the state-changing entry points until unpause() is called. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["pause", caller: Address], data = []
+    fn pause(e: Env, caller: Address);
 
-    /// Set the additional amount percentage that must be paid back to close a loan.
-    fn set_fee_percent(e: Env, new_fee_percentage: u32);
+    /// Resume normal operation after a pause. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["unpause", caller: Address], data = []
+    fn unpause(e: Env, caller: Address);
+
+    /// Check whether the contract is paused.
+    fn is_paused(e: Env) -> bool;
+
+    /// Set whether cancel_loan_offer() and payoff_loan() remain available while the contract is paused. "caller" must hold the Guardian role.
+    /// Exits are allowed by default, so that users can always withdraw their funds.
+    fn set_exits_allowed(e: Env, caller: Address, allowed: bool);
+
+    /// Check whether cancel_loan_offer() and payoff_loan() remain available while the contract is paused.
+    fn exits_allowed(e: Env) -> bool;
+
+    /// Set the fee, in basis points, that must be paid back on top of the amount to close a loan. "caller" must hold the Treasury role.
+    fn set_fee_percent(e: Env, caller: Address, new_fee_bps: u32);
+
+    /// Set whether the fee of new loans is charged once (Flat) or as an annual rate accrued from acceptance (Accrued). "caller" must hold the Treasury role.
+    fn set_interest_model(e: Env, caller: Address, interest_model: InterestModel);
 
     /// Whitelist a TC contract address to use for loans. Does nothing if the whitelist is already whitelisted.
-    fn add_whitelisted_tc(e: Env, tc_addr: Address);
+    /// "caller" must hold the Compliance role.
+    fn add_whitelisted_tc(e: Env, caller: Address, tc_addr: Address);
 
     /// Remove a TC contract address from the whitelist. Does nothing if the address is not in the whitelist.
-    fn remove_whitelisted_tc(e: Env, tc_addr: Address);
+    /// "caller" must hold the Compliance role.
+    fn remove_whitelisted_tc(e: Env, caller: Address, tc_addr: Address);
 
     /// Call this function to transfer the TC to the creditor when a borrower has defaulted on a loan.
-    fn default_loan(e: Env, offer_id: u64);
+    /// The smart contract keeps the TC of a loan funded from the pooled balance, and the principal remains in the pool's total assets until the TC is liquidated.
+    /// It also keeps the TC if the creditor has chosen so with set_hold_collateral().
+    /// Anyone can call it, once the loan's grace period after its due date has passed.
+    fn default_loan(e: Env, caller: Address, offer_id: u64);
+
+    /// Redeem the TC of a defaulted loan held by the smart contract, once the TC's redeem time has passed. Anyone can call this function.
+    /// The redeemed amount is recorded as the loan's recovery. For loans funded from the pooled balance, it is added to the pooled balance in place of the loan's principal.
+    /// Otherwise the liquidation fee is added to the pooled balance, or kept for the Treasury role while no shares are in circulation, and the rest is transferred to the creditor. Returns the recovery.
+    /// Emit event with topics = ["liquidate", creditor: Address], data = [offer_id: u64, recovery: i128, fee: i128]
+    fn liquidate_loan(e: Env, offer_id: u64) -> i128;
+
+    /// Choose whether the smart contract keeps the TC of the creditor's defaulted loans for liquidate_loan(), instead of transferring it to the creditor.
+    fn set_hold_collateral(e: Env, creditor: Address, hold: bool);
+
+    /// Get whether the smart contract keeps the TC of the creditor's defaulted loans.
+    fn holds_collateral(e: Env, creditor: Address) -> bool;
+
+    /// Set the fee, in basis points of the recovery, that the pool keeps when liquidating a creditor's loan. "caller" must hold the Treasury role.
+    /// Fails if "fee_bps" is greater than 10000.
+    fn set_liquidation_fee_bps(e: Env, caller: Address, fee_bps: u32);
+
+    /// Get the fee, in basis points of the recovery, that the pool keeps when liquidating a creditor's loan.
+    fn get_liquidation_fee_bps(e: Env) -> u32;
+
+    /// Get the liquidation fees collected while the pool had no shares in circulation, in the smallest unit of the liquidity token.
+    fn get_treasury_fees(e: Env) -> i128;
+
+    /// Transfer the liquidation fees collected while the pool had no shares in circulation to "to", and return the amount.
+    /// "caller" must hold the Treasury role.
+    /// Emit event with topics = ["fees", to: Address], data = [amount: i128]
+    fn withdraw_treasury_fees(e: Env, caller: Address, to: Address) -> i128;
+
+    /// Set the time in seconds after a loan's due date before anyone can default it. "caller" must hold the Risk role.
+    /// Loans keep the grace period in effect when their offer was created.
+    fn set_grace_period(e: Env, caller: Address, grace_period: u64);
+
+    /// Get the time in seconds after a loan's due date before anyone can default it.
+    fn get_grace_period(e: Env) -> u64;
+
+    /// Set the maximum loan-to-value ratio of new loans, in basis points of the TC's value. "caller" must hold the Risk role.
+    /// Fails if "max_ltv_bps" is greater than 10000.
+    fn set_max_ltv_bps(e: Env, caller: Address, max_ltv_bps: u32);
+
+    /// Get the maximum loan-to-value ratio of new loans, in basis points of the TC's value.
+    fn get_max_ltv_bps(e: Env) -> u32;
 
     // --------------------------------------------------------------------------------
     // Pool interface
     // --------------------------------------------------------------------------------
 
-    /// Create a loan offer against a TC. The caller (creditor) transfers liquidity tokens to the smart contract equal to the value of the TC.
-    /// The loan will use the liquidity pool's fee percentage at the time of the offer being created
-    fn create_loan_offer(e: Env, from: Address, tc_addr: Address, tc_id: u64) -> u64;
+    /// Create a loan offer against a TC. The caller (creditor) transfers liquidity tokens equal to "principal" to the smart contract.
+    /// "principal" is expressed in whole liquidity tokens, like the value of the TC, and can be at most the pool's max LTV of the TC's value.
+    /// The loan will use the liquidity pool's fee, interest model and grace period at the time of the offer being created
+    /// "term" is the time in seconds from acceptance until the loan is due, and must be greater than 0.
+    /// Emit event with topics = ["offer", from: Address], data = [offer_id: u64, principal: i128, collateral_value: i128]
+    fn create_loan_offer(
+        e: Env,
+        from: Address,
+        tc_addr: Address,
+        tc_id: u64,
+        term: u64,
+        principal: i128,
+    ) -> u64;
+
+    /// Create a loan offer against a TC, funded from the pooled balance instead of a creditor. "caller" must hold the Risk role.
+    /// The smart contract is the creditor of the loan, and fails if the pooled balance is less than "principal". Otherwise works like create_loan_offer().
+    fn create_pool_loan_offer(
+        e: Env,
+        caller: Address,
+        tc_addr: Address,
+        tc_id: u64,
+        term: u64,
+        principal: i128,
+    ) -> u64;
 
     /// Cancel a loan offer. Caller must be the user who created the request (creditor).
     /// Transfers the liquidity tokens back to the caller.
     fn cancel_loan_offer(e: Env, offer_id: u64);
 
+    /// Cancel a loan offer funded from the pooled balance, returning the principal to it. "caller" must hold the Risk role.
+    fn cancel_pool_loan_offer(e: Env, caller: Address, offer_id: u64);
+
     /// Accept a loan offer. The caller (borrower) must own the TC.
-    /// Transfers the TC to the creditor, and liquidity tokens equal to the associated TC's value are sent from the smart contract to the caller.
+    /// Transfers the TC to the smart contract, and liquidity tokens equal to the loan's principal are sent from the smart contract to the caller.
+    /// The loan is due "term" seconds from now.
+    /// Emit event with topics = ["accept", from: Address], data = [offer_id: u64, principal: i128, collateral_value: i128]
     fn accept_loan_offer(e: Env, from: Address, offer_id: u64);
 
     /// Pay off a loan by returning the TC from the smart contract to the borrower, then sending the liquidity tokens from the borrower back to the creditor.
-    /// If the contract's fee percentage is greater than 0, the amount of liquidity tokens required to pay off is higher than the original amount.
-    /// The loan offer must be accepted prior to this step.
+    /// If the loan's fee is greater than 0, the amount of liquidity tokens required to pay off, as returned by get_payoff_amount(), is higher than the original amount.
+    /// The loan offer must be accepted prior to this step, and the loan must not have been defaulted.
+    /// For loans funded from the pooled balance, the amount paid off, including interest, is added to the pooled balance.
+    /// Works like repay() with the whole outstanding balance.
     fn payoff_loan(e: Env, offer_id: u64);
 
-    /// Get the fee percentage associated with a loan.
+    /// Repay part of a loan by sending up to "amount" liquidity tokens, in their smallest unit, from the borrower to the creditor.
+    /// Payments are applied to the interest owed first, then to the principal. Interest of an Accrued loan accrues on the outstanding principal only,
+    /// so repaying early lowers the interest owed. Once nothing is outstanding, the loan is closed and the TC is returned to the borrower.
+    /// Returns the outstanding balance left.
+    /// Emit event with topics = ["repay", borrower: Address], data = [offer_id: u64, interest_paid: i128, principal_paid: i128, outstanding: i128]
+    fn repay(e: Env, offer_id: u64, amount: i128) -> i128;
+
+    /// Get the outstanding balance of an active loan at the current ledger timestamp: the principal and interest still owed, in the smallest unit of the liquidity token.
+    /// Returns 0 for loans that are not active.
+    fn get_outstanding(e: Env, offer_id: u64) -> i128;
+
+    /// Set the token contract whose tokens represent shares of the pooled liquidity. "caller" must hold the Treasury role.
+    /// The smart contract must be the admin of the share token, so that it can mint shares. Can only be set once.
+    fn set_share_token(e: Env, caller: Address, share_token: Address);
+
+    /// Get the token contract whose tokens represent shares of the pooled liquidity, if set.
+    fn get_share_token(e: Env) -> Option<Address>;
+
+    /// Deposit "amount" of liquidity tokens, in their smallest unit, into the pooled balance. Returns the number of shares minted to "from".
+    /// Shares are minted at the current share price, the total assets of the pool divided by the total shares, rounded down.
+    /// Emit event with topics = ["deposit", from: Address], data = [amount: i128, shares: i128]
+    fn deposit(e: Env, from: Address, amount: i128) -> i128;
+
+    /// Burn "shares" of "to" and transfer their value in liquidity tokens, rounded down, from the pooled balance to "to". Returns the amount transferred.
+    /// Fails if the pooled balance is less than the amount, because it is lent out.
+    /// Emit event with topics = ["withdraw", to: Address], data = [shares: i128, amount: i128]
+    fn withdraw(e: Env, to: Address, shares: i128) -> i128;
+
+    /// Get the number of shares of the pooled liquidity in circulation.
+    fn get_total_shares(e: Env) -> i128;
+
+    /// Get the pooled balance of liquidity tokens, in their smallest unit, available to lend or withdraw.
+    fn get_pool_balance(e: Env) -> i128;
+
+    /// Get the value backing the shares: the pooled balance plus the principal of pending, active and unliquidated defaulted loans funded from it, in the smallest unit of the liquidity token.
+    fn get_total_assets(e: Env) -> i128;
+
+    /// Get the Unix timestamp at which a loan is due, or 0 if the offer has not been accepted yet.
+    fn get_loan_due_date(e: Env, offer_id: u64) -> u64;
+
+    /// Get the amount of liquidity tokens, in their smallest unit, recovered by liquidating a defaulted loan.
+    fn get_loan_recovery(e: Env, offer_id: u64) -> i128;
+
+    /// Get the fee associated with a loan, in basis points.
     fn get_loan_fee(e: Env, offer_id: u64) -> u32;
 
-    /// Get the loan smart contract's current fee percentage.
+    /// Get the loan smart contract's current fee, in basis points.
     fn get_pool_fee(e: Env) -> u32;
 
+    /// Get the interest model associated with a loan.
+    fn get_loan_interest_model(e: Env, offer_id: u64) -> InterestModel;
+
+    /// Get the loan smart contract's current interest model.
+    fn get_interest_model(e: Env) -> InterestModel;
+
     /// Get the contract address and TC id associated with a loan.
     fn get_loan_tc(e: Env, offer_id: u64) -> (Address, u64);
 
@@ -68,9 +248,12 @@ pub trait LiquidityPoolTrait {
     /// Get the amount required to successfully pay off the loan.
     fn get_payoff_amount(e: Env, offer_id: u64) -> i128;
 
-    /// Get the base amount of the loan
+    /// Get the principal of the loan, the base amount lent to the borrower
     fn get_loan_amount(e: Env, offer_id: u64) -> i128;
 
+    /// Get the value of the TC pledged for the loan, at the time the offer was created
+    fn get_loan_collateral_value(e: Env, offer_id: u64) -> i128;
+
     /// Get the status of a loan
     fn get_loan_status(e: Env, offer_id: u64) -> u32;
 
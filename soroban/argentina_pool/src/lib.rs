@@ -3,11 +3,16 @@
 mod admin;
 mod contract;
 mod errors;
+mod event;
 mod ext_token;
 mod interface;
 mod loan;
+mod pause;
+mod pool;
+mod roles;
 mod storage_types;
 mod test;
 mod test_util;
+mod upgrade;
 
 pub use crate::contract::LiquidityPool;
@@ -1,6 +1,8 @@
 use crate::{
     errors::Error,
-    storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD},
+    storage_types::{
+        DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD, DEFAULT_GRACE_PERIOD,
+    },
 };
 use soroban_sdk::{contracttype, panic_with_error, Address, Env, Map};
 
@@ -12,6 +14,18 @@ pub enum LoanStatus {
     Active = 1,
     Closed = 2,
     Defaulted = 3,
+    // defaulted, and the TC has been redeemed by the smart contract
+    Liquidated = 4,
+}
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum InterestModel {
+    // the fee is charged once, however long the loan runs
+    Flat = 0,
+    // the fee is an annual rate, accrued from the time the loan offer is accepted
+    Accrued = 1,
 }
 
 #[derive(Clone)]
@@ -19,26 +33,123 @@ pub enum LoanStatus {
 pub struct Loan {
     pub borrower: Address,
     pub creditor: Address,
-    pub amount: i128,
+    // amount lent to the borrower, in whole external tokens
+    pub principal: i128,
+    // value of the pledged TC when the offer was created, in whole external tokens
+    pub collateral_value: i128,
     pub tc_address: Address,
     pub tc_id: u64,
-    pub fee_percent: u32,
+    // fee in basis points, charged as set by interest_model
+    pub fee_bps: u32,
     pub status: LoanStatus,
+    pub interest_model: InterestModel,
+    // Unix timestamp at which the loan offer was accepted
+    pub start_time: u64,
+    // time in seconds from acceptance until the loan is due
+    pub term: u64,
+    // time in seconds after the due date before anyone can default the loan
+    pub grace_period: u64,
+    // Unix timestamp at which the loan is due, 0 until the offer is accepted
+    pub due_date: u64,
+    // amount of external tokens, in their smallest unit, recovered by redeeming the TC after a default
+    pub recovery: i128,
+    // principal still owed, in the smallest unit of the external token
+    pub outstanding_principal: i128,
+    // interest owed up to accrual_time, in the smallest unit of the external token
+    pub accrued_interest: i128,
+    // Unix timestamp up to which interest has been added to accrued_interest
+    pub accrual_time: u64,
+    // fraction of a unit of interest accrued on top of accrued_interest, in units of 1 / (10000 * SECONDS_PER_YEAR)
+    pub interest_remainder: i128,
 }
 
-pub fn write_fee_percent(e: &Env, fee_percent: u32) {
-    let key = DataKey::FeePercent;
-    e.storage().instance().set(&key, &fee_percent);
+pub fn write_fee_bps(e: &Env, fee_bps: u32) {
+    let key = DataKey::FeeBps;
+    e.storage().instance().set(&key, &fee_bps);
 }
 
-pub fn read_fee_percent(e: &Env) -> u32 {
-    let key = DataKey::FeePercent;
+pub fn read_fee_bps(e: &Env) -> u32 {
+    let key = DataKey::FeeBps;
     match e.storage().instance().get::<DataKey, u32>(&key) {
-        Some(fee_percent) => fee_percent,
+        Some(fee_bps) => fee_bps,
         None => 0,
     }
 }
 
+pub fn write_interest_model(e: &Env, interest_model: InterestModel) {
+    let key = DataKey::InterestModel;
+    e.storage().instance().set(&key, &interest_model);
+}
+
+pub fn read_interest_model(e: &Env) -> InterestModel {
+    let key = DataKey::InterestModel;
+    e.storage()
+        .instance()
+        .get(&key)
+        .unwrap_or(InterestModel::Flat)
+}
+
+pub fn write_grace_period(e: &Env, grace_period: u64) {
+    let key = DataKey::GracePeriod;
+    e.storage().instance().set(&key, &grace_period);
+}
+
+pub fn read_grace_period(e: &Env) -> u64 {
+    let key = DataKey::GracePeriod;
+    match e.storage().instance().get::<DataKey, u64>(&key) {
+        Some(grace_period) => grace_period,
+        None => DEFAULT_GRACE_PERIOD,
+    }
+}
+
+pub fn write_max_ltv_bps(e: &Env, max_ltv_bps: u32) {
+    let key = DataKey::MaxLtvBps;
+    e.storage().instance().set(&key, &max_ltv_bps);
+}
+
+pub fn read_max_ltv_bps(e: &Env) -> u32 {
+    let key = DataKey::MaxLtvBps;
+    // loans may lend the full value of the TC unless a cap is set
+    e.storage().instance().get(&key).unwrap_or(10000)
+}
+
+pub fn write_liquidation_fee_bps(e: &Env, fee_bps: u32) {
+    let key = DataKey::LiquidationFeeBps;
+    e.storage().instance().set(&key, &fee_bps);
+}
+
+pub fn read_liquidation_fee_bps(e: &Env) -> u32 {
+    let key = DataKey::LiquidationFeeBps;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_holds_collateral(e: &Env, creditor: &Address, hold: bool) {
+    let key = DataKey::HoldsCollateral(creditor.clone());
+    if !hold {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage().persistent().set(&key, &hold);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn holds_collateral(e: &Env, creditor: &Address) -> bool {
+    let key = DataKey::HoldsCollateral(creditor.clone());
+    match e.storage().persistent().get::<DataKey, bool>(&key) {
+        Some(hold) => {
+            e.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            hold
+        }
+        None => false,
+    }
+}
+
 pub fn write_loan(e: &Env, offer_id: u64, loan: Loan) {
     let key = DataKey::Loan(offer_id);
     e.storage().persistent().set(&key, &loan);
@@ -0,0 +1,83 @@
+use soroban_sdk::{Address, Env};
+
+use crate::storage_types::DataKey;
+
+// The pooled liquidity is tracked in the smallest unit of the external token.
+// "balance" is held by the smart contract and available to lend or withdraw,
+// "lent" is the principal of pool loans that are pending, active, or defaulted
+// and not liquidated yet.
+
+pub fn has_share_token(e: &Env) -> bool {
+    let key = DataKey::ShareToken;
+    e.storage().instance().has(&key)
+}
+
+pub fn write_share_token(e: &Env, share_token: &Address) {
+    let key = DataKey::ShareToken;
+    e.storage().instance().set(&key, share_token);
+}
+
+pub fn read_share_token(e: &Env) -> Option<Address> {
+    let key = DataKey::ShareToken;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_total_shares(e: &Env, total_shares: i128) {
+    let key = DataKey::TotalShares;
+    e.storage().instance().set(&key, &total_shares);
+}
+
+pub fn read_total_shares(e: &Env) -> i128 {
+    let key = DataKey::TotalShares;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_pool_balance(e: &Env, balance: i128) {
+    let key = DataKey::PoolBalance;
+    e.storage().instance().set(&key, &balance);
+}
+
+pub fn read_pool_balance(e: &Env) -> i128 {
+    let key = DataKey::PoolBalance;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_pool_lent(e: &Env, lent: i128) {
+    let key = DataKey::PoolLent;
+    e.storage().instance().set(&key, &lent);
+}
+
+pub fn read_pool_lent(e: &Env) -> i128 {
+    let key = DataKey::PoolLent;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+// liquidation fees collected while the pool had no shareholders, held for the Treasury role
+pub fn write_treasury_fees(e: &Env, amount: i128) {
+    let key = DataKey::TreasuryFees;
+    e.storage().instance().set(&key, &amount);
+}
+
+pub fn read_treasury_fees(e: &Env) -> i128 {
+    let key = DataKey::TreasuryFees;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+// value of the pool that backs the shares
+pub fn read_total_assets(e: &Env) -> i128 {
+    read_pool_balance(e) + read_pool_lent(e)
+}
+
+// Shares minted for depositing "amount", rounded down in favour of the existing shareholders.
+// The first deposit mints one share per unit of the external token.
+pub fn shares_for_deposit(amount: i128, total_shares: i128, total_assets: i128) -> Option<i128> {
+    if total_shares == 0 {
+        return Some(amount);
+    }
+    amount.checked_mul(total_shares)?.checked_div(total_assets)
+}
+
+// Amount paid out for burning "shares", rounded down in favour of the remaining shareholders.
+pub fn amount_for_shares(shares: i128, total_shares: i128, total_assets: i128) -> Option<i128> {
+    shares.checked_mul(total_assets)?.checked_div(total_shares)
+}
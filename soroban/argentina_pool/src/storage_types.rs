@@ -7,6 +7,17 @@ pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_I
 pub(crate) const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+pub(crate) const ROLE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ROLE_LIFETIME_THRESHOLD: u32 = ROLE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+// version of the storage layout, to be bumped along with a migration step whenever the layout changes
+pub(crate) const STORAGE_VERSION: u32 = 2;
+
+// default time after a loan's due date before anyone can default it, in seconds
+pub(crate) const DEFAULT_GRACE_PERIOD: u64 = 30 * 86400;
+
 #[derive(Clone)]
 #[contracttype]
 pub struct TokenInfo {
@@ -14,13 +25,54 @@ pub struct TokenInfo {
     pub decimals: u32,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub address: Address,
+    // Unix timestamp after which the proposal can no longer be accepted
+    pub expiration_time: Option<u64>,
+}
+
+// privileged duties that the admin can hand out to separate accounts
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    // unused by the pool, kept so role ids match the TC contracts
+    Minter = 0,
+    // keeps the whitelist of accepted TC contracts
+    Compliance = 1,
+    // sets fees, the interest model and the share token
+    Treasury = 2,
+    // sets the grace period and max loan-to-value, and underwrites pool loans
+    Risk = 3,
+    // pauses the contract and controls exits
+    Guardian = 4,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Admin,
     ExtToken,
     Supply,
-    FeePercent,
+    FeePercent, // pool fee in whole percent, replaced by FeeBps in version 2
     Loan(u64),
     TCWhiteList,
+    Version,
+    PendingAdmin,
+    Role(Role, Address),
+    Paused,
+    ExitsAllowed,
+    FeeBps,
+    InterestModel,
+    GracePeriod,
+    MaxLtvBps,
+    ShareToken,
+    TotalShares,
+    PoolBalance,
+    PoolLent,
+    LiquidationFeeBps,
+    HoldsCollateral(Address),
+    TreasuryFees,
 }
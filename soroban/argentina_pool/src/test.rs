@@ -1,14 +1,26 @@
 #![cfg(test)]
-use soroban_sdk::{map, testutils::Address as _, vec, Address, BytesN, Env, Error, Vec};
+use soroban_sdk::{
+    map, symbol_short, testutils::Address as _, testutils::Events, vec, Address, BytesN, Env,
+    Error, IntoVal, Vec,
+};
 
 use crate::{
-    contract::LiquidityPoolClient,
+    contract::{calculate_scaled_amount_with_interest, LiquidityPoolClient},
     errors::Error as ContractError,
-    loan::LoanStatus,
-    test_util::{setup_pool, setup_tc, setup_test_token},
+    loan::{read_loan, InterestModel, LoanStatus},
+    roles::ALL_ROLES,
+    storage_types::{DataKey, Role, SECONDS_PER_YEAR},
+    test_util::{
+        pool_contract, set_ledger_past_grace_period, set_ledger_timestamp, setup_pool,
+        setup_share_token, setup_tc, setup_test_token, tc_contract, upload_rebuilt_wasm,
+    },
+    upgrade::LoanV1,
     LiquidityPool,
 };
 
+// 90 days
+const LOAN_TERM: u64 = 90 * 86400;
+
 #[test]
 fn test_initialize() {
     let e = Env::default();
@@ -26,6 +38,128 @@ fn test_initialize() {
     assert_eq!(client.get_pool_fee(), 2);
 }
 
+#[test]
+fn test_admin_handover() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+
+    // the admin does not change until the proposal is accepted
+    let new_admin = Address::generate(&e);
+    client.propose_admin(&new_admin, &None);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("propose"), admin.clone()).into_val(&e)
+            );
+            let data: (Address, Option<u64>) = data.into_val(&e);
+            assert_eq!(data, (new_admin.clone(), None));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+    client.cancel_admin_proposal();
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("unpropose"), admin.clone()).into_val(&e)
+            );
+            let data: Address = data.into_val(&e);
+            assert_eq!(data, new_admin.clone());
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(client.pending_admin(), None);
+    let res = client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+
+    client.propose_admin(&new_admin, &None);
+    client.accept_admin();
+    assert_eq!(e.auths()[0].0, new_admin.clone());
+    assert_eq!(client.pending_admin(), None);
+
+    // the roles of the old admin move to the new admin
+    for role in ALL_ROLES {
+        assert!(!client.has_role(&role, &admin));
+        assert!(client.has_role(&role, &new_admin));
+    }
+
+    // the new admin manages the roles
+    client.grant_role(&Role::Treasury, &new_admin);
+    assert_eq!(e.auths()[0].0, new_admin);
+}
+
+#[test]
+fn test_roles() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+
+    // the admin holds every role after initialization
+    assert!(client.has_role(&Role::Compliance, &admin));
+    assert!(client.has_role(&Role::Treasury, &admin));
+    assert!(client.has_role(&Role::Risk, &admin));
+
+    let treasury = Address::generate(&e);
+    let compliance = Address::generate(&e);
+    client.grant_role(&Role::Treasury, &treasury);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("grant"), Role::Treasury, treasury.clone()).into_val(&e)
+            );
+            let admin_decoded: Address = data.into_val(&e);
+            assert_eq!(admin, admin_decoded);
+        }
+        None => panic!("The event is not published"),
+    }
+    client.grant_role(&Role::Compliance, &compliance);
+
+    // each privileged function requires its own role
+    let res = client.try_set_fee_percent(&compliance, &3);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.set_fee_percent(&treasury, &3);
+    assert_eq!(e.auths()[0].0, treasury.clone());
+    assert_eq!(client.get_pool_fee(), 3);
+    let res = client.try_add_whitelisted_tc(&treasury, &tc_client.address);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.add_whitelisted_tc(&compliance, &tc_client.address);
+
+    // the admin can give up a role
+    client.revoke_role(&Role::Compliance, &admin);
+    assert!(!client.has_role(&Role::Compliance, &admin));
+    let res = client.try_remove_whitelisted_tc(&admin, &tc_client.address);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+}
+
 #[test]
 #[should_panic(expected = "already initialized")]
 fn test_initialize_twice() {
@@ -54,22 +188,22 @@ fn test_tc_whitelist() {
     assert_eq!(whitelist, Vec::<Address>::new(&e));
 
     // add a TC to the whitelist
-    client.add_whitelisted_tc(&tc_client.address);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
     whitelist = client.get_whitelisted_tcs();
     assert_eq!(whitelist, vec![&e, tc_client.address.clone()]);
 
     // adding the same TC to the whitelist twice should not result in a duplicate value
-    client.add_whitelisted_tc(&tc_client.address);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
     whitelist = client.get_whitelisted_tcs();
     assert_eq!(whitelist, vec![&e, tc_client.address.clone()]);
 
     // removing a nonexistent TC should not change the whitelist
     let tc_client_2 = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
-    client.remove_whitelisted_tc(&tc_client_2.address);
+    client.remove_whitelisted_tc(&admin, &tc_client_2.address);
     assert_eq!(whitelist, vec![&e, tc_client.address.clone()]);
 
     // add a second TC to the whitelist
-    client.add_whitelisted_tc(&tc_client_2.address);
+    client.add_whitelisted_tc(&admin, &tc_client_2.address);
     whitelist = client.get_whitelisted_tcs();
     assert_eq!(
         whitelist,
@@ -82,7 +216,7 @@ fn test_tc_whitelist() {
     );
 
     // remove the 1st TC from the whitelist
-    client.remove_whitelisted_tc(&tc_client.address);
+    client.remove_whitelisted_tc(&admin, &tc_client.address);
     whitelist = client.get_whitelisted_tcs();
     assert_eq!(whitelist, vec![&e, tc_client_2.address.clone()]);
 }
@@ -99,7 +233,13 @@ fn test_create_loan_offer_no_whitelist() {
     let creditor = Address::generate(&e);
 
     // call should fail because TC was not whitelisted
-    let res = client.try_create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    let res = client.try_create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
@@ -120,12 +260,18 @@ fn test_create_loan_offer_insufficient_balance() {
     let borrower = Address::generate(&e);
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
-    client.add_whitelisted_tc(&tc_client.address);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
 
     // call should fail because creditor does not have enough token
-    let res = client.try_create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    let res = client.try_create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
     assert_eq!(res, Err(Ok(Error::from_contract_error(10))));
 }
 
@@ -142,12 +288,18 @@ fn test_create_loan_offer() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &20000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
-    client.add_whitelisted_tc(&tc_client.address);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
 
     // successful call
-    let mut loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    let mut loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
     assert_eq!(loan_id, 0);
     assert_eq!(client.get_loan_fee(&loan_id), 0);
     assert_eq!(client.get_loan_creditor(&loan_id), creditor.clone());
@@ -158,10 +310,113 @@ fn test_create_loan_offer() {
     assert_eq!(token_client.balance(&client.address), 10000000000000);
 
     // create another loan offer, returned loan id should be incremented
-    loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
     assert_eq!(loan_id, 1);
 }
 
+#[test]
+fn test_loan_to_value() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &10000000000000);
+    token_admin_client.mint(&creditor.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+
+    // only the Risk role can set the max LTV, which cannot exceed 100%
+    assert_eq!(client.get_max_ltv_bps(), 10000);
+    let res = client.try_set_max_ltv_bps(&creditor, &8000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    let res = client.try_set_max_ltv_bps(&admin, &10001);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    client.set_max_ltv_bps(&admin, &8000);
+    assert_eq!(client.get_max_ltv_bps(), 8000);
+
+    // the principal must be positive and within the max LTV
+    let res = client.try_create_loan_offer(&creditor, &tc_client.address, &0, &LOAN_TERM, &800001);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::LTVExceeded as u32
+        )))
+    );
+    let res = client.try_create_loan_offer(&creditor, &tc_client.address, &0, &LOAN_TERM, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &800000,
+    );
+    assert_eq!(client.get_loan_amount(&loan_id), 800000);
+    assert_eq!(client.get_loan_collateral_value(&loan_id), 1000000);
+    assert_eq!(token_client.balance(&creditor), 2000000000000);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("offer"), creditor.clone()).into_val(&e)
+            );
+            let data: (u64, i128, i128) = data.into_val(&e);
+            assert_eq!(data, (loan_id, 800000, 1000000));
+        }
+        None => panic!("The event is not published"),
+    }
+
+    // the borrower receives the principal against the whole TC
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("accept"), borrower.clone()).into_val(&e)
+            );
+            let data: (u64, i128, i128) = data.into_val(&e);
+            assert_eq!(data, (loan_id, 800000, 1000000));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(token_client.balance(&borrower), 8000000000000);
+    assert_eq!(tc_client.get_owner(&0), client.address.clone());
+    assert_eq!(client.get_payoff_amount(&loan_id), 8000000000000);
+
+    client.payoff_loan(&loan_id);
+    assert_eq!(token_client.balance(&borrower), 0);
+    assert_eq!(token_client.balance(&creditor), 10000000000000);
+    assert_eq!(tc_client.get_owner(&0), borrower.clone());
+}
+
 #[test]
 fn test_cancel_loan_offer() {
     let e = Env::default();
@@ -175,11 +430,17 @@ fn test_cancel_loan_offer() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
 
-    client.add_whitelisted_tc(&tc_client.address);
-    let loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
     assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Pending as u32);
     assert_eq!(token_client.balance(&creditor.clone()), 0);
     client.cancel_loan_offer(&loan_id);
@@ -201,11 +462,17 @@ fn test_accept_loan_offer() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
     assert_eq!(tc_client.get_owner(&0), borrower.clone());
-    client.add_whitelisted_tc(&tc_client.address);
-    let loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
     client.accept_loan_offer(&borrower.clone(), &loan_id);
     assert_eq!(client.get_loan_borrower(&loan_id), borrower.clone());
     assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Active as u32);
@@ -237,11 +504,17 @@ fn test_payoff_loan() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
     assert_eq!(tc_client.get_owner(&0), borrower.clone());
-    client.add_whitelisted_tc(&tc_client.address);
-    let loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
 
     // it should not be possible to pay off the loan before the offer is accepted
     let res = client.try_payoff_loan(&loan_id);
@@ -259,6 +532,78 @@ fn test_payoff_loan() {
     assert_eq!(token_client.balance(&creditor.clone()), 10000000000000);
 }
 
+#[test]
+fn test_pause() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &10000000000000);
+    token_admin_client.mint(&creditor.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+
+    // only a guardian can pause
+    let guardian = Address::generate(&e);
+    let res = client.try_pause(&guardian);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.grant_role(&Role::Guardian, &guardian);
+    client.pause(&guardian);
+    assert!(client.is_paused());
+
+    // new loans fail while paused
+    let res = client.try_create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    let res = client.try_accept_loan_offer(&borrower.clone(), &loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+
+    // the creditor can withdraw unless the guardian closes the exits
+    client.set_exits_allowed(&guardian, &false);
+    assert!(!client.exits_allowed());
+    let res = client.try_cancel_loan_offer(&loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    client.set_exits_allowed(&guardian, &true);
+    client.cancel_loan_offer(&loan_id);
+    assert_eq!(token_client.balance(&creditor.clone()), 10000000000000);
+
+    client.unpause(&guardian);
+    assert!(!client.is_paused());
+}
+
 #[test]
 fn test_payoff_loan_with_interest() {
     let e = Env::default();
@@ -273,14 +618,24 @@ fn test_payoff_loan_with_interest() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
     assert_eq!(tc_client.get_owner(&0), borrower.clone());
-    client.add_whitelisted_tc(&tc_client.address);
-    client.set_fee_percent(&2);
-    assert_eq!(client.get_pool_fee(), 2);
-    let loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
-    assert_eq!(client.get_loan_fee(&loan_id), 2);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    client.set_fee_percent(&admin, &200);
+    assert_eq!(client.get_pool_fee(), 200);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    assert_eq!(client.get_loan_fee(&loan_id), 200);
+    assert_eq!(
+        client.get_loan_interest_model(&loan_id),
+        InterestModel::Flat
+    );
     client.accept_loan_offer(&borrower.clone(), &loan_id);
 
     token_admin_client.mint(&borrower.clone(), &200000000000);
@@ -293,7 +648,7 @@ fn test_payoff_loan_with_interest() {
 }
 
 #[test]
-fn test_default_loan() {
+fn test_payoff_loan_with_accrued_interest() {
     let e = Env::default();
     e.mock_all_auths();
     let admin = Address::generate(&e);
@@ -306,42 +661,291 @@ fn test_default_loan() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
-    assert_eq!(tc_client.get_owner(&0), borrower.clone());
-    client.add_whitelisted_tc(&tc_client.address);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+
+    // 10% APR
+    client.set_fee_percent(&admin, &1000);
+    client.set_interest_model(&admin, &InterestModel::Accrued);
+    assert_eq!(client.get_interest_model(), InterestModel::Accrued);
+    set_ledger_timestamp(&e, 1000);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    assert_eq!(
+        client.get_loan_interest_model(&loan_id),
+        InterestModel::Accrued
+    );
 
-    let loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
+    // no interest accrues before the offer is accepted
+    set_ledger_timestamp(&e, 5000);
+    assert_eq!(client.get_payoff_amount(&loan_id), 10000000000000);
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    assert_eq!(client.get_payoff_amount(&loan_id), 10000000000000);
 
-    // it should not be possible to default a loan that hasn't been accepted yet
-    let res = client.try_default_loan(&loan_id);
+    // loans taken out under the previous model keep it
+    client.set_interest_model(&admin, &InterestModel::Flat);
+    assert_eq!(
+        client.get_loan_interest_model(&loan_id),
+        InterestModel::Accrued
+    );
+
+    // half a year at 10% APR accrues 5%
+    set_ledger_timestamp(&e, 5000 + SECONDS_PER_YEAR / 2);
+    assert_eq!(client.get_payoff_amount(&loan_id), 10500000000000);
+
+    // a full year accrues 10%
+    set_ledger_timestamp(&e, 5000 + SECONDS_PER_YEAR);
+    assert_eq!(client.get_payoff_amount(&loan_id), 11000000000000);
+
+    token_admin_client.mint(&borrower.clone(), &1000000000000);
+    client.payoff_loan(&loan_id);
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Closed as u32);
+    assert_eq!(token_client.balance(&borrower.clone()), 0);
+    assert_eq!(token_client.balance(&creditor.clone()), 11000000000000);
+
+    // interest stops accruing once the loan is closed
+    set_ledger_timestamp(&e, 5000 + 2 * SECONDS_PER_YEAR);
+    assert_eq!(client.get_payoff_amount(&loan_id), 10000000000000);
+}
+
+#[test]
+fn test_partial_repayments() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &30000000000000);
+    token_admin_client.mint(&creditor.clone(), &20000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    tc_client.pledge(&borrower.clone(), &1);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+
+    // a 10% flat fee is owed in full from acceptance
+    client.set_fee_percent(&admin, &1000);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    assert_eq!(client.get_outstanding(&loan_id), 0);
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    assert_eq!(client.get_outstanding(&loan_id), 11000000000000);
+
+    let res = client.try_repay(&loan_id, &0);
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
-            ContractError::InvalidStatus as u32
+            ContractError::NotPermitted as u32
         )))
     );
 
+    // payments go to the interest first
+    assert_eq!(client.repay(&loan_id, &500000000000), 10500000000000);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("repay"), borrower.clone()).into_val(&e)
+            );
+            let data: (u64, i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data, (loan_id, 500000000000, 0, 10500000000000));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(client.repay(&loan_id, &1000000000000), 9500000000000);
+    match e.events().all().last() {
+        Some((_, _, data)) => {
+            let data: (u64, i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data, (loan_id, 500000000000, 500000000000, 9500000000000));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(token_client.balance(&creditor), 11500000000000);
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Active as u32);
+    assert_eq!(tc_client.get_owner(&0), client.address.clone());
+
+    // only the outstanding balance is taken, after which the TC is returned
+    assert_eq!(client.repay(&loan_id, &10000000000000), 0);
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Closed as u32);
+    assert_eq!(client.get_outstanding(&loan_id), 0);
+    assert_eq!(tc_client.get_owner(&0), borrower.clone());
+    assert_eq!(token_client.balance(&creditor), 21000000000000);
+    assert_eq!(token_client.balance(&borrower), 9000000000000);
+
+    // accrued interest is charged on the outstanding principal only
+    client.set_interest_model(&admin, &InterestModel::Accrued);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &1,
+        &LOAN_TERM,
+        &1000000,
+    );
+    set_ledger_timestamp(&e, 1000);
     client.accept_loan_offer(&borrower.clone(), &loan_id);
-    client.default_loan(&loan_id);
+    assert_eq!(client.get_outstanding(&loan_id), 10000000000000);
+
+    // repaying half of the principal after half a year
+    set_ledger_timestamp(&e, 1000 + SECONDS_PER_YEAR / 2);
+    assert_eq!(client.get_outstanding(&loan_id), 10500000000000);
+    assert_eq!(client.repay(&loan_id, &5500000000000), 5000000000000);
+
+    // halves the interest of the second half year
+    set_ledger_timestamp(&e, 1000 + SECONDS_PER_YEAR);
+    assert_eq!(client.get_outstanding(&loan_id), 5250000000000);
+    assert_eq!(client.get_payoff_amount(&loan_id), 5250000000000);
+    client.payoff_loan(&loan_id);
+    match e.events().all().last() {
+        Some((_, _, data)) => {
+            let data: (u64, i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data, (loan_id, 250000000000, 5000000000000, 0));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Closed as u32);
+    assert_eq!(tc_client.get_owner(&1), borrower.clone());
+    assert_eq!(token_client.balance(&borrower), 8250000000000);
+}
+
+#[test]
+fn test_partial_repayments_rounding() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &20000100000000);
+    token_admin_client.mint(&creditor.clone(), &20000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    tc_client.pledge(&borrower.clone(), &1);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    client.set_fee_percent(&admin, &1000);
+    client.set_interest_model(&admin, &InterestModel::Accrued);
+
+    // two identical loans accepted at the same time
+    let loan_id1 =
+        client.create_loan_offer(&creditor.clone(), &tc_client.address, &0, &LOAN_TERM, &1000);
+    let loan_id2 =
+        client.create_loan_offer(&creditor.clone(), &tc_client.address, &1, &LOAN_TERM, &1000);
+    set_ledger_timestamp(&e, 1000);
+    client.accept_loan_offer(&borrower.clone(), &loan_id1);
+    client.accept_loan_offer(&borrower.clone(), &loan_id2);
+
+    // a day of interest is 2739726.03 units, rounded up in the outstanding balance
+    set_ledger_timestamp(&e, 1000 + 86400);
+    assert_eq!(client.get_outstanding(&loan_id1), 10002739727);
+
+    // the first loan pays part of the interest every day, the fractions are carried over
+    let balance = token_client.balance(&borrower);
+    for day in 1..=10 {
+        set_ledger_timestamp(&e, 1000 + day * 86400);
+        client.repay(&loan_id1, &2000000);
+    }
+    client.payoff_loan(&loan_id1);
+    assert_eq!(client.get_loan_status(&loan_id1), LoanStatus::Closed as u32);
+    let paid_in_installments = balance - token_client.balance(&borrower);
+
+    // the second loan is paid off once, at the same time
+    let balance = token_client.balance(&borrower);
+    assert_eq!(client.get_payoff_amount(&loan_id2), 10027397261);
+    client.payoff_loan(&loan_id2);
+    assert_eq!(client.get_loan_status(&loan_id2), LoanStatus::Closed as u32);
+    assert_eq!(balance - token_client.balance(&borrower), 10027397261);
+    assert_eq!(paid_in_installments, 10027397261);
+}
+
+#[test]
+fn test_interest_rounding_and_overflow() {
+    // flat fees are charged in full
     assert_eq!(
-        client.get_loan_status(&loan_id),
-        LoanStatus::Defaulted as u32
+        calculate_scaled_amount_with_interest(1000, 7, 250, InterestModel::Flat, 0),
+        Some(10250000000)
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(1000, 7, 250, InterestModel::Flat, 1000),
+        Some(10250000000)
     );
-    assert_eq!(tc_client.get_owner(&0), creditor.clone());
 
-    // it should not be possible to pay off a loan that has been defaulted
-    let res = client.try_payoff_loan(&loan_id);
+    // fractions of the smallest token unit are rounded up in the creditor's favour
     assert_eq!(
-        res,
-        Err(Ok(Error::from_contract_error(
-            ContractError::InvalidStatus as u32
-        )))
+        calculate_scaled_amount_with_interest(1, 0, 1, InterestModel::Flat, 0),
+        Some(2)
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(1, 0, 1, InterestModel::Accrued, 1),
+        Some(2)
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(10000, 0, 1, InterestModel::Flat, 0),
+        Some(10001)
+    );
+
+    // no interest without a fee or elapsed time
+    assert_eq!(
+        calculate_scaled_amount_with_interest(1, 0, 0, InterestModel::Flat, 0),
+        Some(1)
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(1, 0, 1000, InterestModel::Accrued, 0),
+        Some(1)
+    );
+
+    // an hour at 10% APR
+    assert_eq!(
+        calculate_scaled_amount_with_interest(1000000, 7, 1000, InterestModel::Accrued, 3600),
+        Some(10000114155252)
+    );
+
+    // overflowing amounts are reported instead of wrapping
+    assert_eq!(
+        calculate_scaled_amount_with_interest(i128::MAX, 0, 0, InterestModel::Flat, 0),
+        Some(i128::MAX)
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(i128::MAX, 0, 1, InterestModel::Flat, 0),
+        None
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(i128::MAX, 7, 0, InterestModel::Flat, 0),
+        None
+    );
+    assert_eq!(
+        calculate_scaled_amount_with_interest(
+            i128::MAX / 10000,
+            0,
+            u32::MAX,
+            InterestModel::Accrued,
+            u64::MAX
+        ),
+        None
     );
 }
 
 #[test]
-fn test_default_loan_already_closed() {
+fn test_default_loan() {
     let e = Env::default();
     e.mock_all_auths();
     let admin = Address::generate(&e);
@@ -354,22 +958,745 @@ fn test_default_loan_already_closed() {
     let creditor = Address::generate(&e);
     token_admin_client.mint(&borrower.clone(), &10000000000000);
     token_admin_client.mint(&creditor.clone(), &10000000000000);
-    tc_client.mint(&1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
     tc_client.pledge(&borrower.clone(), &0);
     assert_eq!(tc_client.get_owner(&0), borrower.clone());
-    client.add_whitelisted_tc(&tc_client.address);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
 
-    let loan_id = client.create_loan_offer(&creditor.clone(), &tc_client.address, &0);
-    client.accept_loan_offer(&borrower.clone(), &loan_id);
-    client.payoff_loan(&loan_id);
-    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Closed as u32);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
 
-    // it should not be possible to default a loan that was already paid off
-    let res = client.try_default_loan(&loan_id);
+    // it should not be possible to default a loan that hasn't been accepted yet
+    let res = client.try_default_loan(&admin, &loan_id);
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
             ContractError::InvalidStatus as u32
         )))
     );
+
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    set_ledger_past_grace_period(&e, &client, loan_id);
+    client.default_loan(&admin, &loan_id);
+    assert_eq!(
+        client.get_loan_status(&loan_id),
+        LoanStatus::Defaulted as u32
+    );
+    assert_eq!(tc_client.get_owner(&0), creditor.clone());
+
+    // it should not be possible to pay off a loan that has been defaulted
+    let res = client.try_payoff_loan(&loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidStatus as u32
+        )))
+    );
+}
+
+#[test]
+fn test_loan_maturity() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    let anyone = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &10000000000000);
+    token_admin_client.mint(&creditor.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+
+    // only the Risk role can change the grace period
+    assert_eq!(client.get_grace_period(), 30 * 86400);
+    let res = client.try_set_grace_period(&anyone, &86400);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.set_grace_period(&admin, &86400);
+    assert_eq!(client.get_grace_period(), 86400);
+
+    // loans need a term
+    let res = client.try_create_loan_offer(&creditor.clone(), &tc_client.address, &0, &0, &1000000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    set_ledger_timestamp(&e, 1000);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    assert_eq!(client.get_loan_due_date(&loan_id), 0);
+
+    // the loan keeps the grace period it was created with
+    client.set_grace_period(&admin, &(7 * 86400));
+
+    // the due date counts from acceptance
+    set_ledger_timestamp(&e, 2000);
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    let due_date = 2000 + LOAN_TERM;
+    assert_eq!(client.get_loan_due_date(&loan_id), due_date);
+
+    // nobody, not even the Risk role, can default the loan until the grace period has passed
+    let res = client.try_default_loan(&anyone, &loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::LoanNotDue as u32
+        )))
+    );
+    let res = client.try_default_loan(&admin, &loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::LoanNotDue as u32
+        )))
+    );
+    set_ledger_timestamp(&e, due_date + 86400);
+    let res = client.try_default_loan(&creditor, &loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::LoanNotDue as u32
+        )))
+    );
+    let res = client.try_default_loan(&admin, &loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::LoanNotDue as u32
+        )))
+    );
+
+    // afterwards anyone can
+    set_ledger_timestamp(&e, due_date + 86400 + 1);
+    client.default_loan(&anyone, &loan_id);
+    assert_eq!(
+        client.get_loan_status(&loan_id),
+        LoanStatus::Defaulted as u32
+    );
+    assert_eq!(tc_client.get_owner(&0), creditor.clone());
+
+    // the borrower can no longer pay off the loan
+    let res = client.try_payoff_loan(&loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidStatus as u32
+        )))
+    );
+}
+
+#[test]
+fn test_pooled_liquidity() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let lp1 = Address::generate(&e);
+    let lp2 = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &10000000000000);
+    token_admin_client.mint(&lp1.clone(), &10000000000000);
+    token_admin_client.mint(&lp2.clone(), &1100000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    client.set_fee_percent(&admin, &1000);
+
+    // deposits need a share token, minted by the pool
+    let res = client.try_deposit(&lp1, &10000000000000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+    let share_token = setup_share_token(&e, &client.address);
+    let res = client.try_set_share_token(&lp1, &share_token.address);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.set_share_token(&admin, &share_token.address);
+    assert_eq!(client.get_share_token(), Some(share_token.address.clone()));
+    let res = client.try_set_share_token(&admin, &share_token.address);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotEmpty as u32
+        )))
+    );
+
+    // the first deposit mints one share per unit
+    assert_eq!(client.deposit(&lp1, &10000000000000), 10000000000000);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(topics, (symbol_short!("deposit"), lp1.clone()).into_val(&e));
+            let data: (i128, i128) = data.into_val(&e);
+            assert_eq!(data, (10000000000000, 10000000000000));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(share_token.balance(&lp1), 10000000000000);
+    assert_eq!(client.get_total_shares(), 10000000000000);
+    assert_eq!(client.get_pool_balance(), 10000000000000);
+
+    // only the Risk role lends from the pool, up to its balance
+    let res = client.try_create_pool_loan_offer(&lp1, &tc_client.address, &0, &LOAN_TERM, &1000000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    let res =
+        client.try_create_pool_loan_offer(&admin, &tc_client.address, &0, &LOAN_TERM, &1000001);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::LTVExceeded as u32
+        )))
+    );
+    let loan_id =
+        client.create_pool_loan_offer(&admin, &tc_client.address, &0, &LOAN_TERM, &1000000);
+    assert_eq!(client.get_loan_creditor(&loan_id), client.address.clone());
+    assert_eq!(client.get_pool_balance(), 0);
+    assert_eq!(client.get_total_assets(), 10000000000000);
+
+    // pool loans are not cancelled by their creditor, and lent funds cannot be withdrawn
+    let res = client.try_cancel_loan_offer(&loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    let res = client.try_withdraw(&lp1, &1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InsufficientLiquidity as u32
+        )))
+    );
+
+    // the interest paid off raises the share price
+    client.accept_loan_offer(&borrower, &loan_id);
+    assert_eq!(token_client.balance(&borrower), 10000000000000);
+    token_admin_client.mint(&borrower.clone(), &1000000000000);
+    client.payoff_loan(&loan_id);
+    assert_eq!(tc_client.get_owner(&0), borrower.clone());
+    assert_eq!(client.get_pool_balance(), 11000000000000);
+    assert_eq!(client.get_total_assets(), 11000000000000);
+
+    // later deposits get fewer shares per unit
+    assert_eq!(client.deposit(&lp2, &1100000000000), 1000000000000);
+    assert_eq!(client.get_total_shares(), 11000000000000);
+
+    // withdrawals are paid at the share price
+    assert_eq!(client.withdraw(&lp1, &10000000000000), 11000000000000);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("withdraw"), lp1.clone()).into_val(&e)
+            );
+            let data: (i128, i128) = data.into_val(&e);
+            assert_eq!(data, (10000000000000, 11000000000000));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(token_client.balance(&lp1), 11000000000000);
+    assert_eq!(share_token.balance(&lp1), 0);
+    assert_eq!(client.withdraw(&lp2, &1000000000000), 1100000000000);
+    assert_eq!(token_client.balance(&lp2), 1100000000000);
+    assert_eq!(client.get_total_shares(), 0);
+    assert_eq!(client.get_pool_balance(), 0);
+}
+
+#[test]
+fn test_pool_loan_default() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let lp = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &20000000000000);
+    token_admin_client.mint(&lp.clone(), &20000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    tc_client.pledge(&borrower.clone(), &1);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let share_token = setup_share_token(&e, &client.address);
+    client.set_share_token(&admin, &share_token.address);
+    client.deposit(&lp, &20000000000000);
+
+    // a cancelled pool loan returns its principal to the pool
+    let loan_id =
+        client.create_pool_loan_offer(&admin, &tc_client.address, &1, &LOAN_TERM, &1000000);
+    assert_eq!(client.get_pool_balance(), 10000000000000);
+    client.cancel_pool_loan_offer(&admin, &loan_id);
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Closed as u32);
+    assert_eq!(client.get_pool_balance(), 20000000000000);
+    assert_eq!(client.get_total_assets(), 20000000000000);
+
+    // a defaulted pool loan keeps its principal in the share price until liquidation
+    let loan_id =
+        client.create_pool_loan_offer(&admin, &tc_client.address, &0, &LOAN_TERM, &1000000);
+    client.accept_loan_offer(&borrower, &loan_id);
+    set_ledger_past_grace_period(&e, &client, loan_id);
+    client.default_loan(&admin, &loan_id);
+    assert_eq!(tc_client.get_owner(&0), client.address.clone());
+    assert_eq!(client.get_pool_balance(), 10000000000000);
+    assert_eq!(client.get_total_assets(), 20000000000000);
+
+    // a deposit between default and liquidation buys shares at the undiluted price
+    let lp2 = Address::generate(&e);
+    token_admin_client.mint(&lp2.clone(), &10000000000000);
+    assert_eq!(client.deposit(&lp2, &10000000000000), 10000000000000);
+
+    // liquidating the TC replaces the principal with the recovery
+    set_ledger_timestamp(&e, 1641024000);
+    assert_eq!(client.liquidate_loan(&loan_id), 10000000000000);
+    assert_eq!(client.get_loan_recovery(&loan_id), 10000000000000);
+    assert_eq!(client.get_pool_balance(), 30000000000000);
+    assert_eq!(client.get_total_assets(), 30000000000000);
+    assert_eq!(client.withdraw(&lp2, &10000000000000), 10000000000000);
+    assert_eq!(client.withdraw(&lp, &20000000000000), 20000000000000);
+    assert_eq!(token_client.balance(&lp2), 10000000000000);
+    assert_eq!(token_client.balance(&lp), 20000000000000);
+}
+
+#[test]
+fn test_liquidate_loan() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor1 = Address::generate(&e);
+    let creditor2 = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &20000000000000);
+    token_admin_client.mint(&creditor1.clone(), &10000000000000);
+    token_admin_client.mint(&creditor2.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    tc_client.pledge(&borrower.clone(), &1);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+
+    // only the Treasury role sets the liquidation fee, which cannot exceed 100%
+    let res = client.try_set_liquidation_fee_bps(&creditor1, &100);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    let res = client.try_set_liquidation_fee_bps(&admin, &10001);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    client.set_liquidation_fee_bps(&admin, &100);
+    assert_eq!(client.get_liquidation_fee_bps(), 100);
+
+    // the first creditor has the smart contract hold defaulted collateral
+    client.set_hold_collateral(&creditor1, &true);
+    assert!(client.holds_collateral(&creditor1));
+    assert!(!client.holds_collateral(&creditor2));
+    let loan1 = client.create_loan_offer(
+        &creditor1.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    let loan2 = client.create_loan_offer(
+        &creditor2.clone(),
+        &tc_client.address,
+        &1,
+        &LOAN_TERM,
+        &1000000,
+    );
+    client.accept_loan_offer(&borrower, &loan1);
+    client.accept_loan_offer(&borrower, &loan2);
+
+    // only defaulted loans are liquidated
+    let res = client.try_liquidate_loan(&loan1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidStatus as u32
+        )))
+    );
+    set_ledger_past_grace_period(&e, &client, loan1);
+    client.default_loan(&admin, &loan1);
+    client.default_loan(&admin, &loan2);
+    assert_eq!(tc_client.get_owner(&0), client.address.clone());
+    assert_eq!(tc_client.get_owner(&1), creditor2.clone());
+
+    // the TC can only be redeemed after its redeem time
+    let res = client.try_liquidate_loan(&loan1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotRedeemable as u32
+        )))
+    );
+    set_ledger_timestamp(&e, 1641024000);
+
+    // the creditor receives the recovery minus the pool's fee
+    assert_eq!(client.liquidate_loan(&loan1), 10000000000000);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("liquidate"), creditor1.clone()).into_val(&e)
+            );
+            let data: (u64, i128, i128) = data.into_val(&e);
+            assert_eq!(data, (loan1, 10000000000000, 100000000000));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(
+        client.get_loan_status(&loan1),
+        LoanStatus::Liquidated as u32
+    );
+    assert_eq!(client.get_loan_recovery(&loan1), 10000000000000);
+    assert_eq!(token_client.balance(&creditor1), 9900000000000);
+    assert_eq!(client.get_treasury_fees(), 100000000000);
+    let res = client.try_liquidate_loan(&loan1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidStatus as u32
+        )))
+    );
+
+    // collateral transferred to the creditor is redeemed by the creditor
+    let res = client.try_liquidate_loan(&loan2);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotOwned as u32
+        )))
+    );
+    assert_eq!(client.get_loan_recovery(&loan2), 0);
+}
+
+#[test]
+fn test_liquidation_fee_without_shareholders() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    let lp = Address::generate(&e);
+    let treasury = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &20000000000000);
+    token_admin_client.mint(&creditor.clone(), &20000000000000);
+    token_admin_client.mint(&lp.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    tc_client.pledge(&borrower.clone(), &1);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    client.set_liquidation_fee_bps(&admin, &100);
+    client.set_hold_collateral(&creditor, &true);
+
+    let loan1 = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    let loan2 = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &1,
+        &LOAN_TERM,
+        &1000000,
+    );
+    client.accept_loan_offer(&borrower, &loan1);
+    client.accept_loan_offer(&borrower, &loan2);
+    set_ledger_past_grace_period(&e, &client, loan1);
+    client.default_loan(&creditor, &loan1);
+    client.default_loan(&creditor, &loan2);
+    set_ledger_timestamp(&e, 1641024000);
+
+    // without shareholders the fee is held for the Treasury role, not added to the pool
+    assert_eq!(client.liquidate_loan(&loan1), 10000000000000);
+    assert_eq!(client.get_pool_balance(), 0);
+    assert_eq!(client.get_treasury_fees(), 100000000000);
+
+    // so the first depositor only gets what they deposit
+    let share_token = setup_share_token(&e, &client.address);
+    client.set_share_token(&admin, &share_token.address);
+    assert_eq!(client.deposit(&lp, &10000000000000), 10000000000000);
+    assert_eq!(client.get_total_assets(), 10000000000000);
+
+    // once there are shareholders, the fee goes to them
+    assert_eq!(client.liquidate_loan(&loan2), 10000000000000);
+    assert_eq!(client.get_pool_balance(), 10100000000000);
+    assert_eq!(client.get_treasury_fees(), 100000000000);
+
+    // only the Treasury role collects the held fees
+    let res = client.try_withdraw_treasury_fees(&lp, &lp);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    assert_eq!(
+        client.withdraw_treasury_fees(&admin, &treasury),
+        100000000000
+    );
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("fees"), treasury.clone()).into_val(&e)
+            );
+            let data: i128 = data.into_val(&e);
+            assert_eq!(data, 100000000000);
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(token_client.balance(&treasury), 100000000000);
+    assert_eq!(client.get_treasury_fees(), 0);
+    assert_eq!(client.withdraw(&lp, &10000000000000), 10100000000000);
+}
+
+#[test]
+fn test_default_loan_already_closed() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &10000000000000);
+    token_admin_client.mint(&creditor.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    assert_eq!(tc_client.get_owner(&0), borrower.clone());
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    client.payoff_loan(&loan_id);
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Closed as u32);
+
+    // it should not be possible to default a loan that was already paid off
+    let res = client.try_default_loan(&admin, &loan_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidStatus as u32
+        )))
+    );
+}
+
+#[test]
+fn test_upgrade() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let borrower = Address::generate(&e);
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&borrower.clone(), &10000000000000);
+    token_admin_client.mint(&creditor.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    tc_client.pledge(&borrower.clone(), &0);
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+    client.accept_loan_offer(&borrower.clone(), &loan_id);
+    let due_date = client.get_loan_due_date(&loan_id);
+    let outstanding = client.get_outstanding(&loan_id);
+    let payoff_amount = client.get_payoff_amount(&loan_id);
+    let borrower_balance = token_client.balance(&borrower);
+    let creditor_balance = token_client.balance(&creditor);
+
+    // move the pool to the release build, then both contracts to a build with another hash
+    let tc_wasm_hash = e.deployer().upload_contract_wasm(tc_contract::WASM);
+    let pool_wasm_hash = e.deployer().upload_contract_wasm(pool_contract::WASM);
+    client.upgrade(&pool_wasm_hash);
+    let tc_rebuilt_hash = upload_rebuilt_wasm(&e, tc_contract::WASM);
+    assert_ne!(tc_wasm_hash, tc_rebuilt_hash);
+    tc_client.upgrade(&tc_rebuilt_hash);
+    let pool_rebuilt_hash = upload_rebuilt_wasm(&e, pool_contract::WASM);
+    assert_ne!(pool_wasm_hash, pool_rebuilt_hash);
+    client.upgrade(&pool_rebuilt_hash);
+    e.budget().reset_default();
+
+    // existing certificates, loans and balances survive the upgrades
+    assert_eq!(client.get_loan_amount(&loan_id), 1000000);
+    assert_eq!(client.get_loan_due_date(&loan_id), due_date);
+    assert_eq!(client.get_outstanding(&loan_id), outstanding);
+    assert_eq!(client.get_payoff_amount(&loan_id), payoff_amount);
+    assert_eq!(token_client.balance(&borrower), borrower_balance);
+    assert_eq!(token_client.balance(&creditor), creditor_balance);
+    assert_eq!(tc_client.get_owner(&0), client.address.clone());
+    assert_eq!(tc_client.get_amount(&0), 1000000);
+    assert_eq!(client.get_loan_borrower(&loan_id), borrower.clone());
+    assert_eq!(client.get_loan_creditor(&loan_id), creditor.clone());
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Active as u32);
+    assert_eq!(
+        client.get_whitelisted_tcs(),
+        vec![&e, tc_client.address.clone()]
+    );
+    assert_eq!(client.version(), 2);
+
+    // the loan can still be paid off
+    client.payoff_loan(&loan_id);
+    assert_eq!(tc_client.get_owner(&0), borrower.clone());
+}
+
+#[test]
+fn test_migrate() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let client = setup_pool(&e, &admin, &token_client.address);
+    let tc_client = setup_tc(&e, &admin, &token_client.address, &token_client.decimals());
+    e.budget().reset_default();
+
+    let creditor = Address::generate(&e);
+    token_admin_client.mint(&creditor.clone(), &10000000000000);
+    tc_client.mint(&admin, &1000000, &1641024000, &Vec::<BytesN<32>>::new(&e));
+    client.add_whitelisted_tc(&admin, &tc_client.address);
+    let loan_id = client.create_loan_offer(
+        &creditor.clone(),
+        &tc_client.address,
+        &0,
+        &LOAN_TERM,
+        &1000000,
+    );
+
+    // storage written by the current version needs no migration
+    let res = client.try_migrate();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // storage written before versioning has no version
+    e.as_contract(&client.address, || {
+        e.storage().instance().remove(&DataKey::Version);
+        for role in ALL_ROLES {
+            e.storage()
+                .persistent()
+                .remove(&DataKey::Role(role, admin.clone()));
+        }
+    });
+    assert_eq!(client.version(), 0);
+
+    // version 1 stored fees in whole percent
+    e.as_contract(&client.address, || {
+        let loan = read_loan(&e, loan_id);
+        let old = LoanV1 {
+            borrower: loan.borrower,
+            creditor: loan.creditor,
+            amount: loan.principal,
+            tc_address: loan.tc_address,
+            tc_id: loan.tc_id,
+            fee_percent: 3,
+            status: loan.status,
+        };
+        e.storage().persistent().set(&DataKey::Loan(loan_id), &old);
+        e.storage().instance().remove(&DataKey::FeeBps);
+        e.storage().instance().set(&DataKey::FeePercent, &2u32);
+    });
+
+    assert!(!client.has_role(&Role::Guardian, &admin));
+    client.migrate();
+    for role in ALL_ROLES {
+        assert!(client.has_role(&role, &admin));
+    }
+    assert_eq!(client.version(), 2);
+    assert_eq!(client.get_loan_amount(&loan_id), 1000000);
+    assert_eq!(client.get_loan_collateral_value(&loan_id), 1000000);
+    assert_eq!(client.get_loan_creditor(&loan_id), creditor.clone());
+    assert_eq!(client.get_loan_status(&loan_id), LoanStatus::Pending as u32);
+    assert_eq!(client.get_loan_fee(&loan_id), 300);
+    assert_eq!(
+        client.get_loan_interest_model(&loan_id),
+        InterestModel::Flat
+    );
+    assert_eq!(client.get_pool_fee(), 200);
+    assert_eq!(client.get_loan_due_date(&loan_id), 0);
+    e.as_contract(&client.address, || {
+        assert!(!e.storage().instance().has(&DataKey::FeePercent));
+    });
 }
@@ -1,15 +1,24 @@
 #![cfg(any(test, feature = "testutils"))]
 
-use soroban_sdk::{testutils::BytesN as _, token, Address, BytesN, Env};
+use soroban_sdk::{
+    testutils::{BytesN as _, Ledger},
+    token, Address, Bytes, BytesN, Env,
+};
 
 use crate::{contract::LiquidityPoolClient, LiquidityPool};
 
-mod tc_contract {
+pub mod tc_contract {
     soroban_sdk::contractimport!(
         file = "../argentina_pledge/target/wasm32-unknown-unknown/release/argentina_pledge.wasm"
     );
 }
 
+pub mod pool_contract {
+    soroban_sdk::contractimport!(
+        file = "target/wasm32-unknown-unknown/release/argentina_pool.wasm"
+    );
+}
+
 pub fn setup_pool<'a>(
     e: &Env,
     admin: &Address,
@@ -33,6 +42,12 @@ pub fn setup_test_token<'a>(
     )
 }
 
+// a token contract administered by the pool, so that it can mint shares
+pub fn setup_share_token<'a>(e: &Env, pool: &Address) -> token::Client<'a> {
+    let addr = e.register_stellar_asset_contract(pool.clone());
+    token::Client::new(e, &addr)
+}
+
 pub fn setup_tc<'a>(
     e: &Env,
     admin: &Address,
@@ -48,8 +63,20 @@ pub fn setup_tc<'a>(
     client.initialize(&admin.clone(), ext_token_address, ext_token_decimals);
     client
 }
-/*
+
 pub fn set_ledger_timestamp(e: &Env, timestamp: u64) {
     e.ledger().with_mut(|li| li.timestamp = timestamp);
 }
-*/
+
+// moves the ledger past the grace period after a loan's due date, so that anyone can default it
+pub fn set_ledger_past_grace_period(e: &Env, client: &LiquidityPoolClient, offer_id: u64) {
+    let grace_end = client.get_loan_due_date(&offer_id) + client.get_grace_period();
+    set_ledger_timestamp(e, grace_end + 1);
+}
+
+// uploads "wasm" with an extra custom section, so that the same code gets a new hash and an upgrade to it swaps the executable
+pub fn upload_rebuilt_wasm(e: &Env, wasm: &[u8]) -> BytesN<32> {
+    let mut rebuilt = Bytes::from_slice(e, wasm);
+    rebuilt.extend_from_slice(&[0, 9, 7, b'u', b'p', b'g', b'r', b'a', b'd', b'e', 1]);
+    e.deployer().upload_contract_wasm(rebuilt)
+}
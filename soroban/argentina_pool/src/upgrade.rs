@@ -0,0 +1,84 @@
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::contract::{initial_interest, scale};
+use crate::loan::{
+    read_grace_period, read_supply, write_fee_bps, write_loan, InterestModel, Loan, LoanStatus,
+};
+use crate::storage_types::{DataKey, STORAGE_VERSION};
+
+// layout of loans before version 2
+#[derive(Clone)]
+#[contracttype(export = false)]
+pub struct LoanV1 {
+    pub borrower: Address,
+    pub creditor: Address,
+    pub amount: i128,
+    pub tc_address: Address,
+    pub tc_id: u64,
+    pub fee_percent: u32,
+    pub status: LoanStatus,
+}
+
+// contracts deployed before versioning was introduced have no stored version
+pub fn read_version(e: &Env) -> u32 {
+    let key = DataKey::Version;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_version(e: &Env, version: u32) {
+    let key = DataKey::Version;
+    e.storage().instance().set(&key, &version);
+}
+
+pub fn migrate(e: &Env) {
+    // version 1 did not change the layout of loans, so older storage starts at the version 2 step
+    if read_version(e) < 2 {
+        migrate_v2(e);
+    }
+    write_version(e, STORAGE_VERSION);
+}
+
+// version 2 replaced whole-percent fees with basis points, and added the interest model, loan terms and due dates,
+// the value of the TC, the recovery from liquidation and the outstanding balance for partial repayments
+fn migrate_v2(e: &Env) {
+    let key = DataKey::FeePercent;
+    if let Some(fee_percent) = e.storage().instance().get::<DataKey, u32>(&key) {
+        write_fee_bps(e, fee_percent * 100);
+        e.storage().instance().remove(&key);
+    }
+
+    for offer_id in 0..read_supply(e) {
+        let key = DataKey::Loan(offer_id);
+        if let Some(old) = e.storage().persistent().get::<DataKey, LoanV1>(&key) {
+            // existing loans keep charging their fee once and lent the full value of the TC
+            let mut loan = Loan {
+                borrower: old.borrower,
+                creditor: old.creditor,
+                principal: old.amount,
+                collateral_value: old.amount,
+                tc_address: old.tc_address,
+                tc_id: old.tc_id,
+                fee_bps: old.fee_percent * 100,
+                status: old.status,
+                interest_model: InterestModel::Flat,
+                start_time: 0,
+                term: 0,
+                grace_period: read_grace_period(e),
+                due_date: 0,
+                recovery: 0,
+                outstanding_principal: 0,
+                accrued_interest: 0,
+                accrual_time: 0,
+                interest_remainder: 0,
+            };
+            // existing loans had no term, so active ones fall due now and can be defaulted after the grace period.
+            // They owe their whole principal and fee.
+            if loan.status == LoanStatus::Active {
+                loan.due_date = e.ledger().timestamp();
+                loan.outstanding_principal = scale(e, loan.principal);
+                loan.accrued_interest = initial_interest(e, &loan);
+            }
+            write_loan(e, offer_id, loan);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::storage_types::{DataKey, PendingAdmin};
+use soroban_sdk::{Address, Env};
+
+pub fn has_admin(e: &Env) -> bool {
+    let key = DataKey::Admin;
+    e.storage().instance().has(&key)
+}
+
+pub fn write_admin(e: &Env, admin: &Address) {
+    let key = DataKey::Admin;
+    e.storage().instance().set(&key, admin);
+}
+
+pub fn read_admin(e: &Env) -> Address {
+    let key = DataKey::Admin;
+    e.storage().instance().get(&key).unwrap()
+}
+
+pub fn read_pending_admin(e: &Env) -> Option<PendingAdmin> {
+    let key = DataKey::PendingAdmin;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_pending_admin(e: &Env, pending_admin: &PendingAdmin) {
+    let key = DataKey::PendingAdmin;
+    e.storage().instance().set(&key, pending_admin);
+}
+
+pub fn remove_pending_admin(e: &Env) {
+    let key = DataKey::PendingAdmin;
+    e.storage().instance().remove(&key);
+}
+
+pub fn is_pending_admin_expired(e: &Env, pending_admin: &PendingAdmin) -> bool {
+    match pending_admin.expiration_time {
+        Some(expiration_time) => e.ledger().timestamp() > expiration_time,
+        None => false,
+    }
+}
@@ -1,9 +1,48 @@
+use crate::admin::{
+    has_admin, is_pending_admin_expired, read_admin, read_pending_admin, remove_pending_admin,
+    write_admin, write_pending_admin,
+};
 use crate::errors::Error;
+use crate::event;
+use crate::storage_types::{
+    PendingAdmin, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, STORAGE_VERSION,
+};
+use crate::upgrade::{migrate, read_version, write_version};
 use soroban_sdk::{
     contract, contractimpl, panic_with_error, Address, BytesN, Env, Symbol, Val, Vec,
 };
 
 pub trait DeployerTrait {
+    /// Initialize the contract with "admin" as administrator.
+    fn initialize(e: Env, admin: Address);
+
+    /// Propose "new_admin" as the next administrator. Can only be called by the admin. Replaces any pending proposal.
+    /// If "expiration_time" is set, the proposal can only be accepted until that Unix timestamp.
+    /// Emit event with topics = ["propose", admin: Address], data = [new_admin: Address, expiration_time: Option<u64>]
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>);
+
+    /// Called by the proposed administrator to become the administrator.
+    /// Emit event with topics = ["set_admin", admin: Address], data = [new_admin: Address]
+    fn accept_admin(e: Env);
+
+    /// Withdraw the pending administrator proposal. Can only be called by the admin.
+    /// Emit event with topics = ["unpropose", admin: Address], data = [new_admin: Address]
+    fn cancel_admin_proposal(e: Env);
+
+    /// Get the proposed administrator, if a proposal is pending and has not expired.
+    fn pending_admin(e: Env) -> Option<Address>;
+
+    /// Replace the contract code with the uploaded WASM identified by "new_wasm_hash". Can only be called by the admin.
+    /// Storage is kept as is; call migrate() afterwards if the new code uses a newer storage layout.
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>);
+
+    /// Rewrite storage written by an older version of the contract to the current layout. Can only be called by the admin.
+    /// Will fail if the storage is already up to date.
+    fn migrate(e: Env);
+
+    /// Get the version of the storage layout.
+    fn version(e: Env) -> u32;
+
     fn deploy_contract(
         e: Env,
         deployer: Address,
@@ -19,6 +58,117 @@ pub struct Deployer;
 
 #[contractimpl]
 impl DeployerTrait for Deployer {
+    fn initialize(e: Env, admin: Address) {
+        if has_admin(&e) {
+            panic!("already initialized")
+        }
+        write_admin(&e, &admin);
+        write_version(&e, STORAGE_VERSION);
+    }
+
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if let Some(expiration_time) = expiration_time {
+            if expiration_time <= e.ledger().timestamp() {
+                panic_with_error!(&e, Error::NotPermitted);
+            }
+        }
+        write_pending_admin(
+            &e,
+            &PendingAdmin {
+                address: new_admin.clone(),
+                expiration_time,
+            },
+        );
+        event::propose_admin(&e, admin, new_admin, expiration_time);
+    }
+
+    fn accept_admin(e: Env) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        if is_pending_admin_expired(&e, &pending_admin) {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let new_admin = pending_admin.address;
+        new_admin.require_auth();
+
+        let admin = read_admin(&e);
+        write_admin(&e, &new_admin);
+        remove_pending_admin(&e);
+        event::set_admin(&e, admin, new_admin);
+    }
+
+    fn cancel_admin_proposal(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        remove_pending_admin(&e);
+        event::cancel_admin_proposal(&e, admin, pending_admin.address);
+    }
+
+    fn pending_admin(e: Env) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        match read_pending_admin(&e) {
+            Some(pending_admin) if !is_pending_admin_expired(&e, &pending_admin) => {
+                Some(pending_admin.address)
+            }
+            _ => None,
+        }
+    }
+
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    fn migrate(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if read_version(&e) >= STORAGE_VERSION {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        migrate(&e);
+    }
+
+    fn version(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_version(&e)
+    }
+
     fn deploy_contract(
         e: Env,
         deployer: Address,
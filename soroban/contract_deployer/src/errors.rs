@@ -5,4 +5,6 @@ use soroban_sdk::contracterror;
 #[repr(u32)]
 pub enum Error {
     ArgumentLengthMismatch = 1,
+    NotPermitted = 2,
+    NotFound = 3,
 }
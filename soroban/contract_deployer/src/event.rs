@@ -0,0 +1,21 @@
+use soroban_sdk::{symbol_short, Address, Env};
+
+pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("set_admin"), admin);
+    e.events().publish(topics, new_admin);
+}
+
+pub(crate) fn propose_admin(
+    e: &Env,
+    admin: Address,
+    new_admin: Address,
+    expiration_time: Option<u64>,
+) {
+    let topics = (symbol_short!("propose"), admin);
+    e.events().publish(topics, (new_admin, expiration_time));
+}
+
+pub(crate) fn cancel_admin_proposal(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("unpropose"), admin);
+    e.events().publish(topics, new_admin);
+}
@@ -1,5 +1,9 @@
 #![no_std]
 
+mod admin;
 mod contract;
 mod errors;
+mod event;
+mod storage_types;
 mod test;
+mod upgrade;
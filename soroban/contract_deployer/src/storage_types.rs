@@ -0,0 +1,24 @@
+use soroban_sdk::{contracttype, Address};
+
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+pub(crate) const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// version of the storage layout, to be bumped along with a migration step whenever the layout changes
+pub(crate) const STORAGE_VERSION: u32 = 1;
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub address: Address,
+    // Unix timestamp after which the proposal can no longer be accepted
+    pub expiration_time: Option<u64>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Admin,
+    Version,
+    PendingAdmin,
+}
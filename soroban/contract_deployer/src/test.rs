@@ -1,8 +1,10 @@
 #![cfg(test)]
 use crate::contract::{Deployer, DeployerClient};
+use crate::errors::Error as ContractError;
 use soroban_sdk::{
-    testutils::{Address as _, BytesN as _},
-    vec, Address, BytesN, Env, IntoVal, Symbol,
+    symbol_short,
+    testutils::{Address as _, BytesN as _, Events},
+    vec, Address, BytesN, Env, Error, IntoVal, Symbol, Val, Vec,
 };
 
 mod tc_contract {
@@ -11,6 +13,12 @@ mod tc_contract {
     );
 }
 
+mod deployer_contract {
+    soroban_sdk::contractimport!(
+        file = "target/wasm32-unknown-unknown/release/contract_deployer.wasm"
+    );
+}
+
 fn install_token_wasm(e: &Env) -> BytesN<32> {
     soroban_sdk::contractimport!(
         file = "../scf_soroban/target/wasm32-unknown-unknown/release/scf_soroban.wasm"
@@ -25,6 +33,7 @@ fn test() {
     let admin = Address::generate(&e);
     let deployer_contract = e.register_contract(None, Deployer);
     let deployer_client = DeployerClient::new(&e, &deployer_contract);
+    deployer_client.initialize(&admin);
     let token = e.register_stellar_asset_contract(admin.clone());
 
     let wasm_hash = install_token_wasm(&e);
@@ -48,11 +57,94 @@ fn test() {
                 buyer.into_val(&e),
                 1000000u32.into_val(&e),
                 1714693253u64.into_val(&e),
+                Vec::<Val>::new(&e).into_val(&e),
+                ().into_val(&e),
+            ],
+            vec![
+                &e,
+                admin.into_val(&e),
+                token.into_val(&e),
+                7u32.into_val(&e),
             ],
-            vec![&e, token.into_val(&e), 7u32.into_val(&e)],
         ],
     );
 
     let tc_client = tc_contract::Client::new(&e, &new_tc_contract);
     assert_eq!(tc_client.admin(), admin);
 }
+
+#[test]
+fn test_upgrade() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let deployer_contract = e.register_contract(None, Deployer);
+    let deployer_client = DeployerClient::new(&e, &deployer_contract);
+    deployer_client.initialize(&admin);
+    assert_eq!(deployer_client.version(), 1);
+
+    // upgrade to the current build
+    let wasm_hash = e.deployer().upload_contract_wasm(deployer_contract::WASM);
+    deployer_client.upgrade(&wasm_hash);
+    assert_eq!(deployer_client.version(), 1);
+
+    // storage written by the current version needs no migration
+    let res = deployer_client.try_migrate();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+}
+
+#[test]
+fn test_admin_handover() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let deployer_contract = e.register_contract(None, Deployer);
+    let deployer_client = DeployerClient::new(&e, &deployer_contract);
+    deployer_client.initialize(&admin);
+
+    // the admin does not change until the proposal is accepted
+    let new_admin = Address::generate(&e);
+    deployer_client.propose_admin(&new_admin, &None);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("propose"), admin.clone()).into_val(&e)
+            );
+            let data: (Address, Option<u64>) = data.into_val(&e);
+            assert_eq!(data, (new_admin.clone(), None));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(deployer_client.pending_admin(), Some(new_admin.clone()));
+    deployer_client.cancel_admin_proposal();
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("unpropose"), admin.clone()).into_val(&e)
+            );
+            let data: Address = data.into_val(&e);
+            assert_eq!(data, new_admin.clone());
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(deployer_client.pending_admin(), None);
+    let res = deployer_client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+
+    deployer_client.propose_admin(&new_admin, &None);
+    deployer_client.accept_admin();
+    assert_eq!(e.auths()[0].0, new_admin.clone());
+    assert_eq!(deployer_client.pending_admin(), None);
+}
@@ -0,0 +1,18 @@
+use crate::storage_types::{DataKey, STORAGE_VERSION};
+use soroban_sdk::Env;
+
+// contracts deployed before versioning was introduced have no stored version
+pub fn read_version(e: &Env) -> u32 {
+    let key = DataKey::Version;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_version(e: &Env, version: u32) {
+    let key = DataKey::Version;
+    e.storage().instance().set(&key, &version);
+}
+
+pub fn migrate(e: &Env) {
+    // the deployer kept no state before version 1
+    write_version(e, STORAGE_VERSION);
+}
@@ -0,0 +1,34 @@
+use crate::storage_types::{Auction, DataKey, AUCTION_BUMP_AMOUNT, AUCTION_LIFETIME_THRESHOLD};
+use soroban_sdk::Env;
+
+pub fn read_auction(e: &Env, auction_id: i128) -> Option<Auction> {
+    let key = DataKey::Auction(auction_id);
+    if let Some(auction) = e.storage().persistent().get::<DataKey, Auction>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, AUCTION_LIFETIME_THRESHOLD, AUCTION_BUMP_AMOUNT);
+        Some(auction)
+    } else {
+        None
+    }
+}
+
+pub fn write_auction(e: &Env, auction_id: i128, auction: &Auction) {
+    let key = DataKey::Auction(auction_id);
+    e.storage().persistent().set(&key, auction);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, AUCTION_LIFETIME_THRESHOLD, AUCTION_BUMP_AMOUNT);
+}
+
+pub fn read_auction_supply(e: &Env) -> i128 {
+    let key = DataKey::AuctionSupply;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn increment_auction_supply(e: &Env) {
+    let key = DataKey::AuctionSupply;
+    e.storage()
+        .instance()
+        .set(&key, &(read_auction_supply(e) + 1));
+}
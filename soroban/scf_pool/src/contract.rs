@@ -1,12 +1,36 @@
-use crate::admin::{has_administrator, read_administrator, write_administrator};
+use crate::admin::{
+    has_administrator, is_pending_admin_expired, read_administrator, read_pending_admin,
+    remove_pending_admin, write_administrator, write_pending_admin,
+};
+use crate::auction::{increment_auction_supply, read_auction, read_auction_supply, write_auction};
 use crate::error::Error;
 use crate::event;
+use crate::fee::{
+    compute_fee, read_accrued_fees, read_fee_bps, read_fee_recipient, write_accrued_fees,
+    write_fee_bps, write_fee_recipient,
+};
 use crate::interface::OfferPoolTrait;
-use crate::offer::{change_offer, increment_supply, read_offer, read_supply, write_offer};
+use crate::offer::{
+    change_offer, increment_supply, index_offer, is_offer_expired, read_offer,
+    read_offers_by_creator, read_offers_by_status, read_offers_for_tc, read_ranked_offers,
+    read_supply, write_offer,
+};
+use crate::pause::{
+    check_exit_allowed, check_not_paused, is_paused, read_exits_allowed, write_exits_allowed,
+    write_paused,
+};
 use crate::pool_token::{has_ext_token, read_ext_tokens, write_ext_tokens};
-use crate::storage_types::{Offer, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
+use crate::pricing::{implied_yield_bps, offer_face_value, offer_price};
+use crate::roles::{check_role, has_role, write_role, ALL_ROLES};
+use crate::storage_types::{
+    Auction, AuctionStatus, Offer, OfferStatus, OfferTerms, PendingAdmin, Role,
+    INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD, STORAGE_VERSION,
+};
+use crate::upgrade::{migrate, read_version, write_version};
 
-use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Env, Vec};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token, vec, Address, BytesN, Env, Vec,
+};
 
 mod tc {
     soroban_sdk::contractimport!(
@@ -24,6 +48,10 @@ impl OfferPoolTrait for OfferPool {
             panic!("already initialized")
         }
         write_administrator(&e, &admin);
+        for role in ALL_ROLES {
+            write_role(&e, role, &admin, true);
+        }
+        write_version(&e, STORAGE_VERSION);
     }
 
     fn admin(env: Env) -> Address {
@@ -33,21 +61,203 @@ impl OfferPoolTrait for OfferPool {
         read_administrator(&env)
     }
 
-    fn set_admin(env: Env, new_admin: Address) {
-        env.storage()
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let admin = read_administrator(&env);
+
+        if let Some(expiration_time) = expiration_time {
+            if expiration_time <= e.ledger().timestamp() {
+                panic_with_error!(&e, Error::NotPermitted);
+            }
+        }
+        write_pending_admin(
+            &e,
+            &PendingAdmin {
+                address: new_admin.clone(),
+                expiration_time,
+            },
+        );
+        event::propose_admin(&e, admin, new_admin, expiration_time);
+    }
+
+    fn accept_admin(e: Env) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        if is_pending_admin_expired(&e, &pending_admin) {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let new_admin = pending_admin.address;
+        new_admin.require_auth();
+
+        // the roles of the outgoing admin pass to the new admin, so the old key keeps none of them
+        let admin = read_administrator(&e);
+        for role in ALL_ROLES {
+            if has_role(&e, role, &admin) {
+                write_role(&e, role, &admin, false);
+                write_role(&e, role, &new_admin, true);
+            }
+        }
+        write_administrator(&e, &new_admin);
+        remove_pending_admin(&e);
+        event::set_admin(&e, admin, new_admin);
+    }
+
+    fn cancel_admin_proposal(e: Env) {
+        let admin = read_administrator(&e);
         admin.require_auth();
 
-        write_administrator(&env, &new_admin);
-        event::set_admin(&env, admin, new_admin);
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let pending_admin = match read_pending_admin(&e) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&e, Error::NotFound),
+        };
+        remove_pending_admin(&e);
+        event::cancel_admin_proposal(&e, admin, pending_admin.address);
+    }
+
+    fn pending_admin(e: Env) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        match read_pending_admin(&e) {
+            Some(pending_admin) if !is_pending_admin_expired(&e, &pending_admin) => {
+                Some(pending_admin.address)
+            }
+            _ => None,
+        }
+    }
+
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        e.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    fn migrate(e: Env, start: i128, limit: u32) -> i128 {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        if read_version(&e) >= STORAGE_VERSION {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        // storage written before versioning predates roles, so the admin is granted the roles it held implicitly
+        if read_version(&e) < 1 {
+            for role in ALL_ROLES {
+                write_role(&e, role, &admin, true);
+            }
+        }
+        migrate(&e, start, limit)
+    }
+
+    fn version(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_version(&e)
+    }
+
+    fn grant_role(e: Env, role: Role, account: Address) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_role(&e, role, &account, true);
+        event::grant_role(&e, admin, role, account);
     }
 
-    fn add_ext_token(e: Env, ext_token_address: Address) {
+    fn revoke_role(e: Env, role: Role, account: Address) {
         let admin = read_administrator(&e);
         admin.require_auth();
 
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_role(&e, role, &account, false);
+        event::revoke_role(&e, admin, role, account);
+    }
+
+    fn has_role(e: Env, role: Role, account: Address) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        has_role(&e, role, &account)
+    }
+
+    fn pause(e: Env, caller: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&e, true);
+        event::pause(&e, caller);
+    }
+
+    fn unpause(e: Env, caller: Address) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&e, false);
+        event::unpause(&e, caller);
+    }
+
+    fn is_paused(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        is_paused(&e)
+    }
+
+    fn set_exits_allowed(e: Env, caller: Address, allowed: bool) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&e, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_exits_allowed(&e, allowed);
+    }
+
+    fn exits_allowed(e: Env) -> bool {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_exits_allowed(&e)
+    }
+
+    fn add_ext_token(e: Env, caller: Address, ext_token_address: Address) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -60,9 +270,9 @@ impl OfferPoolTrait for OfferPool {
         write_ext_tokens(&e, token_list);
     }
 
-    fn remove_ext_token(e: Env, ext_token_address: Address) {
-        let admin = read_administrator(&e);
-        admin.require_auth();
+    fn remove_ext_token(e: Env, caller: Address, ext_token_address: Address) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
 
         e.storage()
             .instance()
@@ -76,6 +286,74 @@ impl OfferPoolTrait for OfferPool {
         write_ext_tokens(&e, token_list);
     }
 
+    fn set_fee_bps(e: Env, caller: Address, fee_bps: u32) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        if fee_bps > 10000 {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        write_fee_bps(&e, fee_bps);
+    }
+
+    fn fee_bps(e: Env) -> u32 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_fee_bps(&e)
+    }
+
+    fn set_fee_recipient(e: Env, caller: Address, ext_token: Address, recipient: Address) {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        write_fee_recipient(&e, ext_token, &recipient);
+    }
+
+    fn fee_recipient(e: Env, ext_token: Address) -> Option<Address> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_fee_recipient(&e, ext_token)
+    }
+
+    fn accrued_fees(e: Env, ext_token: Address) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_accrued_fees(&e, ext_token)
+    }
+
+    fn withdraw_fees(e: Env, caller: Address, ext_token: Address) -> i128 {
+        check_role(&e, Role::Treasury, &caller);
+        caller.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let recipient = match read_fee_recipient(&e, ext_token.clone()) {
+            Some(recipient) => recipient,
+            None => panic_with_error!(&e, Error::FeeRecipientEmpty),
+        };
+        let amount = read_accrued_fees(&e, ext_token.clone());
+        if amount > 0 {
+            write_accrued_fees(&e, ext_token.clone(), 0);
+            token::Client::new(&e, &ext_token).transfer(
+                &e.current_contract_address(),
+                &recipient,
+                &amount,
+            );
+        }
+        event::withdraw_fees(&e, ext_token, recipient, amount);
+        amount
+    }
+
     fn get_ext_tokens(e: Env) -> Vec<Address> {
         e.storage()
             .instance()
@@ -92,10 +370,12 @@ impl OfferPoolTrait for OfferPool {
         amount: i128,
         tc_contract: Address,
         tc_id: i128,
+        terms: OfferTerms,
     ) -> i128 {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
         if !has_ext_token(&e, ext_token.clone()) {
             panic_with_error!(&e, Error::TokenNotSupported);
         }
@@ -107,20 +387,53 @@ impl OfferPoolTrait for OfferPool {
         if tc_client.is_disabled(&tc_id) {
             panic_with_error!(&e, Error::TCDisabled);
         }
+        if tc_client.is_frozen(&tc_id) {
+            panic_with_error!(&e, Error::TCFrozen);
+        }
+        if tc_client.check_expired() {
+            panic_with_error!(&e, Error::TCExpired);
+        }
+        if let Some(valid_until) = terms.valid_until {
+            if valid_until <= e.ledger().timestamp() {
+                panic_with_error!(&e, Error::NotPermitted);
+            }
+        }
+        // a partial offer must leave part of the TC to the seller
+        if let Some(tc_amount) = terms.tc_amount {
+            if tc_amount == 0 || tc_amount >= tc_client.amount(&tc_id) {
+                panic_with_error!(&e, Error::NotPermitted);
+            }
+        }
 
         from.require_auth();
         token_client.transfer(&from, &e.current_contract_address(), &amount);
 
         let offer_id = read_supply(&e);
 
-        write_offer(
-            &e,
-            offer_id,
-            from.clone(),
-            ext_token,
+        let offer = Offer {
+            from: from.clone(),
+            pool_token: ext_token,
             amount,
             tc_contract,
             tc_id,
+            status: OfferStatus::Pending,
+            valid_until: terms.valid_until,
+            discount_bps: terms.discount_bps,
+            price: 0,
+            yield_bps: 0,
+            tc_amount: terms.tc_amount,
+            filled_tc_id: None,
+            fee_bps: read_fee_bps(&e),
+            fee: 0,
+        };
+        write_offer(&e, offer_id, &offer);
+        index_offer(
+            &e,
+            offer_id,
+            offer.status,
+            &offer.from,
+            &offer.tc_contract,
+            offer.tc_id,
         );
 
         increment_supply(&e);
@@ -128,20 +441,20 @@ impl OfferPoolTrait for OfferPool {
         return offer_id;
     }
 
-    // Cancels an offer and returns the offered amount to the owner. Callable by the admin or offer owner.
+    // Cancels an offer and returns the offered amount to the owner. Callable by a holder of the Risk role or the offer owner.
     fn expire_offer(e: Env, from: Address, offer_id: i128) {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
         match read_offer(&e, offer_id) {
             Some(offer) => {
-                if offer.status != 0 {
+                if offer.status != OfferStatus::Pending {
                     panic_with_error!(&e, Error::OfferChanged);
                 }
-                // check that 'from' matches either the admin or the offer owner
-                let admin = read_administrator(&e);
+                // check that 'from' either holds the Risk role or is the offer owner
                 let offer_from = offer.from;
-                if (from != admin) && (from != offer_from) {
+                if (from != offer_from) && !has_role(&e, Role::Risk, &from) {
                     panic_with_error!(&e, Error::NotAuthorized);
                 }
 
@@ -151,13 +464,39 @@ impl OfferPoolTrait for OfferPool {
                 let token_client = token::Client::new(&e, &offer.pool_token);
 
                 token_client.transfer(&e.current_contract_address(), &offer_from, &amount);
-                change_offer(&e, offer_id, 1);
+                change_offer(&e, offer_id, OfferStatus::Expired);
                 event::expire_offer(&e, from, offer_id);
             }
             None => panic_with_error!(&e, Error::OfferEmpty),
         }
     }
 
+    // Returns the offered amount to the owner once the offer or its TC has expired. Callable by anyone.
+    fn reclaim_expired(e: Env, offer_id: i128) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
+        match read_offer(&e, offer_id) {
+            Some(offer) => {
+                if offer.status != OfferStatus::Pending {
+                    panic_with_error!(&e, Error::OfferChanged);
+                }
+                if !is_offer_expired(&e, &offer)
+                    && !tc::Client::new(&e, &offer.tc_contract).check_expired()
+                {
+                    panic_with_error!(&e, Error::NotPermitted);
+                }
+
+                let token_client = token::Client::new(&e, &offer.pool_token);
+                token_client.transfer(&e.current_contract_address(), &offer.from, &offer.amount);
+                change_offer(&e, offer_id, OfferStatus::Expired);
+                event::reclaim_expired(&e, offer.from, offer_id);
+            }
+            None => panic_with_error!(&e, Error::OfferEmpty),
+        }
+    }
+
     fn get_offer(e: Env, offer_id: i128) -> Offer {
         e.storage()
             .instance()
@@ -169,19 +508,55 @@ impl OfferPoolTrait for OfferPool {
         }
     }
 
+    fn list_offers(e: Env, status: OfferStatus, start: u32, limit: u32) -> Vec<i128> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_offers_by_status(&e, status, start, limit)
+    }
+
+    fn offers_by_creator(e: Env, from: Address) -> Vec<i128> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_offers_by_creator(&e, from)
+    }
+
+    fn offers_for_tc(e: Env, tc_contract: Address, tc_id: i128) -> Vec<i128> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_offers_for_tc(&e, tc_contract, tc_id)
+    }
+
+    fn ranked_offers(e: Env, tc_contract: Address, tc_id: i128) -> Vec<i128> {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let tc_client = tc::Client::new(&e, &tc_contract);
+        let (_, decimals) = tc_client.external_token();
+        let amount = tc_client.amount(&tc_id);
+        let time_left = tc_client.end_time().saturating_sub(e.ledger().timestamp());
+        read_ranked_offers(&e, tc_contract, tc_id, amount, decimals, time_left)
+    }
+
     // On accepting an offer, the offered amount in tokens is transferred from to contract address to 'to' and the TC is transferred to the offer creator.
     fn accept_offer(e: Env, to: Address, offer_id: i128) {
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
         match read_offer(&e, offer_id) {
-            Some(offer) => {
-                if offer.status != 0 {
+            Some(mut offer) => {
+                if offer.status != OfferStatus::Pending {
                     panic_with_error!(&e, Error::OfferChanged);
                 }
-                let from = offer.from;
+                if is_offer_expired(&e, &offer) {
+                    panic_with_error!(&e, Error::OfferExpired);
+                }
+                let from = offer.from.clone();
                 let amount = offer.amount;
-                let tc_contract = offer.tc_contract;
+                let tc_contract = offer.tc_contract.clone();
                 let tc_id = offer.tc_id;
 
                 let token_client = token::Client::new(&e, &offer.pool_token);
@@ -189,15 +564,244 @@ impl OfferPoolTrait for OfferPool {
                 if tc_client.is_disabled(&tc_id) {
                     panic_with_error!(&e, Error::TCDisabled);
                 }
+                if tc_client.is_frozen(&tc_id) {
+                    panic_with_error!(&e, Error::TCFrozen);
+                }
+                if tc_client.check_expired() {
+                    panic_with_error!(&e, Error::TCExpired);
+                }
+
+                // price the TC, or the part of it being bought, against its face value and time to maturity
+                let (_, decimals) = tc_client.external_token();
+                let face_value = offer_face_value(&offer, tc_client.amount(&tc_id), decimals);
+                let time_left = tc_client.end_time() - e.ledger().timestamp();
+                let price = offer_price(&offer, face_value, time_left);
+                if price > amount {
+                    panic_with_error!(&e, Error::OfferUnderfunded);
+                }
+                let yield_bps = implied_yield_bps(face_value, price, time_left);
+
+                // the TC is taken from 'to', so 'to' must own it, also when only part of it is split off
+                if tc_client.owner(&tc_id) != to {
+                    panic_with_error!(&e, Error::NotAuthorized);
+                }
                 to.require_auth();
-                tc_client.transfer(&to, &from, &tc_id);
+                match offer.tc_amount {
+                    Some(tc_amount) => {
+                        // split off the part of the TC that was bought, the rest stays with the seller
+                        let contract_address = e.current_contract_address();
+                        let splits = vec![
+                            &e,
+                            tc::SplitRequest {
+                                amount: tc_amount,
+                                to: contract_address.clone(),
+                            },
+                        ];
+                        let filled_tc_id = tc_client.split(&tc_id, &splits).get_unchecked(0);
+                        tc_client.sign_off(&filled_tc_id);
+                        tc_client.transfer(&contract_address, &from, &filled_tc_id);
+                        offer.filled_tc_id = Some(filled_tc_id);
+                    }
+                    None => tc_client.transfer(&to, &from, &tc_id),
+                }
 
-                token_client.transfer(&e.current_contract_address(), &to, &amount);
+                // the fee stays in the contract until it is withdrawn to the fee recipient
+                let fee = compute_fee(price, offer.fee_bps);
+                token_client.transfer(&e.current_contract_address(), &to, &(price - fee));
+                if fee > 0 {
+                    let accrued = read_accrued_fees(&e, offer.pool_token.clone());
+                    write_accrued_fees(&e, offer.pool_token.clone(), accrued + fee);
+                }
+                if amount > price {
+                    token_client.transfer(&e.current_contract_address(), &from, &(amount - price));
+                }
 
-                change_offer(&e, offer_id, 2);
-                event::accept_offer(&e, to, offer_id);
+                offer.price = price;
+                offer.yield_bps = yield_bps;
+                offer.fee = fee;
+                write_offer(&e, offer_id, &offer);
+                change_offer(&e, offer_id, OfferStatus::Accepted);
+
+                // the TC is gone, so every competing offer on it is refunded and closed
+                refund_pending_offers(&e, tc_contract, tc_id);
+                event::accept_offer(&e, to, offer_id, price, yield_bps, fee);
             }
             None => panic_with_error!(&e, Error::OfferEmpty),
         }
     }
+
+    fn start_auction(
+        e: Env,
+        seller: Address,
+        tc_contract: Address,
+        tc_id: i128,
+        ext_token: Address,
+        reserve: i128,
+        end_time: u64,
+    ) -> i128 {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
+        if !has_ext_token(&e, ext_token.clone()) {
+            panic_with_error!(&e, Error::TokenNotSupported);
+        }
+        if reserve <= 0 || end_time <= e.ledger().timestamp() {
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        let tc_client = tc::Client::new(&e, &tc_contract);
+        if end_time > tc_client.end_time() {
+            // the auction must be settled while the TC can still be sold
+            panic_with_error!(&e, Error::NotPermitted);
+        }
+        if tc_client.is_disabled(&tc_id) {
+            panic_with_error!(&e, Error::TCDisabled);
+        }
+        if tc_client.is_frozen(&tc_id) {
+            panic_with_error!(&e, Error::TCFrozen);
+        }
+        if tc_client.check_expired() {
+            panic_with_error!(&e, Error::TCExpired);
+        }
+
+        // hold the TC in the contract until the auction is settled
+        seller.require_auth();
+        tc_client.transfer(&seller, &e.current_contract_address(), &tc_id);
+
+        let auction_id = read_auction_supply(&e);
+        let auction = Auction {
+            seller: seller.clone(),
+            tc_contract,
+            tc_id,
+            ext_token,
+            reserve,
+            end_time,
+            highest_bidder: None,
+            highest_bid: 0,
+            status: AuctionStatus::Open,
+            fee_bps: read_fee_bps(&e),
+            fee: 0,
+        };
+        write_auction(&e, auction_id, &auction);
+        increment_auction_supply(&e);
+        event::start_auction(&e, seller, auction_id, reserve);
+        auction_id
+    }
+
+    // Locks the bid in the contract and refunds the bid it outbids.
+    fn bid(e: Env, bidder: Address, auction_id: i128, amount: i128) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&e);
+        let mut auction = match read_auction(&e, auction_id) {
+            Some(auction) => auction,
+            None => panic_with_error!(&e, Error::AuctionEmpty),
+        };
+        if auction.status != AuctionStatus::Open {
+            panic_with_error!(&e, Error::AuctionChanged);
+        }
+        if e.ledger().timestamp() >= auction.end_time {
+            panic_with_error!(&e, Error::AuctionEnded);
+        }
+        if amount < auction.reserve || amount <= auction.highest_bid {
+            panic_with_error!(&e, Error::BidTooLow);
+        }
+
+        bidder.require_auth();
+        let token_client = token::Client::new(&e, &auction.ext_token);
+        token_client.transfer(&bidder, &e.current_contract_address(), &amount);
+        if let Some(previous_bidder) = auction.highest_bidder {
+            token_client.transfer(
+                &e.current_contract_address(),
+                &previous_bidder,
+                &auction.highest_bid,
+            );
+        }
+
+        auction.highest_bidder = Some(bidder.clone());
+        auction.highest_bid = amount;
+        write_auction(&e, auction_id, &auction);
+        event::bid(&e, bidder, auction_id, amount);
+    }
+
+    // Hands out the TC and the proceeds once bidding has closed. Callable by anyone.
+    fn settle_auction(e: Env, auction_id: i128) {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_exit_allowed(&e);
+        let mut auction = match read_auction(&e, auction_id) {
+            Some(auction) => auction,
+            None => panic_with_error!(&e, Error::AuctionEmpty),
+        };
+        if auction.status != AuctionStatus::Open {
+            panic_with_error!(&e, Error::AuctionChanged);
+        }
+        if e.ledger().timestamp() < auction.end_time {
+            panic_with_error!(&e, Error::AuctionNotEnded);
+        }
+
+        let tc_client = tc::Client::new(&e, &auction.tc_contract);
+        // bids below the reserve are rejected, so any bid means the reserve was met
+        let to = match auction.highest_bidder.clone() {
+            Some(winner) => {
+                // the fee stays in the contract until it is withdrawn to the fee recipient, as for offers
+                let fee = compute_fee(auction.highest_bid, auction.fee_bps);
+                token::Client::new(&e, &auction.ext_token).transfer(
+                    &e.current_contract_address(),
+                    &auction.seller,
+                    &(auction.highest_bid - fee),
+                );
+                if fee > 0 {
+                    let accrued = read_accrued_fees(&e, auction.ext_token.clone());
+                    write_accrued_fees(&e, auction.ext_token.clone(), accrued + fee);
+                }
+                auction.fee = fee;
+                auction.status = AuctionStatus::Sold;
+                // the TC changes hands, so the offers made on it can no longer be accepted
+                refund_pending_offers(&e, auction.tc_contract.clone(), auction.tc_id);
+                winner
+            }
+            None => {
+                auction.status = AuctionStatus::Unsold;
+                auction.seller.clone()
+            }
+        };
+        tc_client.transfer(&e.current_contract_address(), &to, &auction.tc_id);
+
+        let price = match auction.status {
+            AuctionStatus::Sold => auction.highest_bid,
+            _ => 0,
+        };
+        event::settle_auction(&e, to, auction_id, price, auction.fee);
+        write_auction(&e, auction_id, &auction);
+    }
+
+    fn get_auction(e: Env, auction_id: i128) -> Auction {
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        match read_auction(&e, auction_id) {
+            Some(auction) => auction,
+            None => panic_with_error!(&e, Error::AuctionEmpty),
+        }
+    }
+}
+
+fn refund_pending_offers(e: &Env, tc_contract: Address, tc_id: i128) {
+    for offer_id in read_offers_for_tc(e, tc_contract, tc_id) {
+        if let Some(offer) = read_offer(e, offer_id) {
+            if offer.status != OfferStatus::Pending {
+                continue;
+            }
+            token::Client::new(e, &offer.pool_token).transfer(
+                &e.current_contract_address(),
+                &offer.from,
+                &offer.amount,
+            );
+            change_offer(e, offer_id, OfferStatus::Expired);
+            event::refund_offer(e, offer.from, offer_id);
+        }
+    }
 }
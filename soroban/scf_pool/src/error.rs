@@ -11,4 +11,17 @@ pub enum Error {
     NotAuthorized = 5,
     TokenNotSupported = 6,
     TCDisabled = 7,
+    NotPermitted = 8,
+    NotFound = 9,
+    Paused = 10,
+    TCFrozen = 11,
+    OfferExpired = 12,
+    TCExpired = 13,
+    AuctionEmpty = 14,
+    AuctionChanged = 15,
+    AuctionEnded = 16,
+    AuctionNotEnded = 17,
+    BidTooLow = 18,
+    OfferUnderfunded = 19,
+    FeeRecipientEmpty = 20,
 }
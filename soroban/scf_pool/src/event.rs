@@ -1,3 +1,4 @@
+use crate::storage_types::Role;
 use soroban_sdk::{symbol_short, Address, Env};
 
 pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
@@ -5,6 +6,31 @@ pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
     e.events().publish(topics, new_admin);
 }
 
+pub(crate) fn propose_admin(
+    e: &Env,
+    admin: Address,
+    new_admin: Address,
+    expiration_time: Option<u64>,
+) {
+    let topics = (symbol_short!("propose"), admin);
+    e.events().publish(topics, (new_admin, expiration_time));
+}
+
+pub(crate) fn cancel_admin_proposal(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("unpropose"), admin);
+    e.events().publish(topics, new_admin);
+}
+
+pub(crate) fn grant_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("grant"), role, account);
+    e.events().publish(topics, admin);
+}
+
+pub(crate) fn revoke_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("revoke"), role, account);
+    e.events().publish(topics, admin);
+}
+
 pub fn create_offer(e: &Env, from: Address, offer_id: i128, amount: i128) {
     let topics = (symbol_short!("create"), from, amount);
     e.events().publish(topics, offer_id.clone());
@@ -15,7 +41,48 @@ pub fn expire_offer(e: &Env, from: Address, offer_id: i128) {
     e.events().publish(topics, offer_id.clone());
 }
 
-pub fn accept_offer(e: &Env, to: Address, offer_id: i128) {
+pub fn accept_offer(e: &Env, to: Address, offer_id: i128, price: i128, yield_bps: i128, fee: i128) {
     let topics = (symbol_short!("accept"), to.clone());
-    e.events().publish(topics, offer_id.clone());
+    e.events()
+        .publish(topics, (offer_id, price, yield_bps, fee));
+}
+
+pub(crate) fn reclaim_expired(e: &Env, from: Address, offer_id: i128) {
+    let topics = (symbol_short!("reclaim"), from);
+    e.events().publish(topics, offer_id);
+}
+
+pub(crate) fn refund_offer(e: &Env, from: Address, offer_id: i128) {
+    let topics = (symbol_short!("refund"), from);
+    e.events().publish(topics, offer_id);
+}
+
+pub(crate) fn withdraw_fees(e: &Env, ext_token: Address, recipient: Address, amount: i128) {
+    let topics = (symbol_short!("fees"), ext_token, recipient);
+    e.events().publish(topics, amount);
+}
+
+pub(crate) fn start_auction(e: &Env, seller: Address, auction_id: i128, reserve: i128) {
+    let topics = (symbol_short!("auction"), seller, reserve);
+    e.events().publish(topics, auction_id);
+}
+
+pub(crate) fn bid(e: &Env, bidder: Address, auction_id: i128, amount: i128) {
+    let topics = (symbol_short!("bid"), bidder, amount);
+    e.events().publish(topics, auction_id);
+}
+
+pub(crate) fn settle_auction(e: &Env, to: Address, auction_id: i128, price: i128, fee: i128) {
+    let topics = (symbol_short!("settle"), to);
+    e.events().publish(topics, (auction_id, price, fee));
+}
+
+pub(crate) fn pause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("pause"), caller);
+    e.events().publish(topics, ());
+}
+
+pub(crate) fn unpause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("unpause"), caller);
+    e.events().publish(topics, ());
 }
@@ -0,0 +1,37 @@
+use crate::storage_types::DataKey;
+use soroban_sdk::{Address, Env};
+
+// fee charged on new offers and auctions, in basis points of the price paid
+pub fn read_fee_bps(e: &Env) -> u32 {
+    let key = DataKey::FeeBps;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_fee_bps(e: &Env, fee_bps: u32) {
+    let key = DataKey::FeeBps;
+    e.storage().instance().set(&key, &fee_bps);
+}
+
+pub fn read_fee_recipient(e: &Env, ext_token: Address) -> Option<Address> {
+    let key = DataKey::FeeRecipient(ext_token);
+    e.storage().instance().get(&key)
+}
+
+pub fn write_fee_recipient(e: &Env, ext_token: Address, recipient: &Address) {
+    let key = DataKey::FeeRecipient(ext_token);
+    e.storage().instance().set(&key, recipient);
+}
+
+pub fn read_accrued_fees(e: &Env, ext_token: Address) -> i128 {
+    let key = DataKey::AccruedFees(ext_token);
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_accrued_fees(e: &Env, ext_token: Address, amount: i128) {
+    let key = DataKey::AccruedFees(ext_token);
+    e.storage().instance().set(&key, &amount);
+}
+
+pub fn compute_fee(price: i128, fee_bps: u32) -> i128 {
+    price * i128::from(fee_bps) / 10000
+}
@@ -1,5 +1,5 @@
-use crate::storage_types::Offer;
-use soroban_sdk::{Address, Env, Vec};
+use crate::storage_types::{Auction, Offer, OfferStatus, OfferTerms, Role};
+use soroban_sdk::{Address, BytesN, Env, Vec};
 
 pub trait OfferPoolTrait {
     /// Initialize the contract with an admin
@@ -12,20 +12,101 @@ pub trait OfferPoolTrait {
     // Admin interface
     // --------------------------------------------------------------------------------
 
-    /// Set the contact admin. Must be called by the current admin.
-    fn set_admin(e: Env, admin: Address);
+    /// Propose "new_admin" as the next administrator. Must be called by the admin. Replaces any pending proposal.
+    /// If "expiration_time" is set, the proposal can only be accepted until that Unix timestamp.
+    /// Emit event with topics = ["propose", admin: Address], data = [new_admin: Address, expiration_time: Option<u64>]
+    fn propose_admin(e: Env, new_admin: Address, expiration_time: Option<u64>);
 
-    /// Add support for an external token, like a liquidity pool token. Must be called by the admin.
-    fn add_ext_token(e: Env, ext_token: Address);
+    /// Called by the proposed administrator to become the administrator. The roles held by the previous administrator are moved to the new one.
+    /// Emit event with topics = ["set_admin", admin: Address], data = [new_admin: Address]
+    fn accept_admin(e: Env);
 
-    /// Remove support for an external token. Must be called by the admin.
-    fn remove_ext_token(e: Env, ext_token: Address);
+    /// Withdraw the pending administrator proposal. Must be called by the admin.
+    /// Emit event with topics = ["unpropose", admin: Address], data = [new_admin: Address]
+    fn cancel_admin_proposal(e: Env);
+
+    /// Get the proposed administrator, if a proposal is pending and has not expired.
+    fn pending_admin(e: Env) -> Option<Address>;
+
+    /// Replace the contract code with the uploaded WASM identified by "new_wasm_hash". Must be called by the admin.
+    /// Storage is kept as is; call migrate() afterwards if the new code uses a newer storage layout.
+    fn upgrade(e: Env, new_wasm_hash: BytesN<32>);
+
+    /// Rewrite storage written by an older version of the contract to the current layout. Must be called by the admin.
+    /// Each call migrates at most "limit" offers from id "start" on and returns the id the next call starts at; the first call starts at 0.
+    /// The version is bumped after the last offer, until then the progress is kept and a call from any other "start" fails.
+    /// Will fail if the storage is already up to date.
+    fn migrate(e: Env, start: i128, limit: u32) -> i128;
+
+    /// Get the version of the storage layout.
+    fn version(e: Env) -> u32;
+
+    /// Grant "role" to "account". Must be called by the admin. The admin holds every role after initialization.
+    /// Emit event with topics = ["grant", role: Role, account: Address], data = [admin: Address]
+    fn grant_role(e: Env, role: Role, account: Address);
+
+    /// Revoke "role" from "account". Must be called by the admin.
+    /// Emit event with topics = ["revoke", role: Role, account: Address], data = [admin: Address]
+    fn revoke_role(e: Env, role: Role, account: Address);
+
+    /// Check whether "account" holds "role".
+    fn has_role(e: Env, role: Role, account: Address) -> bool;
+
+    /// Stop the state-changing entry points until unpause() is called. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["pause", caller: Address], data = []
+    fn pause(e: Env, caller: Address);
+
+    /// Resume normal operation after a pause. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["unpause", caller: Address], data = []
+    fn unpause(e: Env, caller: Address);
+
+    /// Check whether the contract is paused.
+    fn is_paused(e: Env) -> bool;
+
+    /// Set whether expire_offer(), reclaim_expired() and settle_auction() remain available while the contract is paused. "caller" must hold the Guardian role.
+    /// Exits are allowed by default, so that users can always withdraw their funds.
+    fn set_exits_allowed(e: Env, caller: Address, allowed: bool);
+
+    /// Check whether expire_offer(), reclaim_expired() and settle_auction() remain available while the contract is paused.
+    fn exits_allowed(e: Env) -> bool;
+
+    /// Add support for an external token, like a liquidity pool token. "caller" must hold the Treasury role.
+    fn add_ext_token(e: Env, caller: Address, ext_token: Address);
+
+    /// Remove support for an external token. "caller" must hold the Treasury role.
+    fn remove_ext_token(e: Env, caller: Address, ext_token: Address);
+
+    /// Set the fee charged on offers and auctions created from now on, in basis points of the price paid. "caller" must hold the Treasury role.
+    /// Will fail if the fee is above 10000.
+    fn set_fee_bps(e: Env, caller: Address, fee_bps: u32);
+
+    /// Get the fee charged on new offers and auctions, in basis points.
+    fn fee_bps(e: Env) -> u32;
+
+    /// Set the address that receives the fees collected in an external token. "caller" must hold the Treasury role.
+    fn set_fee_recipient(e: Env, caller: Address, ext_token: Address, recipient: Address);
+
+    /// Get the address that receives the fees collected in an external token, if any.
+    fn fee_recipient(e: Env, ext_token: Address) -> Option<Address>;
+
+    /// Get the fees collected in an external token that have not been withdrawn yet.
+    fn accrued_fees(e: Env, ext_token: Address) -> i128;
+
+    /// Transfer the fees collected in an external token to its fee recipient, and return the amount. "caller" must hold the Treasury role.
+    /// Will fail if no fee recipient is set for the token.
+    /// Emit event with topics = ["fees", ext_token: Address, recipient: Address], data = [amount: i128]
+    fn withdraw_fees(e: Env, caller: Address, ext_token: Address) -> i128;
 
     // --------------------------------------------------------------------------------
     // Pool interface
     // --------------------------------------------------------------------------------
 
     /// Create an offer against a TC. The caller (from) transfers liquidity tokens to the smart contract equal to the value of the TC.
+    /// If "terms.valid_until" is set, the offer can only be accepted until that Unix timestamp.
+    /// If "terms.discount_bps" is set, the price is the TC's face value discounted at that annual rate over the time left to maturity when the offer is accepted.
+    /// "amount" is then the most that can be paid, and anything left over goes back to the caller (from) on acceptance.
+    /// If "terms.tc_amount" is set, the offer buys only that part of the TC's face value, which must be less than the whole.
+    /// Will fail if the TC is disabled, frozen or expired.
     /// Emit event with topics = ["create_offer", from: Address, amount: i128], data = [offer_id: i128]
     fn create_offer(
         e: Env,
@@ -34,22 +115,81 @@ pub trait OfferPoolTrait {
         amount: i128,
         tc_contract: Address,
         tc_id: i128,
+        terms: OfferTerms,
     ) -> i128;
 
-    /// Cancel a offer by expiring it. Caller must be the user who created the request (the from of the offer).
+    /// Cancel a offer by expiring it. Caller must be the user who created the request (the from of the offer), or hold the Risk role.
     /// Transfers the liquidity tokens back to the caller (from ).
     /// Emit event with topics = ["expire_offer", from: Address ], data = [offer_id: i128]
     fn expire_offer(e: Env, from: Address, offer_id: i128);
 
+    /// Return the liquidity tokens of an offer to its creator once the offer is past "valid_until" or its TC has expired. Can be called by anyone.
+    /// Emit event with topics = ["reclaim", from: Address], data = [offer_id: i128]
+    fn reclaim_expired(e: Env, offer_id: i128);
+
     /// get an offer by offer_id, anyone can get the offer information with offer id.
     /// If offer is not found, will return a error for empty offer.
     fn get_offer(e: Env, offer_id: i128) -> Offer;
 
-    /// Accept an offer. The caller (to) must own the TC.
+    /// Get the ids of the offers with a given status. When an offer leaves a status, the last offer with that status takes its place.
+    /// Returns at most "limit" ids, and never more than 100, skipping the first "start" ones.
+    fn list_offers(e: Env, status: OfferStatus, start: u32, limit: u32) -> Vec<i128>;
+
+    /// Get the ids of the pending offers created by an address. Offers leave this list once they are accepted or expired.
+    fn offers_by_creator(e: Env, from: Address) -> Vec<i128>;
+
+    /// Get the ids of the pending offers on a TC. Offers leave this list once they are accepted or expired.
+    fn offers_for_tc(e: Env, tc_contract: Address, tc_id: i128) -> Vec<i128>;
+
+    /// Get the ids of the pending offers on a TC that can still be accepted, from the highest price to the lowest.
+    /// Discount rate offers are ranked by what they would pay now. Offers with the same price are listed in the order they were created.
+    fn ranked_offers(e: Env, tc_contract: Address, tc_id: i128) -> Vec<i128>;
+
+    /// Accept an offer. The caller (to) must own the TC. Will fail if the TC is disabled or frozen.
+    /// Will also fail if the offer is past "valid_until", the TC has expired, or a discount rate offer prices the TC above its amount.
+    /// The fee fixed on the offer at creation is deducted from the price and kept by the smart contract for the fee recipient.
+    /// The price paid, the annual yield it implies, in basis points, and the fee are stored on the offer.
+    /// For an offer on part of the TC, the TC is split so that only that part is transferred to the creditor, and the id of the new TC is stored on the offer.
     /// Transfers the TC to the creditor (from in the offer), and liquidity tokens equal to the associated TC's value are sent from the smart contract to the caller (to).
-    /// Emit event with topics = ["accept_offer", to: Address, amount:i128 ], data = [offer_id: i128]
+    /// Every other pending offer on the same TC is closed as expired and its liquidity tokens are returned to its creator.
+    /// Emit event with topics = ["refund", from: Address], data = [offer_id: i128] for each of them.
+    /// Emit event with topics = ["accept_offer", to: Address, amount:i128 ], data = [offer_id: i128, price: i128, yield_bps: i128, fee: i128]
     fn accept_offer(e: Env, to: Address, offer_id: i128);
 
     /// Get all supported external tokens, and their associated pool token addresses.
     fn get_ext_tokens(e: Env) -> Vec<Address>;
+
+    // --------------------------------------------------------------------------------
+    // Auction interface
+    // --------------------------------------------------------------------------------
+
+    /// Auction a TC for an external token. The TC is transferred from the seller to the smart contract until the auction is settled.
+    /// The current fee is fixed on the auction and charged on the winning bid, as for offers.
+    /// Bids are accepted until "end_time", and must be at least "reserve". Will fail if the TC is disabled, frozen or expired, or if "end_time" is after the TC's maturity.
+    /// Emit event with topics = ["auction", seller: Address, reserve: i128], data = [auction_id: i128]
+    fn start_auction(
+        e: Env,
+        seller: Address,
+        tc_contract: Address,
+        tc_id: i128,
+        ext_token: Address,
+        reserve: i128,
+        end_time: u64,
+    ) -> i128;
+
+    /// Bid on an open auction. The bidder transfers "amount" to the smart contract, and the previous highest bidder is refunded.
+    /// The bid must be at least the reserve and higher than the current highest bid.
+    /// Emit event with topics = ["bid", bidder: Address, amount: i128], data = [auction_id: i128]
+    fn bid(e: Env, bidder: Address, auction_id: i128, amount: i128);
+
+    /// Settle an auction once its end_time has passed. Can be called by anyone.
+    /// The TC goes to the highest bidder and the highest bid, less the fee, to the seller, and pending offers on the TC are refunded. Without bids, the TC goes back to the seller.
+    /// The fee is kept by the smart contract for the fee recipient and stored on the auction.
+    /// Emit event with topics = ["refund", from: Address], data = [offer_id: i128] for each of them.
+    /// Emit event with topics = ["settle", to: Address], data = [auction_id: i128, price: i128, fee: i128], where "to" receives the TC
+    /// and "price" is the winning bid, or 0 without bids.
+    fn settle_auction(e: Env, auction_id: i128);
+
+    /// Get an auction by auction_id. Will fail if the auction is not found.
+    fn get_auction(e: Env, auction_id: i128) -> Auction;
 }
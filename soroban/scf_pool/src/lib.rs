@@ -1,14 +1,20 @@
 #![no_std]
 
 mod admin;
+mod auction;
 mod contract;
 mod error;
 mod event;
+mod fee;
 mod interface;
 mod offer;
+mod pause;
 mod pool_token;
+mod pricing;
+mod roles;
 mod storage_types;
 mod test;
 mod test_util;
+mod upgrade;
 
 pub use crate::contract::OfferPoolClient;
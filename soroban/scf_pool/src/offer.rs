@@ -1,5 +1,8 @@
-use crate::storage_types::{DataKey, Offer, OFFER_BUMP_AMOUNT, OFFER_LIFETIME_THRESHOLD};
-use soroban_sdk::{Address, Env};
+use crate::pricing::{offer_face_value, offer_price};
+use crate::storage_types::{
+    DataKey, Offer, OfferStatus, OFFER_BUMP_AMOUNT, OFFER_LIFETIME_THRESHOLD, OFFER_PAGE_SIZE,
+};
+use soroban_sdk::{Address, Env, Vec};
 
 pub fn read_offer(e: &Env, offer_id: i128) -> Option<Offer> {
     let key = DataKey::Offer(offer_id);
@@ -26,33 +29,28 @@ pub fn increment_supply(e: &Env) {
     e.storage().instance().set(&key, &(read_supply(&e) + 1));
 }
 
-pub fn write_offer(
-    e: &Env,
-    offer_id: i128,
-    from: Address,
-    pool_token: Address,
-    amount: i128,
-    tc_contract: Address,
-    tc_id: i128,
-) {
-    let input_offer = Offer {
-        from: from,
-        pool_token,
-        amount: amount,
-        tc_contract,
-        tc_id,
-        status: 0,
-    };
+pub fn write_offer(e: &Env, offer_id: i128, offer: &Offer) {
     let key = DataKey::Offer(offer_id);
-    e.storage().persistent().set(&key, &input_offer);
+    e.storage().persistent().set(&key, offer);
     e.storage()
         .persistent()
         .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
 }
 
-pub fn change_offer(e: &Env, offer_id: i128, status: i128) -> bool {
+pub fn change_offer(e: &Env, offer_id: i128, status: OfferStatus) -> bool {
     let key = DataKey::Offer(offer_id);
     if let Some(offer) = e.storage().persistent().get::<DataKey, Offer>(&key) {
+        remove_from_status(e, offer.status, offer_id);
+        add_to_status(e, status, offer_id);
+        // closed offers leave the creator and TC indexes, so that these only grow with the open offers
+        if offer.status == OfferStatus::Pending && status != OfferStatus::Pending {
+            remove_from_index(e, DataKey::OffersByCreator(offer.from.clone()), offer_id);
+            remove_from_index(
+                e,
+                DataKey::OffersForTC(offer.tc_contract.clone(), offer.tc_id),
+                offer_id,
+            );
+        }
         let mut new_offer = offer;
         new_offer.status = status;
         e.storage().persistent().set(&key, &new_offer);
@@ -64,3 +62,203 @@ pub fn change_offer(e: &Env, offer_id: i128, status: i128) -> bool {
         return false;
     }
 }
+
+pub fn is_offer_expired(e: &Env, offer: &Offer) -> bool {
+    match offer.valid_until {
+        Some(valid_until) => e.ledger().timestamp() > valid_until,
+        None => false,
+    }
+}
+
+// adds a new offer to the status index, and to the creator and TC indexes while it is pending
+pub fn index_offer(
+    e: &Env,
+    offer_id: i128,
+    status: OfferStatus,
+    from: &Address,
+    tc_contract: &Address,
+    tc_id: i128,
+) {
+    add_to_status(e, status, offer_id);
+    if status == OfferStatus::Pending {
+        add_to_index(e, DataKey::OffersByCreator(from.clone()), offer_id);
+        add_to_index(
+            e,
+            DataKey::OffersForTC(tc_contract.clone(), tc_id),
+            offer_id,
+        );
+    }
+}
+
+// at most OFFER_PAGE_SIZE ids with a given status, skipping the first "start" ones
+pub fn read_offers_by_status(e: &Env, status: OfferStatus, start: u32, limit: u32) -> Vec<i128> {
+    let count = read_status_count(e, status);
+    let end = start.saturating_add(limit.min(OFFER_PAGE_SIZE)).min(count);
+    let mut offer_ids = Vec::new(e);
+    let mut i = start;
+    while i < end {
+        let page = read_index(e, DataKey::OffersByStatus(status, i / OFFER_PAGE_SIZE));
+        let from = i % OFFER_PAGE_SIZE;
+        let to = (from + end - i).min(page.len());
+        if to <= from {
+            break;
+        }
+        offer_ids.append(&page.slice(from..to));
+        i += to - from;
+    }
+    offer_ids
+}
+
+pub fn read_offers_by_creator(e: &Env, from: Address) -> Vec<i128> {
+    read_index(e, DataKey::OffersByCreator(from))
+}
+
+pub fn read_offers_for_tc(e: &Env, tc_contract: Address, tc_id: i128) -> Vec<i128> {
+    read_index(e, DataKey::OffersForTC(tc_contract, tc_id))
+}
+
+// pending offers on a TC that can still be accepted, from the highest price to the lowest.
+// Offers with the same price keep the order in which they were created.
+pub fn read_ranked_offers(
+    e: &Env,
+    tc_contract: Address,
+    tc_id: i128,
+    amount: u32,
+    decimals: u32,
+    time_left: u64,
+) -> Vec<i128> {
+    let mut ranked: Vec<i128> = Vec::new(e);
+    let mut prices: Vec<i128> = Vec::new(e);
+    for offer_id in read_offers_for_tc(e, tc_contract, tc_id) {
+        let offer = match read_offer(e, offer_id) {
+            Some(offer) => offer,
+            None => continue,
+        };
+        if offer.status != OfferStatus::Pending || is_offer_expired(e, &offer) {
+            continue;
+        }
+        let face_value = offer_face_value(&offer, amount, decimals);
+        let price = offer_price(&offer, face_value, time_left);
+        let mut i = 0;
+        while i < prices.len() && prices.get_unchecked(i) >= price {
+            i += 1;
+        }
+        ranked.insert(i, offer_id);
+        prices.insert(i, price);
+    }
+    ranked
+}
+
+fn read_index(e: &Env, key: DataKey) -> Vec<i128> {
+    match e.storage().persistent().get::<DataKey, Vec<i128>>(&key) {
+        Some(offer_ids) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
+            offer_ids
+        }
+        None => Vec::new(e),
+    }
+}
+
+fn write_index(e: &Env, key: DataKey, offer_ids: Vec<i128>) {
+    e.storage().persistent().set(&key, &offer_ids);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
+}
+
+fn add_to_index(e: &Env, key: DataKey, offer_id: i128) {
+    let mut offer_ids = read_index(e, key.clone());
+    offer_ids.push_back(offer_id);
+    write_index(e, key, offer_ids);
+}
+
+fn remove_from_index(e: &Env, key: DataKey, offer_id: i128) {
+    let mut offer_ids = read_index(e, key.clone());
+    if let Some(i) = offer_ids.first_index_of(offer_id) {
+        offer_ids.remove(i);
+    }
+    if offer_ids.is_empty() {
+        e.storage().persistent().remove(&key);
+    } else {
+        write_index(e, key, offer_ids);
+    }
+}
+
+fn read_status_count(e: &Env, status: OfferStatus) -> u32 {
+    let key = DataKey::StatusCount(status);
+    match e.storage().persistent().get::<DataKey, u32>(&key) {
+        Some(count) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
+            count
+        }
+        None => 0,
+    }
+}
+
+fn write_status_count(e: &Env, status: OfferStatus, count: u32) {
+    let key = DataKey::StatusCount(status);
+    e.storage().persistent().set(&key, &count);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
+}
+
+fn write_status_slot(e: &Env, offer_id: i128, slot: u32) {
+    let key = DataKey::StatusSlot(offer_id);
+    e.storage().persistent().set(&key, &slot);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
+}
+
+// the ids of the offers with a given status are kept in pages of OFFER_PAGE_SIZE ids,
+// so that no storage entry grows with the number of offers
+fn add_to_status(e: &Env, status: OfferStatus, offer_id: i128) {
+    let count = read_status_count(e, status);
+    let key = DataKey::OffersByStatus(status, count / OFFER_PAGE_SIZE);
+    let mut page = read_index(e, key.clone());
+    page.push_back(offer_id);
+    write_index(e, key, page);
+    write_status_slot(e, offer_id, count);
+    write_status_count(e, status, count + 1);
+}
+
+// the last id with the same status takes the place of the removed one
+fn remove_from_status(e: &Env, status: OfferStatus, offer_id: i128) {
+    let slot = match e
+        .storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::StatusSlot(offer_id))
+    {
+        Some(slot) => slot,
+        None => return,
+    };
+    let last = read_status_count(e, status) - 1;
+    let last_key = DataKey::OffersByStatus(status, last / OFFER_PAGE_SIZE);
+    let mut last_page = read_index(e, last_key.clone());
+    let last_id = last_page.pop_back_unchecked();
+    if slot != last {
+        if slot / OFFER_PAGE_SIZE == last / OFFER_PAGE_SIZE {
+            last_page.set(slot % OFFER_PAGE_SIZE, last_id);
+        } else {
+            let key = DataKey::OffersByStatus(status, slot / OFFER_PAGE_SIZE);
+            let mut page = read_index(e, key.clone());
+            page.set(slot % OFFER_PAGE_SIZE, last_id);
+            write_index(e, key, page);
+        }
+        write_status_slot(e, last_id, slot);
+    }
+    if last_page.is_empty() {
+        e.storage().persistent().remove(&last_key);
+    } else {
+        write_index(e, last_key, last_page);
+    }
+    e.storage()
+        .persistent()
+        .remove(&DataKey::StatusSlot(offer_id));
+    write_status_count(e, status, last);
+}
@@ -0,0 +1,37 @@
+use crate::error::Error;
+use crate::storage_types::DataKey;
+use soroban_sdk::{panic_with_error, Env};
+
+pub fn is_paused(e: &Env) -> bool {
+    let key = DataKey::Paused;
+    e.storage().instance().get(&key).unwrap_or(false)
+}
+
+pub fn write_paused(e: &Env, paused: bool) {
+    let key = DataKey::Paused;
+    e.storage().instance().set(&key, &paused);
+}
+
+// exits stay open during a pause unless the guardian closes them
+pub fn read_exits_allowed(e: &Env) -> bool {
+    let key = DataKey::ExitsAllowed;
+    e.storage().instance().get(&key).unwrap_or(true)
+}
+
+pub fn write_exits_allowed(e: &Env, allowed: bool) {
+    let key = DataKey::ExitsAllowed;
+    e.storage().instance().set(&key, &allowed);
+}
+
+pub fn check_not_paused(e: &Env) {
+    if is_paused(e) {
+        panic_with_error!(e, Error::Paused);
+    }
+}
+
+// for entry points that let users withdraw their funds
+pub fn check_exit_allowed(e: &Env) {
+    if is_paused(e) && !read_exits_allowed(e) {
+        panic_with_error!(e, Error::Paused);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::storage_types::Offer;
+
+pub(crate) const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+// face value in base units of an external token with "decimals" of "amount" whole units of a TC
+pub fn face_value(amount: u32, decimals: u32) -> i128 {
+    i128::from(amount) * 10i128.pow(decimals)
+}
+
+// face value discounted at an annual rate of "discount_bps" over "time_left" seconds
+pub fn discounted_price(face_value: i128, discount_bps: u32, time_left: u64) -> i128 {
+    let discount = face_value * i128::from(discount_bps) * i128::from(time_left)
+        / (10000 * i128::from(SECONDS_PER_YEAR));
+    (face_value - discount).max(0)
+}
+
+// annual yield in basis points earned by paying "price" for a face value due in "time_left" seconds
+pub fn implied_yield_bps(face_value: i128, price: i128, time_left: u64) -> i128 {
+    if price <= 0 || time_left == 0 {
+        return 0;
+    }
+    (face_value - price) * 10000 * i128::from(SECONDS_PER_YEAR) / (price * i128::from(time_left))
+}
+
+// what an offer would pay for the "face_value" it buys, maturing in "time_left" seconds
+pub fn offer_price(offer: &Offer, face_value: i128, time_left: u64) -> i128 {
+    match offer.discount_bps {
+        Some(discount_bps) => discounted_price(face_value, discount_bps, time_left),
+        None => offer.amount,
+    }
+}
+
+// face value an offer buys out of a TC of "amount" whole units, in base units of an external token with "decimals"
+pub fn offer_face_value(offer: &Offer, amount: u32, decimals: u32) -> i128 {
+    match offer.tc_amount {
+        Some(tc_amount) => face_value(tc_amount, decimals),
+        None => face_value(amount, decimals),
+    }
+}
@@ -7,6 +7,30 @@ pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_I
 pub(crate) const OFFER_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const OFFER_LIFETIME_THRESHOLD: u32 = OFFER_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+pub(crate) const AUCTION_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const AUCTION_LIFETIME_THRESHOLD: u32 = AUCTION_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub(crate) const ROLE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ROLE_LIFETIME_THRESHOLD: u32 = ROLE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// number of offer ids stored under one status page, and the most list_offers() returns at once
+pub(crate) const OFFER_PAGE_SIZE: u32 = 100;
+
+// version of the storage layout, to be bumped along with a migration step whenever the layout changes
+pub(crate) const STORAGE_VERSION: u32 = 2;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum OfferStatus {
+    // waiting to be accepted
+    Pending = 0,
+    // cancelled or reclaimed, the amount went back to the offerer
+    Expired = 1,
+    // accepted by the TC owner, the TC went to the offerer
+    Accepted = 2,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[contracttype]
 pub struct Offer {
@@ -15,7 +39,67 @@ pub struct Offer {
     pub amount: i128,
     pub tc_contract: Address,
     pub tc_id: i128,
-    pub status: i128,
+    pub status: OfferStatus,
+    // Unix timestamp after which the offer can no longer be accepted, and its amount can be reclaimed by anyone
+    pub valid_until: Option<u64>,
+    // annual discount rate in basis points. When set, the price is derived from the TC's face value and time to maturity on acceptance, and "amount" is the most that can be paid
+    pub discount_bps: Option<u32>,
+    // price paid to the TC owner, set on acceptance
+    pub price: i128,
+    // annual yield in basis points implied by the price, set on acceptance
+    pub yield_bps: i128,
+    // part of the TC's face value the offer buys. When set, the TC is split on acceptance and only that part goes to the offerer
+    pub tc_amount: Option<u32>,
+    // id of the TC split off for the offerer, set on acceptance of a partial offer
+    pub filled_tc_id: Option<i128>,
+    // fee in basis points of the price, fixed when the offer is created
+    pub fee_bps: u32,
+    // fee deducted from the price, set on acceptance
+    pub fee: i128,
+}
+
+// optional terms of a new offer
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct OfferTerms {
+    // Unix timestamp after which the offer can no longer be accepted
+    pub valid_until: Option<u64>,
+    // annual discount rate in basis points to price the TC with on acceptance
+    pub discount_bps: Option<u32>,
+    // part of the TC's face value to buy, if less than all of it
+    pub tc_amount: Option<u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AuctionStatus {
+    // accepting bids until end_time
+    Open = 0,
+    // settled with a winning bid, the TC went to the winner
+    Sold = 1,
+    // settled without bids, the TC went back to the seller
+    Unsold = 2,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Auction {
+    pub seller: Address,
+    pub tc_contract: Address,
+    pub tc_id: i128,
+    pub ext_token: Address,
+    // lowest bid that can win the auction
+    pub reserve: i128,
+    // Unix timestamp at which bidding closes and the auction can be settled
+    pub end_time: u64,
+    pub highest_bidder: Option<Address>,
+    pub highest_bid: i128,
+    pub status: AuctionStatus,
+    // fee in basis points of the winning bid, fixed when the auction is started
+    pub fee_bps: u32,
+    // fee deducted from the winning bid at settlement
+    pub fee: i128,
 }
 
 #[derive(Clone)]
@@ -25,6 +109,31 @@ pub struct TokenInfo {
     pub decimals: u32,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub address: Address,
+    // Unix timestamp after which the proposal can no longer be accepted
+    pub expiration_time: Option<u64>,
+}
+
+// privileged duties that the admin can hand out to separate accounts
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    // unused by the pool, kept so role ids match the TC contracts
+    Minter = 0,
+    // unused by the pool, kept so role ids match the TC contracts
+    Compliance = 1,
+    // manages accepted tokens, fees and fee withdrawals
+    Treasury = 2,
+    // expires offers on behalf of their creators
+    Risk = 3,
+    // pauses the contract and controls exits
+    Guardian = 4,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -32,4 +141,20 @@ pub enum DataKey {
     Offer(i128),
     ExtTokens, // contains a set of support external tokens
     Admin,
+    Version,
+    MigrationCursor,
+    PendingAdmin,
+    Role(Role, Address),
+    Paused,
+    ExitsAllowed,
+    OffersByStatus(OfferStatus, u32), // one page of the ids of the offers with a given status
+    StatusCount(OfferStatus),         // number of offers with a given status
+    StatusSlot(i128),                 // position of an offer among the offers with its status
+    OffersByCreator(Address),         // ids of the pending offers created by an address
+    OffersForTC(Address, i128), // ids of the pending offers on a TC, keyed by TC contract and TC id
+    AuctionSupply,
+    Auction(i128),
+    FeeBps,
+    FeeRecipient(Address), // fee recipient of an external token
+    AccruedFees(Address),  // fees collected in an external token and not withdrawn yet
 }
@@ -1,13 +1,18 @@
 #![cfg(test)]
 use crate::contract::{OfferPool, OfferPoolClient};
 use crate::error::Error as ContractError;
+use crate::offer::{change_offer, index_offer, write_offer};
+use crate::roles::ALL_ROLES;
+use crate::storage_types::{AuctionStatus, DataKey, OfferStatus, OfferTerms, Role};
 use crate::test_util::{
-    setup_pool, setup_tc, setup_test_token, tc_contract::Error as TCError,
-    tc_contract::SplitRequest,
+    pool_contract, setup_pool, setup_tc, setup_test_token, tc_contract,
+    tc_contract::Error as TCError, tc_contract::SplitRequest, upload_rebuilt_wasm,
 };
+use crate::upgrade::OfferV1;
 use soroban_sdk::{
-    map, symbol_short, testutils::Address as _, testutils::Events, vec, Address, Env, Error,
-    IntoVal, String,
+    map, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    vec, Address, BytesN, Env, Error, IntoVal, String,
 };
 
 #[test]
@@ -39,6 +44,107 @@ fn test_initialize() {
     assert_eq!(client.get_ext_tokens(), vec![&e]);
 }
 
+#[test]
+fn test_admin_handover() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (client, _) = setup_pool(&e, &admin);
+
+    // the admin does not change until the proposal is accepted
+    let new_admin = Address::generate(&e);
+    client.propose_admin(&new_admin, &None);
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("propose"), admin.clone()).into_val(&e)
+            );
+            let data: (Address, Option<u64>) = data.into_val(&e);
+            assert_eq!(data, (new_admin.clone(), None));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+    client.cancel_admin_proposal();
+    match e.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("unpropose"), admin.clone()).into_val(&e)
+            );
+            let data: Address = data.into_val(&e);
+            assert_eq!(data, new_admin.clone());
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(client.pending_admin(), None);
+    let res = client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+
+    client.propose_admin(&new_admin, &None);
+    client.accept_admin();
+    assert_eq!(e.auths()[0].0, new_admin.clone());
+    assert_eq!(client.pending_admin(), None);
+    assert_eq!(client.admin(), new_admin);
+
+    // the roles of the old admin move to the new admin
+    for role in ALL_ROLES {
+        assert!(!client.has_role(&role, &admin));
+        assert!(client.has_role(&role, &new_admin));
+    }
+}
+
+#[test]
+fn test_roles() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let (client, contract_id) = setup_pool(&e, &admin);
+
+    // the admin holds every role after initialization
+    assert!(client.has_role(&Role::Treasury, &admin));
+    assert!(client.has_role(&Role::Risk, &admin));
+
+    let treasury = Address::generate(&e);
+    client.grant_role(&Role::Treasury, &treasury);
+    match e.events().all().last() {
+        Some((contract_address, topics, data)) => {
+            assert_eq!(contract_address, contract_id.clone());
+            assert_eq!(
+                topics,
+                (symbol_short!("grant"), Role::Treasury, treasury.clone()).into_val(&e)
+            );
+            let data_decoded: Address = data.into_val(&e);
+            assert_eq!(data_decoded, admin);
+        }
+        None => panic!("the event is not published"),
+    }
+    client.add_ext_token(&treasury, &token_client.address);
+    assert_eq!(e.auths()[0].0, treasury.clone());
+    assert_eq!(
+        client.get_ext_tokens(),
+        vec![&e, token_client.address.clone()]
+    );
+
+    // a revoked role can no longer be used
+    client.revoke_role(&Role::Treasury, &treasury);
+    assert!(!client.has_role(&Role::Treasury, &treasury));
+    let res = client.try_remove_ext_token(&treasury, &token_client.address);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+}
+
 #[test]
 #[should_panic(expected = "already initialized")]
 fn test_initialize_twice() {
@@ -60,14 +166,14 @@ fn test_add_and_remove_ext_tokens() {
     let (client, _) = setup_pool(&e, &admin);
 
     // add an ext token
-    client.add_ext_token(&token_client_1.address);
+    client.add_ext_token(&admin, &token_client_1.address);
     assert_eq!(
         client.get_ext_tokens(),
         vec![&e, token_client_1.address.clone()]
     );
 
     // adding the same ext token twice should not result in a duplicate value
-    client.add_ext_token(&token_client_1.address);
+    client.add_ext_token(&admin, &token_client_1.address);
     assert_eq!(
         client.get_ext_tokens(),
         vec![&e, token_client_1.address.clone()]
@@ -75,14 +181,14 @@ fn test_add_and_remove_ext_tokens() {
 
     // removing a nonexistent ext token should not change the token list
     let (token_client_2, _) = setup_test_token(&e, &admin);
-    client.remove_ext_token(&token_client_2.address);
+    client.remove_ext_token(&admin, &token_client_2.address);
     assert_eq!(
         client.get_ext_tokens(),
         vec![&e, token_client_1.address.clone()]
     );
 
     // add a second ext token to the whitelist
-    client.add_ext_token(&token_client_2.address);
+    client.add_ext_token(&admin, &token_client_2.address);
     assert_eq!(
         client.get_ext_tokens(),
         map![
@@ -94,7 +200,7 @@ fn test_add_and_remove_ext_tokens() {
     );
 
     // remove the 1st ext token from the whitelist
-    client.remove_ext_token(&token_client_1.address);
+    client.remove_ext_token(&admin, &token_client_1.address);
     assert_eq!(
         client.get_ext_tokens(),
         vec![&e, token_client_2.address.clone()]
@@ -111,7 +217,7 @@ fn test_create_offer() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, contract_id) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -123,7 +229,7 @@ fn test_create_offer() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -134,6 +240,7 @@ fn test_create_offer() {
         &600000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
 
     assert_eq!(offer_id, 0);
@@ -164,7 +271,7 @@ fn test_create_offer() {
     assert_eq!(offer.amount, 600000);
     assert_eq!(offer.tc_contract, tc_client.address);
     assert_eq!(offer.tc_id, 0);
-    assert_eq!(offer.status, 0);
+    assert_eq!(offer.status, OfferStatus::Pending);
     assert_eq!(token_client.balance(&offerer.clone()), 400000);
 }
 
@@ -189,7 +296,7 @@ fn test_create_offer_unsupported_token() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -200,6 +307,7 @@ fn test_create_offer_unsupported_token() {
         &600000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
 
     assert_eq!(
@@ -220,7 +328,7 @@ fn test_create_offer_insufficient_balance() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -232,7 +340,7 @@ fn test_create_offer_insufficient_balance() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -243,6 +351,7 @@ fn test_create_offer_insufficient_balance() {
         &2000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     assert_eq!(res.is_err(), true);
 }
@@ -258,7 +367,7 @@ fn test_create_offer_disabled_tc() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -270,7 +379,7 @@ fn test_create_offer_disabled_tc() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
     tc_client.split(
         &0,
         &vec![
@@ -291,6 +400,7 @@ fn test_create_offer_disabled_tc() {
         &600000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     assert_eq!(
         res,
@@ -300,6 +410,70 @@ fn test_create_offer_disabled_tc() {
     );
 }
 
+#[test]
+fn test_frozen_tc() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, _) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerer
+    token_admin_client.mint(&offerer, &2000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // a frozen TC can neither receive nor accept offers
+    tc_client.freeze(&admin, &0, &BytesN::from_array(&e, &[1; 32]));
+    let res = pool_client.try_create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::TCFrozen as u32
+        )))
+    );
+    let res = pool_client.try_accept_offer(&supplier, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::TCFrozen as u32
+        )))
+    );
+
+    tc_client.unfreeze(&admin, &0);
+    pool_client.accept_offer(&supplier, &offer_id);
+    assert_eq!(tc_client.owner(&0), offerer);
+}
+
 #[test]
 fn test_create_offer_nonexistent_tc() {
     let e = Env::default();
@@ -309,7 +483,7 @@ fn test_create_offer_nonexistent_tc() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -332,6 +506,7 @@ fn test_create_offer_nonexistent_tc() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     assert_eq!(
         res,
@@ -346,6 +521,7 @@ fn test_create_offer_nonexistent_tc() {
         &1000000,
         &random_addr,
         &0,
+        &OfferTerms::default(),
     );
     assert!(res.is_err());
 }
@@ -360,30 +536,31 @@ fn test_accept_offer() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, contract_id) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
         &e,
         &admin,
         &buyer,
-        &1000000,
+        &1000,
         &1712793295,
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
-    token_admin_client.mint(&offerer, &1000000);
+    token_admin_client.mint(&offerer, &10000000000);
 
     // create and accept the offer
     let offer_id = pool_client.create_offer(
         &offerer,
         &token_client.address,
-        &1000000,
+        &10000000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     pool_client.accept_offer(&supplier, &offer_id);
 
@@ -401,15 +578,17 @@ fn test_accept_offer() {
             );
 
             // Test the event data
-            let data_decoded: i128 = data.into_val(&e);
-            assert_eq!(data_decoded, offer_id);
+            let data_decoded: (i128, i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data_decoded, (offer_id, 10000000000, 0, 0));
         }
         None => panic!("The event is not published"),
     }
 
     let offer = pool_client.get_offer(&offer_id);
-    assert_eq!(offer.status, 2);
-    assert_eq!(token_client.balance(&supplier), 1000000)
+    assert_eq!(offer.status, OfferStatus::Accepted);
+    assert_eq!(offer.price, 10000000000);
+    assert_eq!(offer.yield_bps, 0);
+    assert_eq!(token_client.balance(&supplier), 10000000000)
 }
 
 #[test]
@@ -438,7 +617,7 @@ fn test_accept_offer_not_tc_owner() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -450,7 +629,7 @@ fn test_accept_offer_not_tc_owner() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -463,6 +642,7 @@ fn test_accept_offer_not_tc_owner() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     let res = pool_client.try_accept_offer(&other_user, &offer_id);
     assert_eq!(
@@ -481,7 +661,7 @@ fn test_expire_accepted_offer() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -493,7 +673,7 @@ fn test_expire_accepted_offer() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -505,10 +685,11 @@ fn test_expire_accepted_offer() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     pool_client.accept_offer(&supplier, &offer_id);
     let offer = pool_client.get_offer(&offer_id);
-    assert_eq!(offer.status, 2);
+    assert_eq!(offer.status, OfferStatus::Accepted);
 
     // try to expire an accepted offer
     let res = pool_client.try_expire_offer(&admin, &offer_id);
@@ -546,7 +727,7 @@ fn test_expire_offer_as_admin() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -558,7 +739,7 @@ fn test_expire_offer_as_admin() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -569,11 +750,94 @@ fn test_expire_offer_as_admin() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     pool_client.expire_offer(&admin, &offer_id);
 
     let offer = pool_client.get_offer(&offer_id);
-    assert_eq!(offer.status, 1);
+    assert_eq!(offer.status, OfferStatus::Expired);
+}
+
+#[test]
+fn test_pause() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, _) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerer
+    token_admin_client.mint(&offerer, &2000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // only a guardian can pause
+    let guardian = Address::generate(&e);
+    let res = pool_client.try_pause(&guardian);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    pool_client.grant_role(&Role::Guardian, &guardian);
+    pool_client.pause(&guardian);
+    assert!(pool_client.is_paused());
+
+    // offers cannot be created or accepted while paused
+    let res = pool_client.try_create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    let res = pool_client.try_accept_offer(&supplier, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+
+    // the offerer can withdraw unless the guardian closes the exits
+    pool_client.set_exits_allowed(&guardian, &false);
+    let res = pool_client.try_expire_offer(&offerer, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    pool_client.set_exits_allowed(&guardian, &true);
+    pool_client.expire_offer(&offerer, &offer_id);
+    assert_eq!(token_client.balance(&offerer), 2000000);
+
+    pool_client.unpause(&guardian);
+    assert!(!pool_client.is_paused());
 }
 
 #[test]
@@ -586,7 +850,7 @@ fn test_expire_offer_as_owner() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, contract_id) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -598,7 +862,7 @@ fn test_expire_offer_as_owner() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -609,6 +873,7 @@ fn test_expire_offer_as_owner() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     pool_client.expire_offer(&offerer, &offer_id);
 
@@ -631,7 +896,7 @@ fn test_expire_offer_as_owner() {
     }
 
     let offer = pool_client.get_offer(&offer_id);
-    assert_eq!(offer.status, 1);
+    assert_eq!(offer.status, OfferStatus::Expired);
 }
 
 #[test]
@@ -644,7 +909,7 @@ fn test_expire_offer_not_owned() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -656,7 +921,7 @@ fn test_expire_offer_not_owned() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -668,6 +933,7 @@ fn test_expire_offer_not_owned() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     let res = pool_client.try_expire_offer(&other_user, &offer_id);
     assert_eq!(
@@ -675,7 +941,15 @@ fn test_expire_offer_not_owned() {
         Err(Ok(Error::from_contract_error(
             ContractError::NotAuthorized as u32
         )))
-    )
+    );
+
+    // a holder of the Risk role can expire any offer
+    pool_client.grant_role(&Role::Risk, &other_user);
+    pool_client.expire_offer(&other_user, &offer_id);
+    assert_eq!(
+        pool_client.get_offer(&offer_id).status,
+        OfferStatus::Expired
+    );
 }
 
 #[test]
@@ -688,7 +962,7 @@ fn test_accept_expired_offer() {
     let offerer = Address::generate(&e);
     let (token_client, token_admin_client) = setup_test_token(&e, &admin);
     let (pool_client, _) = setup_pool(&e, &admin);
-    pool_client.add_ext_token(&token_client.address);
+    pool_client.add_ext_token(&admin, &token_client.address);
 
     // setup tc
     let tc_client = setup_tc(
@@ -700,7 +974,7 @@ fn test_accept_expired_offer() {
         &token_client.address,
         &token_client.decimals(),
     );
-    tc_client.mint_original(&supplier, &String::from_str(&e, ""));
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
 
     // mint ext token to offerer
     token_admin_client.mint(&offerer, &1000000);
@@ -712,10 +986,11 @@ fn test_accept_expired_offer() {
         &1000000,
         &tc_client.address,
         &0,
+        &OfferTerms::default(),
     );
     pool_client.expire_offer(&admin, &offer_id);
     let offer = pool_client.get_offer(&offer_id);
-    assert_eq!(offer.status, 1);
+    assert_eq!(offer.status, OfferStatus::Expired);
 
     // try to accept an expired offer
     let res = pool_client.try_accept_offer(&supplier, &offer_id);
@@ -726,3 +1001,1380 @@ fn test_accept_expired_offer() {
         )))
     );
 }
+
+#[test]
+fn test_offer_deadline() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerer
+    token_admin_client.mint(&offerer, &1000000);
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+
+    // a deadline in the past is rejected
+    let res = pool_client.try_create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            valid_until: Some(1000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            valid_until: Some(2000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(pool_client.get_offer(&offer_id).valid_until, Some(2000));
+
+    // the offer cannot be reclaimed before its deadline
+    let res = pool_client.try_reclaim_expired(&offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // past the deadline the offer can no longer be accepted
+    e.ledger().with_mut(|li| li.timestamp = 2001);
+    let res = pool_client.try_accept_offer(&supplier, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::OfferExpired as u32
+        )))
+    );
+
+    // anyone can return the funds to the offerer
+    pool_client.reclaim_expired(&offer_id);
+    match e.events().all().last() {
+        Some((contract_address, topics, data)) => {
+            assert_eq!(contract_address, pool_id.clone());
+            assert_eq!(
+                topics,
+                (symbol_short!("reclaim"), offerer.clone()).into_val(&e)
+            );
+            let data_decoded: i128 = data.into_val(&e);
+            assert_eq!(data_decoded, offer_id);
+        }
+        None => panic!("the event is not published"),
+    }
+    assert_eq!(token_client.balance(&offerer), 1000000);
+    assert_eq!(token_client.balance(&pool_id), 0);
+    assert_eq!(
+        pool_client.get_offer(&offer_id).status,
+        OfferStatus::Expired
+    );
+
+    let res = pool_client.try_reclaim_expired(&offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::OfferChanged as u32
+        )))
+    );
+}
+
+#[test]
+fn test_expired_tc() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerer
+    token_admin_client.mint(&offerer, &1000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // once the TC has expired, offers can be neither created nor accepted
+    e.ledger().with_mut(|li| li.timestamp = 1712793295);
+    let res = pool_client.try_create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::TCExpired as u32
+        )))
+    );
+    let res = pool_client.try_accept_offer(&supplier, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::TCExpired as u32
+        )))
+    );
+
+    // an offer without a deadline can be reclaimed once the TC has expired
+    pool_client.reclaim_expired(&offer_id);
+    assert_eq!(token_client.balance(&offerer), 1000000);
+    assert_eq!(token_client.balance(&pool_id), 0);
+    assert_eq!(
+        pool_client.get_offer(&offer_id).status,
+        OfferStatus::Expired
+    );
+}
+
+#[test]
+fn test_discount_offer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer1 = Address::generate(&e);
+    let offerer2 = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup a tc of 1000 units of a 7 decimals token, maturing in half a year
+    let end_time = 1712793295;
+    e.ledger()
+        .with_mut(|li| li.timestamp = end_time - 365 * 24 * 60 * 60 / 2);
+    assert_eq!(token_client.decimals(), 7);
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000,
+        &end_time,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerers
+    token_admin_client.mint(&offerer1, &10000000000);
+    token_admin_client.mint(&offerer2, &10000000000);
+
+    // a 10% annual discount prices the TC at 950 units, above the fixed offer
+    let fixed_offer = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &9400000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let discount_offer = pool_client.create_offer(
+        &offerer2,
+        &token_client.address,
+        &10000000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            discount_bps: Some(1000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        pool_client.ranked_offers(&tc_client.address, &0),
+        vec![&e, discount_offer, fixed_offer]
+    );
+
+    // the supplier gets the discounted price and the rest goes back to the offerer
+    pool_client.accept_offer(&supplier, &discount_offer);
+    assert_eq!(tc_client.owner(&0), offerer2);
+    assert_eq!(token_client.balance(&supplier), 9500000000);
+    assert_eq!(token_client.balance(&offerer2), 500000000);
+    assert_eq!(token_client.balance(&offerer1), 10000000000);
+    assert_eq!(token_client.balance(&pool_id), 0);
+
+    // paying 950 units for 1000 due in half a year yields 10.52% a year
+    let offer = pool_client.get_offer(&discount_offer);
+    assert_eq!(offer.price, 9500000000);
+    assert_eq!(offer.yield_bps, 1052);
+    match e.events().all().last() {
+        Some((contract_address, topics, data)) => {
+            assert_eq!(contract_address, pool_id.clone());
+            assert_eq!(
+                topics,
+                (symbol_short!("accept"), supplier.clone()).into_val(&e)
+            );
+            let data_decoded: (i128, i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data_decoded, (discount_offer, 9500000000, 1052, 0));
+        }
+        None => panic!("the event is not published"),
+    }
+}
+
+#[test]
+fn test_discount_offer_underfunded() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, _) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup a tc of 1000 units maturing in half a year
+    let end_time = 1712793295;
+    e.ledger()
+        .with_mut(|li| li.timestamp = end_time - 365 * 24 * 60 * 60 / 2);
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000,
+        &end_time,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // the discounted price grows from 950 to 975 units as the TC gets closer to maturity
+    token_admin_client.mint(&offerer, &10000000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &9600000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            discount_bps: Some(1000),
+            ..Default::default()
+        },
+    );
+    e.ledger()
+        .with_mut(|li| li.timestamp = end_time - 365 * 24 * 60 * 60 / 4);
+    let res = pool_client.try_accept_offer(&supplier, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::OfferUnderfunded as u32
+        )))
+    );
+}
+
+#[test]
+fn test_partial_offer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer1 = Address::generate(&e);
+    let offerer2 = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerers
+    token_admin_client.mint(&offerer1, &1000000);
+    token_admin_client.mint(&offerer2, &1000000);
+
+    // a partial offer must leave part of the TC to the seller
+    let res = pool_client.try_create_offer(
+        &offerer1,
+        &token_client.address,
+        &380000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            tc_amount: Some(1000000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    let offer_id = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &380000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            tc_amount: Some(400000),
+            ..Default::default()
+        },
+    );
+    let whole_offer_id = pool_client.create_offer(
+        &offerer2,
+        &token_client.address,
+        &900000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // only the owner of the TC can sell part of it
+    let res = pool_client.try_accept_offer(&offerer2, &offer_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    assert_eq!(tc_client.owner(&0), supplier);
+    assert!(!tc_client.is_disabled(&0));
+    assert_eq!(token_client.balance(&offerer2), 100000);
+
+    // the TC is split, the offerer gets the part it bought and the seller keeps the rest
+    pool_client.accept_offer(&supplier, &offer_id);
+    let offer = pool_client.get_offer(&offer_id);
+    assert_eq!(offer.status, OfferStatus::Accepted);
+    assert_eq!(offer.price, 380000);
+    let filled_tc_id = offer.filled_tc_id.unwrap();
+    assert_eq!(tc_client.owner(&filled_tc_id), offerer1);
+    assert_eq!(tc_client.amount(&filled_tc_id), 400000);
+    assert!(tc_client.is_disabled(&0));
+    let children = tc_client.children(&0);
+    assert_eq!(children, vec![&e, filled_tc_id, filled_tc_id + 1]);
+    assert_eq!(tc_client.owner(&(filled_tc_id + 1)), supplier);
+    assert_eq!(tc_client.amount(&(filled_tc_id + 1)), 600000);
+    assert_eq!(token_client.balance(&supplier), 380000);
+
+    // the whole TC is no longer available, so the competing offer is refunded
+    assert_eq!(
+        pool_client.get_offer(&whole_offer_id).status,
+        OfferStatus::Expired
+    );
+    assert_eq!(token_client.balance(&offerer2), 1000000);
+    assert_eq!(token_client.balance(&pool_id), 0);
+}
+
+#[test]
+fn test_partial_discount_offer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer1 = Address::generate(&e);
+    let offerer2 = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup a tc of 1000 units of a 7 decimals token, maturing in half a year
+    let end_time = 1712793295;
+    e.ledger()
+        .with_mut(|li| li.timestamp = end_time - 365 * 24 * 60 * 60 / 2);
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000,
+        &end_time,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    token_admin_client.mint(&offerer1, &10000000000);
+    token_admin_client.mint(&offerer2, &10000000000);
+
+    // a 10% annual discount prices 400 units of the TC at 380, above the fixed offer for the same part
+    let fixed_offer = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &3700000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            tc_amount: Some(400),
+            ..Default::default()
+        },
+    );
+    let discount_offer = pool_client.create_offer(
+        &offerer2,
+        &token_client.address,
+        &4000000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            discount_bps: Some(1000),
+            tc_amount: Some(400),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        pool_client.ranked_offers(&tc_client.address, &0),
+        vec![&e, discount_offer, fixed_offer]
+    );
+
+    // the offerer gets 400 units of the TC and the part of its amount above the price back
+    pool_client.accept_offer(&supplier, &discount_offer);
+    let offer = pool_client.get_offer(&discount_offer);
+    assert_eq!(offer.price, 3800000000);
+    assert_eq!(offer.yield_bps, 1052);
+    assert_eq!(tc_client.amount(&offer.filled_tc_id.unwrap()), 400);
+    assert_eq!(token_client.balance(&supplier), 3800000000);
+    assert_eq!(token_client.balance(&offerer2), 6200000000);
+    assert_eq!(token_client.balance(&offerer1), 10000000000);
+    assert_eq!(token_client.balance(&pool_id), 0);
+}
+
+#[test]
+fn test_fees() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let treasury = Address::generate(&e);
+    let fee_recipient = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+    pool_client.grant_role(&Role::Treasury, &treasury);
+
+    // setup tcs
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+    let other_tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    other_tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // only the treasury sets fees, up to 100%
+    let res = pool_client.try_set_fee_bps(&offerer, &100);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    let res = pool_client.try_set_fee_bps(&treasury, &10001);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    pool_client.set_fee_bps(&treasury, &100);
+    assert_eq!(pool_client.fee_bps(), 100);
+
+    // the fee is fixed on the offer when it is created
+    token_admin_client.mint(&offerer, &20000000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &10000000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    pool_client.set_fee_bps(&treasury, &500);
+    let other_offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &10000000000,
+        &other_tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    assert_eq!(pool_client.get_offer(&offer_id).fee_bps, 100);
+    assert_eq!(pool_client.get_offer(&other_offer_id).fee_bps, 500);
+
+    // the fee is deducted from the price paid to the seller
+    pool_client.accept_offer(&supplier, &offer_id);
+    assert_eq!(token_client.balance(&supplier), 9900000000);
+    assert_eq!(pool_client.get_offer(&offer_id).fee, 100000000);
+    match e.events().all().last() {
+        Some((_, _, data)) => {
+            let data_decoded: (i128, i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data_decoded, (offer_id, 10000000000, 0, 100000000));
+        }
+        None => panic!("the event is not published"),
+    }
+    pool_client.accept_offer(&supplier, &other_offer_id);
+    assert_eq!(token_client.balance(&supplier), 19400000000);
+    assert_eq!(pool_client.accrued_fees(&token_client.address), 600000000);
+    assert_eq!(token_client.balance(&pool_id), 600000000);
+
+    // fees can only be withdrawn to the fee recipient of the token
+    let res = pool_client.try_withdraw_fees(&treasury, &token_client.address);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::FeeRecipientEmpty as u32
+        )))
+    );
+    pool_client.set_fee_recipient(&treasury, &token_client.address, &fee_recipient);
+    assert_eq!(
+        pool_client.fee_recipient(&token_client.address),
+        Some(fee_recipient.clone())
+    );
+    assert_eq!(
+        pool_client.withdraw_fees(&treasury, &token_client.address),
+        600000000
+    );
+    match e.events().all().last() {
+        Some((contract_address, topics, data)) => {
+            assert_eq!(contract_address, pool_id.clone());
+            assert_eq!(
+                topics,
+                (
+                    symbol_short!("fees"),
+                    token_client.address.clone(),
+                    fee_recipient.clone()
+                )
+                    .into_val(&e)
+            );
+            let data_decoded: i128 = data.into_val(&e);
+            assert_eq!(data_decoded, 600000000);
+        }
+        None => panic!("the event is not published"),
+    }
+    assert_eq!(token_client.balance(&fee_recipient), 600000000);
+    assert_eq!(token_client.balance(&pool_id), 0);
+    assert_eq!(pool_client.accrued_fees(&token_client.address), 0);
+}
+
+#[test]
+fn test_list_offers() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer1 = Address::generate(&e);
+    let offerer2 = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, _) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup two tcs to make offers on
+    let tc_client1 = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &400000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client1.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+    let tc_client2 = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &600000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client2.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerers
+    token_admin_client.mint(&offerer1, &2000000);
+    token_admin_client.mint(&offerer2, &2000000);
+    let offer0 = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &400000,
+        &tc_client1.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer1 = pool_client.create_offer(
+        &offerer2,
+        &token_client.address,
+        &400000,
+        &tc_client1.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer2 = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &600000,
+        &tc_client2.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer3 = pool_client.create_offer(
+        &offerer2,
+        &token_client.address,
+        &600000,
+        &tc_client2.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &0, &10),
+        vec![&e, offer0, offer1, offer2, offer3]
+    );
+    assert_eq!(
+        pool_client.offers_by_creator(&offerer1),
+        vec![&e, offer0, offer2]
+    );
+    assert_eq!(
+        pool_client.offers_for_tc(&tc_client2.address, &0),
+        vec![&e, offer2, offer3]
+    );
+    assert_eq!(pool_client.offers_for_tc(&tc_client1.address, &1), vec![&e]);
+
+    // offers move between status lists as they change
+    pool_client.expire_offer(&offerer2, &offer1);
+    pool_client.accept_offer(&supplier, &offer0);
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &0, &10),
+        vec![&e, offer2, offer3]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Expired, &0, &10),
+        vec![&e, offer1]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Accepted, &0, &10),
+        vec![&e, offer0]
+    );
+
+    // closed offers leave the creator and TC indexes
+    assert_eq!(pool_client.offers_by_creator(&offerer1), vec![&e, offer2]);
+    assert_eq!(pool_client.offers_by_creator(&offerer2), vec![&e, offer3]);
+    assert_eq!(pool_client.offers_for_tc(&tc_client1.address, &0), vec![&e]);
+    assert_eq!(
+        pool_client.offers_for_tc(&tc_client2.address, &0),
+        vec![&e, offer2, offer3]
+    );
+
+    // pagination
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &1, &1),
+        vec![&e, offer3]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &0, &1),
+        vec![&e, offer2]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &2, &10),
+        vec![&e]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &1, &u32::MAX),
+        vec![&e, offer3]
+    );
+}
+
+#[test]
+fn test_list_offers_pages() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, contract_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+    token_admin_client.mint(&offerer, &1000000);
+    pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // fill the pending offers past one page
+    let offer = pool_client.get_offer(&0);
+    e.budget().reset_unlimited();
+    e.as_contract(&contract_id, || {
+        for offer_id in 1..150 {
+            write_offer(&e, offer_id, &offer);
+            index_offer(
+                &e,
+                offer_id,
+                OfferStatus::Pending,
+                &offer.from,
+                &offer.tc_contract,
+                offer.tc_id,
+            );
+        }
+    });
+    let first = pool_client.list_offers(&OfferStatus::Pending, &0, &u32::MAX);
+    assert_eq!(first.len(), 100);
+    assert_eq!(first.get_unchecked(99), 99);
+    let rest = pool_client.list_offers(&OfferStatus::Pending, &95, &100);
+    assert_eq!(rest.len(), 55);
+    assert_eq!(rest.get_unchecked(0), 95);
+    assert_eq!(rest.get_unchecked(54), 149);
+
+    // the last pending offer takes the place of the one that left, across pages
+    e.as_contract(&contract_id, || {
+        change_offer(&e, 5, OfferStatus::Expired);
+        change_offer(&e, 148, OfferStatus::Expired);
+    });
+    let pending = pool_client.list_offers(&OfferStatus::Pending, &0, &100);
+    assert_eq!(pending.get_unchecked(5), 149);
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &145, &100),
+        vec![&e, 145, 146, 147]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Expired, &0, &100),
+        vec![&e, 5, 148]
+    );
+}
+
+#[test]
+fn test_competing_offers() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer1 = Address::generate(&e);
+    let offerer2 = Address::generate(&e);
+    let offerer3 = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tcs
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+    let other_tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    other_tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to offerers
+    token_admin_client.mint(&offerer1, &3000000);
+    token_admin_client.mint(&offerer2, &2000000);
+    token_admin_client.mint(&offerer3, &2000000);
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+    let offer1 = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &500000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer2 = pool_client.create_offer(
+        &offerer2,
+        &token_client.address,
+        &800000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer3 = pool_client.create_offer(
+        &offerer3,
+        &token_client.address,
+        &800000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let late_offer = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &900000,
+        &tc_client.address,
+        &0,
+        &OfferTerms {
+            valid_until: Some(2000),
+            ..Default::default()
+        },
+    );
+    let other_offer = pool_client.create_offer(
+        &offerer1,
+        &token_client.address,
+        &700000,
+        &other_tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // highest amount first, ties in creation order
+    assert_eq!(
+        pool_client.ranked_offers(&tc_client.address, &0),
+        vec![&e, late_offer, offer2, offer3, offer1]
+    );
+
+    // offers past their deadline are no longer ranked
+    e.ledger().with_mut(|li| li.timestamp = 2001);
+    assert_eq!(
+        pool_client.ranked_offers(&tc_client.address, &0),
+        vec![&e, offer2, offer3, offer1]
+    );
+
+    // the owner is free to accept any of the offers
+    pool_client.accept_offer(&supplier, &offer3);
+    assert_eq!(tc_client.owner(&0), offerer3);
+    assert_eq!(token_client.balance(&supplier), 800000);
+
+    // competing offers were refunded and closed
+    assert_eq!(pool_client.get_offer(&offer1).status, OfferStatus::Expired);
+    assert_eq!(pool_client.get_offer(&offer2).status, OfferStatus::Expired);
+    assert_eq!(
+        pool_client.get_offer(&late_offer).status,
+        OfferStatus::Expired
+    );
+    assert_eq!(token_client.balance(&offerer1), 2300000);
+    assert_eq!(token_client.balance(&offerer2), 2000000);
+    assert_eq!(token_client.balance(&offerer3), 1200000);
+    assert!(e.events().all().contains((
+        pool_id.clone(),
+        (symbol_short!("refund"), offerer2.clone()).into_val(&e),
+        offer2.into_val(&e),
+    )));
+    assert_eq!(pool_client.ranked_offers(&tc_client.address, &0), vec![&e]);
+
+    // offers on other TCs are left alone
+    assert_eq!(
+        pool_client.get_offer(&other_offer).status,
+        OfferStatus::Pending
+    );
+    assert_eq!(token_client.balance(&pool_id), 700000);
+}
+
+#[test]
+fn test_auction() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let bidder1 = Address::generate(&e);
+    let bidder2 = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, pool_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    // mint ext token to bidders
+    token_admin_client.mint(&bidder1, &1000000);
+    token_admin_client.mint(&bidder2, &1000000);
+    token_admin_client.mint(&offerer, &1000000);
+    e.ledger().with_mut(|li| li.timestamp = 1000);
+
+    // an auction must end in the future
+    let res = pool_client.try_start_auction(
+        &supplier,
+        &tc_client.address,
+        &0,
+        &token_client.address,
+        &800000,
+        &1000,
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // an auction must end by the TC's maturity
+    let res = pool_client.try_start_auction(
+        &supplier,
+        &tc_client.address,
+        &0,
+        &token_client.address,
+        &800000,
+        &1712793296,
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // the fee in effect when the auction starts is charged at settlement
+    pool_client.set_fee_bps(&admin, &100);
+
+    // the TC is escrowed in the pool
+    let auction_id = pool_client.start_auction(
+        &supplier,
+        &tc_client.address,
+        &0,
+        &token_client.address,
+        &800000,
+        &2000,
+    );
+    assert_eq!(tc_client.owner(&0), pool_id);
+    match e.events().all().last() {
+        Some((contract_address, topics, data)) => {
+            assert_eq!(contract_address, pool_id.clone());
+            assert_eq!(
+                topics,
+                (symbol_short!("auction"), supplier.clone(), 800000i128).into_val(&e)
+            );
+            let data_decoded: i128 = data.into_val(&e);
+            assert_eq!(data_decoded, auction_id);
+        }
+        None => panic!("the event is not published"),
+    }
+
+    // bids below the reserve are rejected
+    let res = pool_client.try_bid(&bidder1, &auction_id, &700000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::BidTooLow as u32
+        )))
+    );
+
+    pool_client.bid(&bidder1, &auction_id, &800000);
+    assert_eq!(token_client.balance(&bidder1), 200000);
+    assert_eq!(token_client.balance(&pool_id), 800000);
+
+    // a bid must beat the highest bid
+    let res = pool_client.try_bid(&bidder2, &auction_id, &800000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::BidTooLow as u32
+        )))
+    );
+
+    // a higher bid refunds the previous bidder
+    pool_client.bid(&bidder2, &auction_id, &900000);
+    assert_eq!(token_client.balance(&bidder1), 1000000);
+    assert_eq!(token_client.balance(&bidder2), 100000);
+    assert_eq!(token_client.balance(&pool_id), 900000);
+    let auction = pool_client.get_auction(&auction_id);
+    assert_eq!(auction.highest_bidder, Some(bidder2.clone()));
+    assert_eq!(auction.highest_bid, 900000);
+
+    // the auction cannot be settled before it ends
+    let res = pool_client.try_settle_auction(&auction_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::AuctionNotEnded as u32
+        )))
+    );
+
+    // no bids once it has ended
+    e.ledger().with_mut(|li| li.timestamp = 2000);
+    let res = pool_client.try_bid(&bidder1, &auction_id, &950000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::AuctionEnded as u32
+        )))
+    );
+
+    // an offer made on the TC during the auction
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address,
+        &1000000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+
+    // later fee changes do not apply to the auction
+    pool_client.set_fee_bps(&admin, &500);
+
+    // anyone can settle, the winner gets the TC and the seller the proceeds less the fee
+    pool_client.settle_auction(&auction_id);
+    match e.events().all().last() {
+        Some((contract_address, topics, data)) => {
+            assert_eq!(contract_address, pool_id.clone());
+            assert_eq!(
+                topics,
+                (symbol_short!("settle"), bidder2.clone()).into_val(&e)
+            );
+            let data_decoded: (i128, i128, i128) = data.into_val(&e);
+            assert_eq!(data_decoded, (auction_id, 900000, 9000));
+        }
+        None => panic!("the event is not published"),
+    }
+    assert_eq!(tc_client.owner(&0), bidder2);
+    assert_eq!(token_client.balance(&supplier), 891000);
+    assert_eq!(token_client.balance(&pool_id), 9000);
+    assert_eq!(pool_client.accrued_fees(&token_client.address), 9000);
+    let auction = pool_client.get_auction(&auction_id);
+    assert_eq!(auction.status, AuctionStatus::Sold);
+    assert_eq!(auction.fee_bps, 100);
+    assert_eq!(auction.fee, 9000);
+
+    // the pending offer on the sold TC is refunded
+    assert_eq!(token_client.balance(&offerer), 1000000);
+    assert_eq!(
+        pool_client.get_offer(&offer_id).status,
+        OfferStatus::Expired
+    );
+
+    let res = pool_client.try_settle_auction(&auction_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::AuctionChanged as u32
+        )))
+    );
+}
+
+#[test]
+fn test_auction_unsold() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let (token_client, _) = setup_test_token(&e, &admin);
+    let (pool_client, _) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    // setup tc
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+
+    let auction_id = pool_client.start_auction(
+        &supplier,
+        &tc_client.address,
+        &0,
+        &token_client.address,
+        &800000,
+        &2000,
+    );
+
+    // without a bid meeting the reserve, the TC goes back to the seller
+    e.ledger().with_mut(|li| li.timestamp = 2000);
+    pool_client.settle_auction(&auction_id);
+    assert_eq!(tc_client.owner(&0), supplier);
+    assert_eq!(
+        pool_client.get_auction(&auction_id).status,
+        AuctionStatus::Unsold
+    );
+
+    let res = pool_client.try_get_auction(&1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::AuctionEmpty as u32
+        )))
+    );
+}
+
+#[test]
+fn test_upgrade() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, _) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+    token_admin_client.mint(&offerer, &1000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address.clone(),
+        &600000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer = pool_client.get_offer(&offer_id);
+
+    // move the pool to the release build, then both contracts to a build with another hash
+    let tc_wasm_hash = e.deployer().upload_contract_wasm(tc_contract::WASM);
+    let pool_wasm_hash = e.deployer().upload_contract_wasm(pool_contract::WASM);
+    pool_client.upgrade(&pool_wasm_hash);
+    let tc_rebuilt_hash = upload_rebuilt_wasm(&e, tc_contract::WASM);
+    assert_ne!(tc_wasm_hash, tc_rebuilt_hash);
+    tc_client.upgrade(&tc_rebuilt_hash);
+    let pool_rebuilt_hash = upload_rebuilt_wasm(&e, pool_contract::WASM);
+    assert_ne!(pool_wasm_hash, pool_rebuilt_hash);
+    pool_client.upgrade(&pool_rebuilt_hash);
+
+    // existing certificates, offers and balances survive the upgrades
+    assert_eq!(tc_client.owner(&0), supplier);
+    assert_eq!(tc_client.amount(&0), 1000000);
+    assert_eq!(pool_client.get_offer(&offer_id), offer);
+    assert_eq!(
+        pool_client.get_ext_tokens(),
+        vec![&e, token_client.address.clone()]
+    );
+    assert_eq!(token_client.balance(&pool_client.address), 600000);
+    assert_eq!(token_client.balance(&offerer), 400000);
+    assert_eq!(pool_client.version(), 2);
+
+    // the offer can still be accepted
+    pool_client.accept_offer(&supplier, &offer_id);
+    assert_eq!(tc_client.owner(&0), offerer);
+}
+
+#[test]
+fn test_migrate() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let supplier = Address::generate(&e);
+    let offerer = Address::generate(&e);
+    let (token_client, token_admin_client) = setup_test_token(&e, &admin);
+    let (pool_client, contract_id) = setup_pool(&e, &admin);
+    pool_client.add_ext_token(&admin, &token_client.address);
+
+    let tc_client = setup_tc(
+        &e,
+        &admin,
+        &buyer,
+        &1000000,
+        &1712793295,
+        &token_client.address,
+        &token_client.decimals(),
+    );
+    tc_client.mint_original(&admin, &supplier, &String::from_str(&e, ""));
+    token_admin_client.mint(&offerer, &1000000);
+    let offer_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address.clone(),
+        &600000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    let offer = pool_client.get_offer(&offer_id);
+    let expired_id = pool_client.create_offer(
+        &offerer,
+        &token_client.address.clone(),
+        &400000,
+        &tc_client.address,
+        &0,
+        &OfferTerms::default(),
+    );
+    pool_client.expire_offer(&offerer, &expired_id);
+    let expired = pool_client.get_offer(&expired_id);
+
+    // storage written by the current version needs no migration
+    let res = pool_client.try_migrate(&0, &10);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // storage written before versioning has no version, numeric statuses, no deadlines and no indexes
+    e.as_contract(&contract_id, || {
+        e.storage().instance().remove(&DataKey::Version);
+        for role in ALL_ROLES {
+            e.storage()
+                .persistent()
+                .remove(&DataKey::Role(role, admin.clone()));
+        }
+        for (id, old, status) in [(offer_id, &offer, 0), (expired_id, &expired, 1)] {
+            let old = OfferV1 {
+                from: old.from.clone(),
+                pool_token: old.pool_token.clone(),
+                amount: old.amount,
+                tc_contract: old.tc_contract.clone(),
+                tc_id: old.tc_id,
+                status,
+            };
+            e.storage().persistent().set(&DataKey::Offer(id), &old);
+        }
+        for key in [
+            DataKey::OffersByStatus(OfferStatus::Pending, 0),
+            DataKey::OffersByStatus(OfferStatus::Expired, 0),
+            DataKey::StatusCount(OfferStatus::Pending),
+            DataKey::StatusCount(OfferStatus::Expired),
+            DataKey::StatusSlot(offer_id),
+            DataKey::StatusSlot(expired_id),
+            DataKey::OffersByCreator(offerer.clone()),
+            DataKey::OffersForTC(tc_client.address.clone(), 0),
+        ] {
+            e.storage().persistent().remove(&key);
+        }
+    });
+    assert_eq!(pool_client.version(), 0);
+    assert_eq!(pool_client.offers_by_creator(&offerer), vec![&e]);
+
+    assert!(!pool_client.has_role(&Role::Guardian, &admin));
+    assert_eq!(pool_client.migrate(&0, &1), 1);
+    for role in ALL_ROLES {
+        assert!(pool_client.has_role(&role, &admin));
+    }
+    // the version only changes after the last batch
+    assert_eq!(pool_client.version(), 0);
+    assert_eq!(pool_client.get_offer(&offer_id), offer);
+
+    // the next batch has to start where the previous one stopped
+    let res = pool_client.try_migrate(&0, &1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    assert_eq!(pool_client.migrate(&1, &1), 2);
+    assert_eq!(pool_client.version(), 2);
+    assert_eq!(pool_client.get_offer(&offer_id), offer);
+    assert_eq!(pool_client.get_offer(&expired_id), expired);
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Pending, &0, &10),
+        vec![&e, offer_id]
+    );
+    assert_eq!(
+        pool_client.list_offers(&OfferStatus::Expired, &0, &10),
+        vec![&e, expired_id]
+    );
+    assert_eq!(pool_client.offers_by_creator(&offerer), vec![&e, offer_id]);
+    assert_eq!(
+        pool_client.offers_for_tc(&tc_client.address, &0),
+        vec![&e, offer_id]
+    );
+}
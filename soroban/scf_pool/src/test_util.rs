@@ -6,8 +6,12 @@ pub mod tc_contract {
     );
 }
 
+pub mod pool_contract {
+    soroban_sdk::contractimport!(file = "target/wasm32-unknown-unknown/release/pool.wasm");
+}
+
 use crate::contract::{OfferPool, OfferPoolClient};
-use soroban_sdk::{contracttype, testutils::BytesN as _, token, Address, BytesN, Env};
+use soroban_sdk::{contracttype, testutils::BytesN as _, token, Address, Bytes, BytesN, Env, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -50,7 +54,21 @@ pub fn setup_tc<'a>(
         .with_address(admin.clone(), BytesN::<32>::random(&e))
         .deploy(wasm_hash);
     let client = tc_contract::Client::new(e, &addr);
-    client.initialize(&admin.clone(), buyer, total_amount, end_time);
-    client.set_external_token_provider(ext_token_address, ext_token_decimals);
+    client.initialize(
+        &admin.clone(),
+        buyer,
+        total_amount,
+        end_time,
+        &Vec::new(e),
+        &None,
+    );
+    client.set_external_token_provider(admin, ext_token_address, ext_token_decimals);
     client
 }
+
+// uploads "wasm" with an extra custom section, so that the same code gets a new hash and an upgrade to it swaps the executable
+pub fn upload_rebuilt_wasm(e: &Env, wasm: &[u8]) -> BytesN<32> {
+    let mut rebuilt = Bytes::from_slice(e, wasm);
+    rebuilt.extend_from_slice(&[0, 9, 7, b'u', b'p', b'g', b'r', b'a', b'd', b'e', 1]);
+    e.deployer().upload_contract_wasm(rebuilt)
+}
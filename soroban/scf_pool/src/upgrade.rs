@@ -0,0 +1,105 @@
+use crate::error::Error;
+use crate::offer::{index_offer, read_supply};
+use crate::storage_types::{
+    DataKey, Offer, OfferStatus, OFFER_BUMP_AMOUNT, OFFER_LIFETIME_THRESHOLD, STORAGE_VERSION,
+};
+use soroban_sdk::{contracttype, panic_with_error, Address, Env};
+
+// layout of offers before version 2
+#[derive(Clone)]
+#[contracttype(export = false)]
+pub struct OfferV1 {
+    pub from: Address,
+    pub pool_token: Address,
+    pub amount: i128,
+    pub tc_contract: Address,
+    pub tc_id: i128,
+    pub status: i128,
+}
+
+// contracts deployed before versioning was introduced have no stored version
+pub fn read_version(e: &Env) -> u32 {
+    let key = DataKey::Version;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_version(e: &Env, version: u32) {
+    let key = DataKey::Version;
+    e.storage().instance().set(&key, &version);
+}
+
+// the next offer to migrate and the supply when the migration started, offers created since then are already in the current layout
+fn read_migration_cursor(e: &Env) -> Option<(i128, i128)> {
+    let key = DataKey::MigrationCursor;
+    e.storage().instance().get(&key)
+}
+
+// migrates the offers from "start" on, at most "limit" of them, and returns where the next batch starts
+pub fn migrate(e: &Env, start: i128, limit: u32) -> i128 {
+    let (cursor, end) = read_migration_cursor(e).unwrap_or((0, read_supply(e)));
+    if start != cursor {
+        panic_with_error!(e, Error::NotPermitted);
+    }
+    let batch_end = end.min(start.saturating_add(i128::from(limit)));
+    // version 1 did not change the layout of offers, so older storage starts at the version 2 step
+    if read_version(e) < 2 {
+        migrate_v2(e, start, batch_end);
+    }
+
+    let key = DataKey::MigrationCursor;
+    if batch_end < end {
+        e.storage().instance().set(&key, &(batch_end, end));
+    } else {
+        e.storage().instance().remove(&key);
+        write_version(e, STORAGE_VERSION);
+    }
+    batch_end
+}
+
+// version 2 added deadlines, OfferStatus, discount pricing, partial offers, fees and the offer indexes
+fn migrate_v2(e: &Env, start: i128, end: i128) {
+    for offer_id in start..end {
+        let key = DataKey::Offer(offer_id);
+        if let Some(old) = e.storage().persistent().get::<DataKey, OfferV1>(&key) {
+            let status = match old.status {
+                1 => OfferStatus::Expired,
+                2 => OfferStatus::Accepted,
+                _ => OfferStatus::Pending,
+            };
+            // offers accepted so far paid their full amount, their yield is unknown
+            let price = if status == OfferStatus::Accepted {
+                old.amount
+            } else {
+                0
+            };
+            let offer = Offer {
+                from: old.from,
+                pool_token: old.pool_token,
+                amount: old.amount,
+                tc_contract: old.tc_contract,
+                tc_id: old.tc_id,
+                status,
+                valid_until: None,
+                discount_bps: None,
+                price,
+                yield_bps: 0,
+                tc_amount: None,
+                filled_tc_id: None,
+                fee_bps: 0,
+                fee: 0,
+            };
+            e.storage().persistent().set(&key, &offer);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, OFFER_LIFETIME_THRESHOLD, OFFER_BUMP_AMOUNT);
+            index_offer(
+                e,
+                offer_id,
+                offer.status,
+                &offer.from,
+                &offer.tc_contract,
+                offer.tc_id,
+            );
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use crate::storage_types::DataKey;
-use soroban_sdk::{Env, Address, panic_with_error};
 use crate::errors::Error;
+use crate::storage_types::{DataKey, PendingAdmin};
+use soroban_sdk::{panic_with_error, Address, Env};
 
 pub fn has_administrator(env: &Env) -> bool {
     let key = DataKey::Admin;
@@ -18,4 +18,26 @@ pub fn read_administrator(env: &Env) -> Address {
 pub fn write_administrator(env: &Env, id: &Address) {
     let key = DataKey::Admin;
     env.storage().instance().set(&key, id);
-}
\ No newline at end of file
+}
+
+pub fn read_pending_admin(env: &Env) -> Option<PendingAdmin> {
+    let key = DataKey::PendingAdmin;
+    env.storage().instance().get(&key)
+}
+
+pub fn write_pending_admin(env: &Env, pending_admin: &PendingAdmin) {
+    let key = DataKey::PendingAdmin;
+    env.storage().instance().set(&key, pending_admin);
+}
+
+pub fn remove_pending_admin(env: &Env) {
+    let key = DataKey::PendingAdmin;
+    env.storage().instance().remove(&key);
+}
+
+pub fn is_pending_admin_expired(env: &Env, pending_admin: &PendingAdmin) -> bool {
+    match pending_admin.expiration_time {
+        Some(expiration_time) => env.ledger().timestamp() > expiration_time,
+        None => false,
+    }
+}
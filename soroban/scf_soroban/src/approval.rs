@@ -0,0 +1,76 @@
+use crate::errors::Error;
+use crate::storage_types::{
+    ApprovalData, DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD,
+};
+use soroban_sdk::{panic_with_error, Address, Env};
+
+pub fn read_approval(env: &Env, id: i128) -> Option<Address> {
+    let key = DataKey::Approval(id);
+    match env.storage().temporary().get::<DataKey, ApprovalData>(&key) {
+        Some(approval) if approval.expiration_ledger >= env.ledger().sequence() => {
+            Some(approval.spender)
+        }
+        _ => None,
+    }
+}
+
+pub fn write_approval(env: &Env, id: i128, spender: Address, expiration_ledger: u32) {
+    // an expiration ledger in the past revokes the approval
+    if expiration_ledger < env.ledger().sequence() {
+        remove_approval(env, id);
+        return;
+    }
+    let key = DataKey::Approval(id);
+    let approval = ApprovalData {
+        spender,
+        expiration_ledger,
+    };
+    env.storage().temporary().set(&key, &approval);
+    let live_for = expiration_ledger - env.ledger().sequence();
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, live_for, live_for);
+}
+
+pub fn remove_approval(env: &Env, id: i128) {
+    let key = DataKey::Approval(id);
+    env.storage().temporary().remove(&key);
+}
+
+pub fn read_operator(env: &Env, owner: Address, operator: Address) -> bool {
+    let key = DataKey::Operator(owner, operator);
+    match env.storage().persistent().get::<DataKey, bool>(&key) {
+        Some(approved) => {
+            env.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            approved
+        }
+        None => false,
+    }
+}
+
+pub fn write_operator(env: &Env, owner: Address, operator: Address, approved: bool) {
+    let key = DataKey::Operator(owner, operator);
+    if !approved {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    env.storage().persistent().set(&key, &approved);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+// "spender" may move token "id" of "owner" if it is approved for the token or is an operator of the owner
+pub fn check_spender(env: &Env, spender: &Address, owner: &Address, id: i128) {
+    if read_approval(env, id).as_ref() == Some(spender) {
+        return;
+    }
+    if read_operator(env, owner.clone(), spender.clone()) {
+        return;
+    }
+    panic_with_error!(env, Error::NotAuthorized);
+}
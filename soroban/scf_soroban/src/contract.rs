@@ -1,19 +1,45 @@
-use crate::admin::{has_administrator, read_administrator, write_administrator};
+use crate::admin::{
+    has_administrator, is_pending_admin_expired, read_administrator, read_pending_admin,
+    remove_pending_admin, write_administrator, write_pending_admin,
+};
+use crate::approval::{
+    check_spender, read_approval, read_operator, write_approval, write_operator,
+};
 use crate::balance::{increment_supply, read_supply};
 use crate::errors::Error;
 use crate::event;
 use crate::interface::TokenizedCertificateTrait;
 use crate::metadata::{read_external_token, write_external_token};
-use crate::order_info::{read_order_info, write_order_info};
-use crate::order_state::{read_paid, update_and_read_expired, write_paid};
+use crate::order_info::{
+    check_installments, check_split_policy, read_grace_period, read_installments, read_order_info,
+    read_split_policy, write_grace_period, write_installments, write_order_info,
+    write_split_policy,
+};
+use crate::order_state::{
+    read_paid, read_paid_amount, read_redeemed, read_settled, read_shortfall,
+    update_and_read_expired, update_and_read_owner, write_paid, write_paid_amount, write_redeemed,
+    write_settled, write_shortfall,
+};
 use crate::owner::{
-    add_vc, check_owner, read_all_owned, read_owner, read_recipient, read_vc, write_owner,
-    write_recipient, write_vc,
+    add_vc, check_owner, read_all_owned, read_owner, read_pending, read_recipient, read_vc,
+    write_owner, write_recipient, write_vc,
+};
+use crate::pause::{
+    check_exit_allowed, check_not_paused, is_paused, read_exits_allowed, write_exits_allowed,
+    write_paused,
+};
+use crate::roles::{check_role, has_role, write_role, ALL_ROLES};
+use crate::storage_types::{
+    Installment, PendingAdmin, Role, SplitPolicy, SplitRequest, INSTANCE_BUMP_AMOUNT,
+    INSTANCE_LIFETIME_THRESHOLD, STORAGE_VERSION,
 };
-use crate::storage_types::{SplitRequest, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
-use crate::sub_tc::{read_sub_tc, read_sub_tc_disabled, write_sub_tc, write_sub_tc_disabled};
+use crate::sub_tc::{
+    check_not_frozen, is_frozen, read_children, read_frozen, read_sub_tc, read_sub_tc_disabled,
+    remove_frozen, write_children, write_frozen, write_sub_tc, write_sub_tc_disabled,
+};
+use crate::upgrade::{migrate, read_version, write_version};
 use soroban_sdk::{
-    contract, contractimpl, panic_with_error, token, vec, Address, Env, String, Vec,
+    contract, contractimpl, panic_with_error, token, vec, Address, BytesN, Env, String, Vec,
 };
 
 #[contract]
@@ -27,6 +53,8 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         buyer_address: Address,
         total_amount: u32,
         end_time: u64,
+        installments: Vec<Installment>,
+        split_policy: Option<SplitPolicy>,
     ) {
         if has_administrator(&e) {
             panic!("already initialized")
@@ -34,10 +62,22 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         if end_time <= e.ledger().timestamp() {
             panic_with_error!(&e, Error::NotPermitted);
         }
+        check_installments(&e, &installments, total_amount, end_time);
+        if let Some(split_policy) = &split_policy {
+            check_split_policy(&e, split_policy);
+        }
         write_administrator(&e, &admin);
+        for role in ALL_ROLES {
+            write_role(&e, role, &admin, true);
+        }
         //write_name(&e, &name);
         //write_symbol(&e, &symbol);
         write_order_info(&e, buyer_address, total_amount, end_time);
+        write_installments(&e, &installments);
+        if let Some(split_policy) = split_policy {
+            write_split_policy(&e, &split_policy);
+        }
+        write_version(&e, STORAGE_VERSION);
     }
 
     fn admin(env: Env) -> Address {
@@ -47,17 +87,193 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         read_administrator(&env)
     }
 
-    fn set_admin(env: Env, new_admin: Address) {
+    fn propose_admin(env: Env, new_admin: Address, expiration_time: Option<u64>) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
         let admin = read_administrator(&env);
         admin.require_auth();
 
+        if let Some(expiration_time) = expiration_time {
+            if expiration_time <= env.ledger().timestamp() {
+                panic_with_error!(&env, Error::NotPermitted);
+            }
+        }
+        write_pending_admin(
+            &env,
+            &PendingAdmin {
+                address: new_admin.clone(),
+                expiration_time,
+            },
+        );
+        event::propose_admin(&env, admin, new_admin, expiration_time);
+    }
+
+    fn accept_admin(env: Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let pending_admin = match read_pending_admin(&env) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&env, Error::NotFound),
+        };
+        if is_pending_admin_expired(&env, &pending_admin) {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+        let new_admin = pending_admin.address;
+        new_admin.require_auth();
+
+        // the roles of the outgoing admin pass to the new admin, so the old key keeps none of them
+        let admin = read_administrator(&env);
+        for role in ALL_ROLES {
+            if has_role(&env, role, &admin) {
+                write_role(&env, role, &admin, false);
+                write_role(&env, role, &new_admin, true);
+            }
+        }
         write_administrator(&env, &new_admin);
+        remove_pending_admin(&env);
         event::set_admin(&env, admin, new_admin);
     }
 
+    fn cancel_admin_proposal(env: Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        let pending_admin = match read_pending_admin(&env) {
+            Some(pending_admin) => pending_admin,
+            None => panic_with_error!(&env, Error::NotFound),
+        };
+        remove_pending_admin(&env);
+        event::cancel_admin_proposal(&env, admin, pending_admin.address);
+    }
+
+    fn pending_admin(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        match read_pending_admin(&env) {
+            Some(pending_admin) if !is_pending_admin_expired(&env, &pending_admin) => {
+                Some(pending_admin.address)
+            }
+            _ => None,
+        }
+    }
+
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    fn migrate(env: Env, start: i128, limit: u32) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if read_version(&env) >= STORAGE_VERSION {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+        // storage written before versioning predates roles, so the admin is granted the roles it held implicitly
+        if read_version(&env) < 1 {
+            for role in ALL_ROLES {
+                write_role(&env, role, &admin, true);
+            }
+        }
+        migrate(&env, start, limit)
+    }
+
+    fn version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_version(&env)
+    }
+
+    fn grant_role(env: Env, role: Role, account: Address) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_role(&env, role, &account, true);
+        event::grant_role(&env, admin, role, account);
+    }
+
+    fn revoke_role(env: Env, role: Role, account: Address) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_role(&env, role, &account, false);
+        event::revoke_role(&env, admin, role, account);
+    }
+
+    fn has_role(env: Env, role: Role, account: Address) -> bool {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        has_role(&env, role, &account)
+    }
+
+    fn pause(env: Env, caller: Address) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&env, true);
+        event::pause(&env, caller);
+    }
+
+    fn unpause(env: Env, caller: Address) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_paused(&env, false);
+        event::unpause(&env, caller);
+    }
+
+    fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        is_paused(&env)
+    }
+
+    fn set_exits_allowed(env: Env, caller: Address, allowed: bool) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Guardian, &caller);
+        caller.require_auth();
+
+        write_exits_allowed(&env, allowed);
+    }
+
+    fn exits_allowed(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_exits_allowed(&env)
+    }
+
     fn amount(env: Env, id: i128) -> u32 {
         env.storage()
             .instance()
@@ -78,8 +294,7 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        update_and_read_expired(&env);
-        read_owner(&env, id)
+        update_and_read_owner(&env, id)
     }
 
     fn vc(env: Env, id: i128) -> Vec<String> {
@@ -93,7 +308,6 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        update_and_read_expired(&env);
         read_all_owned(&env, address)
     }
 
@@ -104,23 +318,107 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         read_sub_tc_disabled(&env, id)
     }
 
+    fn freeze(env: Env, caller: Address, id: i128, reason_hash: BytesN<32>) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Compliance, &caller);
+        caller.require_auth();
+
+        // fails if the token does not exist
+        read_sub_tc(&env, id);
+        write_frozen(&env, id, &reason_hash);
+        event::freeze(&env, caller, id, reason_hash);
+    }
+
+    fn unfreeze(env: Env, caller: Address, id: i128) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Compliance, &caller);
+        caller.require_auth();
+
+        if read_frozen(&env, id).is_none() {
+            panic_with_error!(&env, Error::NotFound);
+        }
+        remove_frozen(&env, id);
+        event::unfreeze(&env, caller, id);
+    }
+
+    fn is_frozen(env: Env, id: i128) -> bool {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        is_frozen(&env, id)
+    }
+
     fn transfer(env: Env, from: Address, to: Address, id: i128) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        update_and_read_expired(&env);
+        check_not_paused(&env);
+        check_not_frozen(&env, id);
+        update_and_read_owner(&env, id);
         check_owner(&env, &from, id);
         from.require_auth();
         write_owner(&env, id, Some(to.clone()));
         event::transfer(&env, from, to, id);
     }
 
-    fn mint_original(env: Env, to: Address, vc: String) {
+    fn approve(env: Env, owner: Address, spender: Address, id: i128, expiration_ledger: u32) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let admin = read_administrator(&env);
-        admin.require_auth();
+        update_and_read_owner(&env, id);
+        check_owner(&env, &owner, id);
+        owner.require_auth();
+        write_approval(&env, id, spender.clone(), expiration_ledger);
+        event::approve(&env, owner, spender, id, expiration_ledger);
+    }
+
+    fn set_approval_for_all(env: Env, owner: Address, operator: Address, approved: bool) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        owner.require_auth();
+        write_operator(&env, owner.clone(), operator.clone(), approved);
+        event::approve_all(&env, owner, operator, approved);
+    }
+
+    fn get_approved(env: Env, id: i128) -> Option<Address> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_approval(&env, id)
+    }
+
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_operator(&env, owner, operator)
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, id: i128) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&env);
+        check_not_frozen(&env, id);
+        update_and_read_owner(&env, id);
+        check_owner(&env, &from, id);
+        check_spender(&env, &spender, &from, id);
+        spender.require_auth();
+        write_owner(&env, id, Some(to.clone()));
+        event::transfer(&env, from, to, id);
+    }
+
+    fn mint_original(env: Env, caller: Address, to: Address, vc: String) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Minter, &caller);
+        caller.require_auth();
 
         let id = read_supply(&env);
         if id != 0 {
@@ -136,15 +434,14 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         event::mint(&env, to, id)
     }
 
-    fn burn(env: Env, id: i128) {
+    fn burn(env: Env, caller: Address, id: i128) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let admin = read_administrator(&env);
-        admin.require_auth();
+        check_role(&env, Role::Minter, &caller);
+        caller.require_auth();
 
-        update_and_read_expired(&env);
-        let from = read_owner(&env, id);
+        let from = update_and_read_owner(&env, id);
         write_owner(&env, id, None);
 
         event::burn(&env, from, id);
@@ -154,6 +451,8 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&env);
+        check_not_frozen(&env, id);
         if read_sub_tc_disabled(&env, id) {
             // if the TC is disabled, it has already been split
             panic_with_error!(&env, Error::NotPermitted);
@@ -169,14 +468,17 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         let contract_addr = env.current_contract_address();
 
         let parent = read_sub_tc(&env, id);
-        if parent.depth >= 5 {
+        let policy = read_split_policy(&env);
+        if parent.depth >= policy.max_depth {
             panic_with_error!(&env, Error::SplitLimitReached);
         }
         let mut sum = 0;
         let root_total = read_order_info(&env).total_amount;
         for req in splits.clone() {
-            // each split must be at least 10% of the root total_amount
-            if req.amount * 10 < root_total {
+            // each split must be at least min_split_bps of the root total_amount
+            if u64::from(req.amount) * 10000
+                < u64::from(root_total) * u64::from(policy.min_split_bps)
+            {
                 panic_with_error!(&env, Error::SplitAmountTooLow);
             }
             sum += req.amount;
@@ -184,6 +486,13 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         if sum > parent.amount {
             panic_with_error!(&env, Error::AmountTooMuch);
         }
+        if sum < parent.amount && !policy.allow_remainder {
+            panic_with_error!(&env, Error::SplitRemainderNotAllowed);
+        }
+        let children = splits.len() + if sum < parent.amount { 1 } else { 0 };
+        if children > policy.max_children {
+            panic_with_error!(&env, Error::SplitChildrenLimitReached);
+        }
 
         let mut remaining = parent.amount;
         let mut new_ids = Vec::new(&env);
@@ -211,29 +520,121 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         }
 
         // disable the original TC
+        write_children(&env, id, &new_ids);
         write_sub_tc_disabled(&env, id, true);
 
         event::split(&env, owner, id, new_ids.clone());
         new_ids
     }
 
+    fn merge(env: Env, id: i128) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&env);
+        check_not_frozen(&env, id);
+        let children = read_children(&env, id);
+        if !read_sub_tc_disabled(&env, id) || children.is_empty() {
+            // only a TC that has been split can be merged
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+        if update_and_read_expired(&env) {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+
+        let parent = read_sub_tc(&env, id);
+        let parent_owner = read_owner(&env, id);
+        let contract_addr = env.current_contract_address();
+        let mut owner = parent_owner.clone();
+        for (i, child_id) in children.iter().enumerate() {
+            let child = read_sub_tc(&env, child_id);
+            if child.parent != id || child.depth != parent.depth + 1 {
+                panic_with_error!(&env, Error::InvalidArgs);
+            }
+            if read_sub_tc_disabled(&env, child_id) {
+                // the child has been split itself, its own children must be merged first
+                panic_with_error!(&env, Error::NotPermitted);
+            }
+            check_not_frozen(&env, child_id);
+            // unclaimed children are treated as belonging to the owner of the parent
+            let mut child_owner = read_owner(&env, child_id);
+            if child_owner == contract_addr {
+                child_owner = parent_owner.clone();
+            }
+            if i == 0 {
+                owner = child_owner;
+            } else if child_owner != owner {
+                panic_with_error!(&env, Error::NotOwned);
+            }
+        }
+        owner.require_auth();
+
+        // burn the children and enable the parent again
+        for child_id in children.iter() {
+            write_owner(&env, child_id, None);
+        }
+        write_children(&env, id, &Vec::new(&env));
+        write_sub_tc_disabled(&env, id, false);
+        if owner != parent_owner {
+            write_owner(&env, id, Some(owner.clone()));
+        }
+
+        event::merge(&env, owner, id, children);
+    }
+
+    fn children(env: Env, id: i128) -> Vec<i128> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_children(&env, id)
+    }
+
     fn redeem(env: Env, id: i128) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        if !update_and_read_expired(&env) || !read_paid(&env) || read_sub_tc_disabled(&env, id) {
+        check_exit_allowed(&env);
+        check_not_frozen(&env, id);
+        if !update_and_read_expired(&env) || read_sub_tc_disabled(&env, id) {
             panic_with_error!(&env, Error::NotPermitted);
         }
 
-        let owner = read_owner(&env, id);
+        let owner = update_and_read_owner(&env, id);
         owner.require_auth();
 
-        // send funds to owner address
+        // the token is entitled to its pro-rata share of everything the buyer has paid so far
         let sub_tc = read_sub_tc(&env, id);
+        let total_amount = read_order_info(&env).total_amount;
+        let entitled =
+            i128::from(sub_tc.amount) * read_paid_amount(&env) / i128::from(total_amount);
+        let redeemed = read_redeemed(&env, id);
+        let amount = entitled - redeemed;
+        // once the order is paid or settled, no further payments can be made and the token is redeemed for good
+        let closed = read_paid(&env) || read_settled(&env);
+        if amount < 0 || (amount == 0 && !closed) {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+
+        // send funds to owner address
         let ext_token = read_external_token(&env);
-        let client = token::Client::new(&env, &ext_token.contract_addr);
-        let amount = i128::from(sub_tc.amount) * 10i128.pow(ext_token.decimals);
-        client.transfer(&env.current_contract_address(), &owner, &amount);
+        if amount > 0 {
+            let client = token::Client::new(&env, &ext_token.contract_addr);
+            client.transfer(&env.current_contract_address(), &owner, &amount);
+            write_redeemed(&env, id, entitled);
+        }
+
+        if !closed {
+            // the token stays alive so it can claim the rest of its share after further payments
+            event::claim(&env, owner, id, amount);
+            return;
+        }
+
+        // record the part of the token's value the buyer never paid
+        let shortfall = i128::from(sub_tc.amount) * 10i128.pow(ext_token.decimals) - entitled;
+        if shortfall > 0 {
+            write_shortfall(&env, id, shortfall);
+            event::shortfall(&env, owner.clone(), id, shortfall);
+        }
 
         // burn the token
         write_owner(&env, id, None);
@@ -241,12 +642,17 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         event::redeem(&env, owner, id);
     }
 
-    fn set_external_token_provider(env: Env, contract_addr: Address, decimals: u32) {
+    fn set_external_token_provider(
+        env: Env,
+        caller: Address,
+        contract_addr: Address,
+        decimals: u32,
+    ) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let admin = read_administrator(&env);
-        admin.require_auth();
+        check_role(&env, Role::Treasury, &caller);
+        caller.require_auth();
 
         write_external_token(&env, contract_addr, decimals);
     }
@@ -265,6 +671,21 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         update_and_read_expired(&env)
     }
 
+    fn end_time(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_order_info(&env).end_time
+    }
+
+    fn external_token(env: Env) -> (Address, u32) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        let ext_token = read_external_token(&env);
+        (ext_token.contract_addr, ext_token.decimals)
+    }
+
     fn recipient(env: Env, id: i128) -> Address {
         env.storage()
             .instance()
@@ -276,12 +697,11 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&env);
 
-        let expired = update_and_read_expired(&env);
-        let owner = read_owner(&env, id);
-        if owner != env.current_contract_address() || read_sub_tc_disabled(&env, id) || expired {
-            panic_with_error!(&env, Error::NotPermitted);
-        }
+        check_pending(&env, id);
+        check_not_frozen(&env, id);
+        let owner = env.current_contract_address();
 
         let recipient = read_recipient(&env, id);
         recipient.require_auth();
@@ -291,7 +711,48 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         event::transfer(&env, owner, recipient, id);
     }
 
-    fn pay_off(env: Env, from: Address) {
+    fn reject(env: Env, id: i128) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&env);
+        check_not_frozen(&env, id);
+
+        check_pending(&env, id);
+        let recipient = read_recipient(&env, id);
+        recipient.require_auth();
+
+        return_to_parent_owner(&env, id);
+        event::reject(&env, recipient, id);
+    }
+
+    fn cancel_split_request(env: Env, id: i128) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_not_paused(&env);
+        check_not_frozen(&env, id);
+
+        check_pending(&env, id);
+        let parent_owner = read_owner(&env, read_sub_tc(&env, id).parent);
+        parent_owner.require_auth();
+
+        return_to_parent_owner(&env, id);
+        event::cancel(&env, parent_owner, id);
+    }
+
+    fn pending_sign_offs(env: Env, address: Address) -> Vec<i128> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        if update_and_read_expired(&env) {
+            // split requests can no longer be signed off after end_time
+            return Vec::new(&env);
+        }
+        read_pending(&env, &address)
+    }
+
+    fn pay_off(env: Env, from: Address, amount: i128) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
@@ -300,26 +761,183 @@ impl TokenizedCertificateTrait for TokenizedCertificate {
         if paid {
             panic_with_error!(&env, Error::NotEmpty);
         }
+        if read_settled(&env) {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidArgs);
+        }
         let ext_token = read_external_token(&env);
         let client = token::Client::new(&env, &ext_token.contract_addr);
         let order_info = read_order_info(&env);
-        let amount = i128::from(order_info.total_amount) * 10i128.pow(ext_token.decimals);
+        let total = i128::from(order_info.total_amount) * 10i128.pow(ext_token.decimals);
+        let paid_amount = read_paid_amount(&env) + amount;
+        if paid_amount > total {
+            panic_with_error!(&env, Error::AmountTooMuch);
+        }
 
         if from != order_info.buyer_address {
             panic_with_error!(&env, Error::NotAuthorized);
         }
         from.require_auth();
-        client.transfer(&from, &env.current_contract_address(), &i128::from(amount));
-        write_paid(&env, true);
+        client.transfer(&from, &env.current_contract_address(), &amount);
+        write_paid_amount(&env, paid_amount);
+        if paid_amount == total {
+            write_paid(&env, true);
+        }
+
+        event::pay_off(&env, from, amount);
     }
 
-    fn add_vc(env: Env, id: i128, vc: String) {
+    fn settle(env: Env, caller: Address) {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        let admin = read_administrator(&env);
-        admin.require_auth();
+        check_role(&env, Role::Treasury, &caller);
+        caller.require_auth();
+
+        if read_paid(&env) || read_settled(&env) {
+            panic_with_error!(&env, Error::NotEmpty);
+        }
+        let end_time = read_order_info(&env).end_time;
+        if env.ledger().timestamp() < end_time.saturating_add(read_grace_period(&env)) {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+        update_and_read_expired(&env);
+
+        write_settled(&env, true);
+        event::settle(&env, caller, read_paid_amount(&env));
+    }
+
+    fn set_grace_period(env: Env, caller: Address, grace_period: u64) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Risk, &caller);
+        caller.require_auth();
+
+        write_grace_period(&env, grace_period);
+    }
+
+    fn set_split_policy(env: Env, caller: Address, split_policy: SplitPolicy) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Risk, &caller);
+        caller.require_auth();
+
+        // the rules cannot change once a TC has been split
+        if read_supply(&env) > 1 {
+            panic_with_error!(&env, Error::NotPermitted);
+        }
+        check_split_policy(&env, &split_policy);
+        write_split_policy(&env, &split_policy);
+    }
+
+    fn split_policy(env: Env) -> SplitPolicy {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_split_policy(&env)
+    }
+
+    fn grace_period(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_grace_period(&env)
+    }
+
+    fn check_settled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_settled(&env)
+    }
+
+    fn shortfall(env: Env, id: i128) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        // a settled order takes no further payments, so the shortfall of a live token is already known
+        if read_settled(&env) && !read_sub_tc_disabled(&env, id) {
+            let sub_tc = read_sub_tc(&env, id);
+            let total_amount = read_order_info(&env).total_amount;
+            let entitled =
+                i128::from(sub_tc.amount) * read_paid_amount(&env) / i128::from(total_amount);
+            let value = i128::from(sub_tc.amount) * 10i128.pow(read_external_token(&env).decimals);
+            return (value - entitled).max(0);
+        }
+        read_shortfall(&env, id)
+    }
+
+    fn paid_amount(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_paid_amount(&env)
+    }
+
+    fn installments(env: Env) -> Vec<Installment> {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        read_installments(&env)
+    }
+
+    fn amount_due(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let order_info = read_order_info(&env);
+        let installments = read_installments(&env);
+        let now = env.ledger().timestamp();
+        let mut due: u64 = 0;
+        if installments.is_empty() {
+            // without a schedule, the whole amount is due at maturity
+            if now >= order_info.end_time {
+                due = u64::from(order_info.total_amount);
+            }
+        } else {
+            for installment in installments.iter() {
+                if installment.due_time <= now {
+                    due += u64::from(installment.amount);
+                }
+            }
+        }
+        let decimals = read_external_token(&env).decimals;
+        let outstanding = i128::from(due) * 10i128.pow(decimals) - read_paid_amount(&env);
+        if outstanding > 0 {
+            outstanding
+        } else {
+            0
+        }
+    }
+
+    fn add_vc(env: Env, caller: Address, id: i128, vc: String) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+        check_role(&env, Role::Compliance, &caller);
+        caller.require_auth();
 
         add_vc(&env, id, vc);
     }
 }
+
+// a split TC can only be signed off, rejected or cancelled while it waits at the contract address before end_time
+fn check_pending(env: &Env, id: i128) {
+    let expired = update_and_read_expired(env);
+    let owner = read_owner(env, id);
+    if owner != env.current_contract_address() || read_sub_tc_disabled(env, id) || expired {
+        panic_with_error!(env, Error::NotPermitted);
+    }
+}
+
+// transfer a split TC waiting for sign off back to the owner of its parent
+fn return_to_parent_owner(env: &Env, id: i128) {
+    let to = read_owner(env, read_sub_tc(env, id).parent);
+    write_owner(env, id, Some(to.clone()));
+    event::transfer(env, env.current_contract_address(), to, id);
+}
@@ -16,4 +16,8 @@ pub enum Error {
     VCListCapacityReached = 10,
     SplitLimitReached = 11,
     SplitAmountTooLow = 12,
+    SplitChildrenLimitReached = 13,
+    SplitRemainderNotAllowed = 14,
+    Paused = 15,
+    Frozen = 16,
 }
@@ -1,15 +1,51 @@
-use soroban_sdk::{symbol_short, Address, Env, Vec};
+use crate::storage_types::Role;
+use soroban_sdk::{symbol_short, Address, BytesN, Env, Vec};
 
 pub(crate) fn set_admin(e: &Env, admin: Address, new_admin: Address) {
     let topics = (symbol_short!("set_admin"), admin);
     e.events().publish(topics, new_admin);
 }
 
+pub(crate) fn propose_admin(
+    e: &Env,
+    admin: Address,
+    new_admin: Address,
+    expiration_time: Option<u64>,
+) {
+    let topics = (symbol_short!("propose"), admin);
+    e.events().publish(topics, (new_admin, expiration_time));
+}
+
+pub(crate) fn cancel_admin_proposal(e: &Env, admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("unpropose"), admin);
+    e.events().publish(topics, new_admin);
+}
+
+pub(crate) fn grant_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("grant"), role, account);
+    e.events().publish(topics, admin);
+}
+
+pub(crate) fn revoke_role(e: &Env, admin: Address, role: Role, account: Address) {
+    let topics = (symbol_short!("revoke"), role, account);
+    e.events().publish(topics, admin);
+}
+
 pub(crate) fn transfer(e: &Env, from: Address, to: Address, id: i128) {
     let topics = (symbol_short!("transfer"), from, to);
     e.events().publish(topics, id);
 }
 
+pub(crate) fn approve(e: &Env, owner: Address, spender: Address, id: i128, expiration_ledger: u32) {
+    let topics = (symbol_short!("approve"), owner, spender);
+    e.events().publish(topics, (id, expiration_ledger));
+}
+
+pub(crate) fn approve_all(e: &Env, owner: Address, operator: Address, approved: bool) {
+    let topics = (symbol_short!("appr_all"), owner, operator);
+    e.events().publish(topics, approved);
+}
+
 pub(crate) fn mint(e: &Env, to: Address, id: i128) {
     let topics = (symbol_short!("mint"), to);
     e.events().publish(topics, id);
@@ -29,3 +65,58 @@ pub(crate) fn split(e: &Env, from: Address, id: i128, new_ids: Vec<i128>) {
     let topics = (symbol_short!("split"), from);
     e.events().publish(topics, (id, new_ids));
 }
+
+pub(crate) fn merge(e: &Env, owner: Address, id: i128, merged_ids: Vec<i128>) {
+    let topics = (symbol_short!("merge"), owner);
+    e.events().publish(topics, (id, merged_ids));
+}
+
+pub(crate) fn reject(e: &Env, recipient: Address, id: i128) {
+    let topics = (symbol_short!("reject"), recipient);
+    e.events().publish(topics, id);
+}
+
+pub(crate) fn cancel(e: &Env, from: Address, id: i128) {
+    let topics = (symbol_short!("cancel"), from);
+    e.events().publish(topics, id);
+}
+
+pub(crate) fn claim(e: &Env, owner: Address, id: i128, amount: i128) {
+    let topics = (symbol_short!("claim"), owner);
+    e.events().publish(topics, (id, amount));
+}
+
+pub(crate) fn pay_off(e: &Env, from: Address, amount: i128) {
+    let topics = (symbol_short!("pay_off"), from);
+    e.events().publish(topics, amount);
+}
+
+pub(crate) fn settle(e: &Env, admin: Address, paid_amount: i128) {
+    let topics = (symbol_short!("settle"), admin);
+    e.events().publish(topics, paid_amount);
+}
+
+pub(crate) fn shortfall(e: &Env, owner: Address, id: i128, amount: i128) {
+    let topics = (symbol_short!("shortfall"), owner);
+    e.events().publish(topics, (id, amount));
+}
+
+pub(crate) fn pause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("pause"), caller);
+    e.events().publish(topics, ());
+}
+
+pub(crate) fn unpause(e: &Env, caller: Address) {
+    let topics = (symbol_short!("unpause"), caller);
+    e.events().publish(topics, ());
+}
+
+pub(crate) fn freeze(e: &Env, caller: Address, id: i128, reason_hash: BytesN<32>) {
+    let topics = (symbol_short!("freeze"), caller);
+    e.events().publish(topics, (id, reason_hash));
+}
+
+pub(crate) fn unfreeze(e: &Env, caller: Address, id: i128) {
+    let topics = (symbol_short!("unfreeze"), caller);
+    e.events().publish(topics, id);
+}
@@ -1,6 +1,6 @@
-use soroban_sdk::{Address, Env, String, Vec};
+use soroban_sdk::{Address, BytesN, Env, String, Vec};
 
-use crate::storage_types::SplitRequest;
+use crate::storage_types::{Installment, Role, SplitPolicy, SplitRequest};
 
 pub trait TokenizedCertificateTrait {
     // --------------------------------------------------------------------------------
@@ -10,9 +10,63 @@ pub trait TokenizedCertificateTrait {
     /// Returns the current administrator
     fn admin(env: Env) -> Address;
 
-    /// If "admin" is the administrator, set the administrator to "new_admin".
+    /// Propose "new_admin" as the next administrator. Can only be called by the admin. Replaces any pending proposal.
+    /// If "expiration_time" is set, the proposal can only be accepted until that Unix timestamp.
+    /// Emit event with topics = ["propose", admin: Address], data = [new_admin: Address, expiration_time: Option<u64>]
+    fn propose_admin(env: Env, new_admin: Address, expiration_time: Option<u64>);
+
+    /// Called by the proposed administrator to become the administrator. The roles held by the previous administrator are moved to the new one.
     /// Emit event with topics = ["set_admin", admin: Address], data = [new_admin: Address]
-    fn set_admin(env: Env, new_admin: Address);
+    fn accept_admin(env: Env);
+
+    /// Withdraw the pending administrator proposal. Can only be called by the admin.
+    /// Emit event with topics = ["unpropose", admin: Address], data = [new_admin: Address]
+    fn cancel_admin_proposal(env: Env);
+
+    /// Get the proposed administrator, if a proposal is pending and has not expired.
+    fn pending_admin(env: Env) -> Option<Address>;
+
+    /// Replace the contract code with the uploaded WASM identified by "new_wasm_hash". Can only be called by the admin.
+    /// Storage is kept as is; call migrate() afterwards if the new code uses a newer storage layout.
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>);
+
+    /// Rewrite storage written by an older version of the contract to the current layout. Can only be called by the admin.
+    /// Each call migrates at most "limit" TCs from id "start" on and returns the id the next call starts at; the first call starts at 0.
+    /// The version is bumped after the last TC, until then the progress is kept and a call from any other "start" fails.
+    /// Will fail if the storage is already up to date.
+    fn migrate(env: Env, start: i128, limit: u32) -> i128;
+
+    /// Get the version of the storage layout.
+    fn version(env: Env) -> u32;
+
+    /// Grant "role" to "account". Can only be called by the admin. The admin holds every role after initialization.
+    /// Emit event with topics = ["grant", role: Role, account: Address], data = [admin: Address]
+    fn grant_role(env: Env, role: Role, account: Address);
+
+    /// Revoke "role" from "account". Can only be called by the admin.
+    /// Emit event with topics = ["revoke", role: Role, account: Address], data = [admin: Address]
+    fn revoke_role(env: Env, role: Role, account: Address);
+
+    /// Check whether "account" holds "role".
+    fn has_role(env: Env, role: Role, account: Address) -> bool;
+
+    /// Stop the state-changing entry points until unpause() is called. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["pause", caller: Address], data = []
+    fn pause(env: Env, caller: Address);
+
+    /// Resume normal operation after a pause. "caller" must hold the Guardian role.
+    /// Emit event with topics = ["unpause", caller: Address], data = []
+    fn unpause(env: Env, caller: Address);
+
+    /// Check whether the contract is paused.
+    fn is_paused(env: Env) -> bool;
+
+    /// Set whether redeem() remain available while the contract is paused. "caller" must hold the Guardian role.
+    /// Exits are allowed by default, so that users can always withdraw their funds.
+    fn set_exits_allowed(env: Env, caller: Address, allowed: bool);
+
+    /// Check whether redeem() remain available while the contract is paused.
+    fn exits_allowed(env: Env) -> bool;
 
     // --------------------------------------------------------------------------------
     // Token interface
@@ -36,26 +90,73 @@ pub trait TokenizedCertificateTrait {
     /// Get the "disabled" value of "id" token.
     fn is_disabled(env: Env, id: i128) -> bool;
 
+    /// Freeze token "id" and its descendants in the split tree, e.g. while the invoice is disputed. "caller" must hold the Compliance role.
+    /// A frozen token cannot be transferred, split, merged, signed off, rejected, cancelled or redeemed. "reason_hash" is the hash of an off-chain record of the reason.
+    /// Emit event with topics = ["freeze", caller: Address], data = [id: i128, reason_hash: BytesN<32>]
+    fn freeze(env: Env, caller: Address, id: i128, reason_hash: BytesN<32>);
+
+    /// Lift the freeze set on token "id". "caller" must hold the Compliance role. Will fail if "id" itself is not frozen.
+    /// Emit event with topics = ["unfreeze", caller: Address], data = [id: i128]
+    fn unfreeze(env: Env, caller: Address, id: i128);
+
+    /// Check whether token "id" is frozen, either directly or through one of its ancestors.
+    fn is_frozen(env: Env, id: i128) -> bool;
+
     /// Transfer token "id" from "from" to "to.
     /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: i128]
     fn transfer(env: Env, from: Address, to: Address, id: i128);
 
+    /// Allow "spender" to transfer token "id" on behalf of "owner" until "expiration_ledger". Replaces any previous approval for "id".
+    /// An "expiration_ledger" lower than the current ledger sequence removes the approval. Approvals are cleared when the token changes owner.
+    /// Emit event with topics = ["approve", owner: Address, spender: Address], data = [id: i128, expiration_ledger: u32]
+    fn approve(env: Env, owner: Address, spender: Address, id: i128, expiration_ledger: u32);
+
+    /// Allow or disallow "operator" to transfer any token of "owner".
+    /// Emit event with topics = ["appr_all", owner: Address, operator: Address], data = [approved: bool]
+    fn set_approval_for_all(env: Env, owner: Address, operator: Address, approved: bool);
+
+    /// Get the address approved to transfer token "id", if any.
+    fn get_approved(env: Env, id: i128) -> Option<Address>;
+
+    /// Check whether "operator" may transfer any token of "owner".
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool;
+
+    /// Transfer token "id" from "from" to "to" on behalf of "from". "spender" must be approved for "id" or be an operator of "from".
+    /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: i128]
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, id: i128);
+
     /// Mint the root-level TC. Will fail if the root-level TC already exists.
     /// The minted TC has a value corresponding to the "total_amount" specified in the initialize() function.
+    /// "caller" must hold the Minter role.
     /// Emit event with topics = ["mint", to: Address], data = [id: i128]
-    fn mint_original(env: Env, to: Address, vc: String);
+    fn mint_original(env: Env, caller: Address, to: Address, vc: String);
 
-    /// Split a token into a number of sub-tokens based on the amounts listed. Will fail if the sum of amounts is greater than the original.
+    /// Split a token into a number of sub-tokens based on the amounts listed. Will fail if the sum of amounts is greater than the original,
+    /// or if the split breaks the rules returned by split_policy().
     /// Emit event with topics = ["split", from: Address], data = [id: i128, new_ids: Vec<i128>]
     fn split(env: Env, id: i128, splits: Vec<SplitRequest>) -> Vec<i128>;
 
-    /// Burn a specified TC and transfer funds to the owner.
-    /// Emit event with topics = ["burn", owner: Address], data = [id: i128]
+    /// Undo the split of token "id" by burning all of its children and enabling "id" again.
+    /// All children must be owned by the same address, which becomes the owner of "id". Unclaimed children count as owned by the owner of "id".
+    /// Will fail if any of the children has been split itself, or after "end_time".
+    /// Emit event with topics = ["merge", owner: Address], data = [id: i128, merged_ids: Vec<i128>]
+    fn merge(env: Env, id: i128);
+
+    /// Get the ids of the tokens created by splitting "id".
+    fn children(env: Env, id: i128) -> Vec<i128>;
+
+    /// Transfer the TC's pro-rata share of the funds paid by the buyer so far to the owner.
+    /// The share is "amount" / "total_amount" of the paid amount, minus what the TC has already redeemed.
+    /// If the order has not been fully paid or settled, the TC is kept so that it can be redeemed again after further payments.
+    /// Emit event with topics = ["claim", owner: Address], data = [id: i128, amount: i128]
+    /// Once the order is fully paid or settled, the TC is burned instead, and any unpaid part of its value is recorded as its shortfall.
+    /// Emit event with topics = ["shortfall", owner: Address], data = [id: i128, amount: i128]
+    /// Emit event with topics = ["redeem", owner: Address], data = [id: i128]
     fn redeem(env: Env, id: i128);
 
-    /// If "admin" is the administrator or the token owner, burn token "id" from "from".
+    /// Burn token "id" from its owner. "caller" must hold the Minter role.
     /// Emit event with topics = ["burn", from: Address], data = [id: i128]
-    fn burn(env: Env, id: i128);
+    fn burn(env: Env, caller: Address, id: i128);
 
     /// checks whether the payoff step was completed
     fn check_paid(env: Env) -> bool;
@@ -63,8 +164,19 @@ pub trait TokenizedCertificateTrait {
     /// use env timestamp and check against stored expiry time
     fn check_expired(env: Env) -> bool;
 
-    /// set the contract address for the external token (e.g. USDC)
-    fn set_external_token_provider(env: Env, contract_addr: Address, decimals: u32);
+    /// returns the Unix timestamp at which the order matures and the TCs expire
+    fn end_time(env: Env) -> u64;
+
+    /// returns the address and decimals of the external token the TCs are redeemed for
+    fn external_token(env: Env) -> (Address, u32);
+
+    /// set the contract address for the external token (e.g. USDC). "caller" must hold the Treasury role.
+    fn set_external_token_provider(
+        env: Env,
+        caller: Address,
+        contract_addr: Address,
+        decimals: u32,
+    );
 
     /// retrieves a pending split request for a given token "id"
     fn recipient(env: Env, id: i128) -> Address;
@@ -74,11 +186,61 @@ pub trait TokenizedCertificateTrait {
     /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: i128]
     fn sign_off(env: Env, id: i128);
 
-    /// pay off OrderInfo.amount using token
-    fn pay_off(env: Env, from: Address);
+    /// reject the TC according to SplitRequest for "id". Must be called by the recipient.
+    /// transfers the TC from the smart contract to the owner of its parent
+    /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: i128]
+    /// Emit event with topics = ["reject", recipient: Address], data = [id: i128]
+    fn reject(env: Env, id: i128);
+
+    /// cancel the SplitRequest for "id" before it is signed off. Must be called by the owner of the parent TC.
+    /// transfers the TC from the smart contract to the owner of its parent
+    /// Emit event with topics = ["transfer", from: Address, to: Address], data = [id: i128]
+    /// Emit event with topics = ["cancel", from: Address], data = [id: i128]
+    fn cancel_split_request(env: Env, id: i128);
+
+    /// Get the ids of all TCs waiting for "address" to sign off. Empty after "end_time".
+    fn pending_sign_offs(env: Env, address: Address) -> Vec<i128>;
+
+    /// pay "amount" of the external token towards OrderInfo.amount. Payments can be split into any number of tranches.
+    /// The order is marked as paid once the cumulative paid amount reaches OrderInfo.amount.
+    /// Emit event with topics = ["pay_off", from: Address], data = [amount: i128]
+    fn pay_off(env: Env, from: Address, amount: i128);
+
+    /// Declare the order settled at the amount actually paid by the buyer. Can only be called by a holder of the Treasury role, once the grace period after "end_time" has passed.
+    /// After settlement, no further payments are accepted and each TC can be redeemed for its pro-rata share of the paid amount.
+    /// Emit event with topics = ["settle", caller: Address], data = [paid_amount: i128]
+    fn settle(env: Env, caller: Address);
+
+    /// Set the time in seconds after "end_time" before an unpaid order can be settled. "caller" must hold the Risk role.
+    fn set_grace_period(env: Env, caller: Address, grace_period: u64);
+
+    /// Get the time in seconds after "end_time" before an unpaid order can be settled.
+    fn grace_period(env: Env) -> u64;
+
+    /// Set the rules enforced by split(). "caller" must hold the Risk role. Can only be called before any TC has been split.
+    fn set_split_policy(env: Env, caller: Address, split_policy: SplitPolicy);
+
+    /// Get the rules enforced by split().
+    fn split_policy(env: Env) -> SplitPolicy;
+
+    /// checks whether the order was settled by the admin
+    fn check_settled(env: Env) -> bool;
+
+    /// Get the value of "id" in external tokens that was not covered by the buyer's payments once the order is settled.
+    fn shortfall(env: Env, id: i128) -> i128;
+
+    /// Get the cumulative amount of external tokens paid by the buyer.
+    fn paid_amount(env: Env) -> i128;
+
+    /// Get the installment schedule set during initialize. Empty if there is no schedule.
+    fn installments(env: Env) -> Vec<Installment>;
+
+    /// Get the amount of external tokens that is due at the current ledger time but has not been paid yet.
+    /// Without an installment schedule, the whole amount becomes due at "end_time".
+    fn amount_due(env: Env) -> i128;
 
-    /// Update the VC associated with a token. Can only be called by the admin.
-    fn add_vc(env: Env, id: i128, vc: String);
+    /// Update the VC associated with a token. "caller" must hold the Compliance role.
+    fn add_vc(env: Env, caller: Address, id: i128, vc: String);
 
     // --------------------------------------------------------------------------------
     // Implementation Interface
@@ -89,5 +251,15 @@ pub trait TokenizedCertificateTrait {
     /// "buyer_address" specifies the account that will perform the pay-off step later.
     /// "total_amount" corresponds to the USD value of the invoice.
     /// "end_time" is a Unix timestamp. It specifies the maturity date of the invoice, after which the tokenized certificates can be redeemed for USDC or other tokens.
-    fn initialize(e: Env, admin: Address, buyer_address: Address, total_amount: u32, end_time: u64);
+    /// "installments" is an optional payment schedule. If not empty, the installment amounts must add up to "total_amount" and the due times must be increasing and no later than "end_time".
+    /// "split_policy" sets the rules enforced by split(). If not given, splits are limited to a depth of 5 and 10 children, each at least 10% of "total_amount".
+    fn initialize(
+        e: Env,
+        admin: Address,
+        buyer_address: Address,
+        total_amount: u32,
+        end_time: u64,
+        installments: Vec<Installment>,
+        split_policy: Option<SplitPolicy>,
+    );
 }
@@ -1,6 +1,7 @@
 #![no_std]
 
 mod admin;
+mod approval;
 mod balance;
 mod contract;
 mod errors;
@@ -10,9 +11,12 @@ mod metadata;
 mod order_info;
 mod order_state;
 mod owner;
+mod pause;
+mod roles;
 mod storage_types;
 mod sub_tc;
 mod test;
 mod test_util;
+mod upgrade;
 
 pub use crate::contract::TokenizedCertificate;
@@ -1,5 +1,10 @@
-use crate::{errors::Error, storage_types::DataKey};
-use soroban_sdk::{contracttype, panic_with_error, Address, Env};
+use crate::{
+    errors::Error,
+    storage_types::{
+        DataKey, Installment, SplitPolicy, DEFAULT_GRACE_PERIOD, DEFAULT_SPLIT_POLICY,
+    },
+};
+use soroban_sdk::{contracttype, panic_with_error, Address, Env, Vec};
 
 #[contracttype]
 #[derive(Clone)]
@@ -30,3 +35,80 @@ pub fn read_order_info(env: &Env) -> TokenOrderInfo {
         None => panic_with_error!(env, Error::NotFound),
     }
 }
+
+pub fn write_installments(env: &Env, installments: &Vec<Installment>) {
+    let key = DataKey::Installments;
+    env.storage().instance().set(&key, installments);
+}
+
+pub fn read_installments(env: &Env) -> Vec<Installment> {
+    let key = DataKey::Installments;
+    match env
+        .storage()
+        .instance()
+        .get::<DataKey, Vec<Installment>>(&key)
+    {
+        Some(data) => data,
+        None => Vec::new(env),
+    }
+}
+
+pub fn write_grace_period(env: &Env, grace_period: u64) {
+    let key = DataKey::GracePeriod;
+    env.storage().instance().set(&key, &grace_period);
+}
+
+pub fn read_grace_period(env: &Env) -> u64 {
+    let key = DataKey::GracePeriod;
+    match env.storage().instance().get::<DataKey, u64>(&key) {
+        Some(data) => data,
+        None => DEFAULT_GRACE_PERIOD,
+    }
+}
+
+pub fn write_split_policy(env: &Env, split_policy: &SplitPolicy) {
+    let key = DataKey::SplitPolicy;
+    env.storage().instance().set(&key, split_policy);
+}
+
+pub fn read_split_policy(env: &Env) -> SplitPolicy {
+    let key = DataKey::SplitPolicy;
+    match env.storage().instance().get::<DataKey, SplitPolicy>(&key) {
+        Some(data) => data,
+        None => DEFAULT_SPLIT_POLICY,
+    }
+}
+
+// a split creates at least one child, and the minimum split cannot exceed the root total
+pub fn check_split_policy(env: &Env, split_policy: &SplitPolicy) {
+    if split_policy.max_children == 0 || split_policy.min_split_bps > 10000 {
+        panic_with_error!(env, Error::InvalidArgs);
+    }
+}
+
+// an installment schedule must cover exactly the total amount, with strictly increasing due times no later than end_time
+pub fn check_installments(
+    env: &Env,
+    installments: &Vec<Installment>,
+    total_amount: u32,
+    end_time: u64,
+) {
+    if installments.is_empty() {
+        return;
+    }
+    let mut sum: u64 = 0;
+    let mut last_due_time = 0;
+    for installment in installments.iter() {
+        if installment.amount == 0
+            || installment.due_time <= last_due_time
+            || installment.due_time > end_time
+        {
+            panic_with_error!(env, Error::InvalidArgs);
+        }
+        sum += u64::from(installment.amount);
+        last_due_time = installment.due_time;
+    }
+    if sum != u64::from(total_amount) {
+        panic_with_error!(env, Error::InvalidArgs);
+    }
+}
@@ -1,11 +1,10 @@
-use crate::balance::read_supply;
 use crate::event;
 
 use crate::order_info::read_order_info;
 use crate::owner::{read_owner, write_owner};
 use crate::storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
 use crate::sub_tc::read_sub_tc;
-use soroban_sdk::Env;
+use soroban_sdk::{Address, Env};
 
 pub fn update_and_read_expired(env: &Env) -> bool {
     let expired_cached = read_expired(&env);
@@ -16,24 +15,28 @@ pub fn update_and_read_expired(env: &Env) -> bool {
     let expired = ledger.timestamp() >= read_order_info(&env).end_time;
     if expired {
         write_expired(&env, true);
-        // transfer unclaimed TCs to the root TC's owner address
-        let last_id = read_supply(&env);
-        if last_id > 0 {
-            let contract_addr = &env.current_contract_address();
-            for i in 1..last_id {
-                let parent_id = read_sub_tc(&env, i).parent;
-                let to = read_owner(&env, parent_id);
-                let owner = read_owner(&env, i);
-                if owner == contract_addr.clone() {
-                    write_owner(&env, i, Some(to.clone()));
-                    event::transfer(&env, contract_addr.clone(), to.clone(), i);
-                }
-            }
-        }
     }
     expired
 }
 
+// Returns the owner of "id". If the order has expired and "id" is an unclaimed split TC,
+// it is transferred to the owner of its parent first.
+pub fn update_and_read_owner(env: &Env, id: i128) -> Address {
+    let owner = read_owner(env, id);
+    let contract_addr = env.current_contract_address();
+    if owner != contract_addr || !update_and_read_expired(env) {
+        return owner;
+    }
+    let parent_id = read_sub_tc(env, id).parent;
+    if parent_id == id {
+        return owner;
+    }
+    let to = update_and_read_owner(env, parent_id);
+    write_owner(env, id, Some(to.clone()));
+    event::transfer(env, contract_addr, to.clone(), id);
+    to
+}
+
 fn read_expired(env: &Env) -> bool {
     let key = DataKey::Expired;
     match env.storage().persistent().get::<DataKey, bool>(&key) {
@@ -79,3 +82,95 @@ pub fn write_paid(env: &Env, val: bool) {
         .persistent()
         .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
 }
+
+pub fn read_paid_amount(env: &Env) -> i128 {
+    let key = DataKey::PaidAmount;
+    match env.storage().persistent().get::<DataKey, i128>(&key) {
+        Some(data) => {
+            env.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            data
+        }
+        None => 0,
+    }
+}
+
+pub fn write_paid_amount(env: &Env, amount: i128) {
+    let key = DataKey::PaidAmount;
+    env.storage().persistent().set(&key, &amount);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn read_redeemed(env: &Env, id: i128) -> i128 {
+    let key = DataKey::Redeemed(id);
+    match env.storage().persistent().get::<DataKey, i128>(&key) {
+        Some(data) => {
+            env.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            data
+        }
+        None => 0,
+    }
+}
+
+pub fn write_redeemed(env: &Env, id: i128, amount: i128) {
+    let key = DataKey::Redeemed(id);
+    env.storage().persistent().set(&key, &amount);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn read_settled(env: &Env) -> bool {
+    let key = DataKey::Settled;
+    match env.storage().persistent().get::<DataKey, bool>(&key) {
+        Some(data) => {
+            env.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            data
+        }
+        None => false,
+    }
+}
+
+pub fn write_settled(env: &Env, val: bool) {
+    let key = DataKey::Settled;
+    env.storage().persistent().set(&key, &val);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn read_shortfall(env: &Env, id: i128) -> i128 {
+    let key = DataKey::Shortfall(id);
+    match env.storage().persistent().get::<DataKey, i128>(&key) {
+        Some(data) => {
+            env.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            data
+        }
+        None => 0,
+    }
+}
+
+pub fn write_shortfall(env: &Env, id: i128, amount: i128) {
+    let key = DataKey::Shortfall(id);
+    env.storage().persistent().set(&key, &amount);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
@@ -1,13 +1,19 @@
-use crate::balance::read_supply;
+use crate::approval::remove_approval;
 use crate::errors::Error;
+use crate::order_state::{update_and_read_expired, update_and_read_owner};
 use crate::storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
-use crate::sub_tc::read_sub_tc_disabled;
+use crate::sub_tc::{read_children, read_sub_tc_disabled};
 use soroban_sdk::{panic_with_error, vec, Address, Env, String, Vec};
 
 pub fn read_owner(env: &Env, id: i128) -> Address {
     let key = DataKey::Owner(id);
-    match env.storage().persistent().get::<DataKey, Address>(&key) {
-        Some(balance) => {
+    // burned tokens are stored with an empty owner
+    match env
+        .storage()
+        .persistent()
+        .get::<DataKey, Option<Address>>(&key)
+    {
+        Some(Some(balance)) => {
             env.storage().persistent().extend_ttl(
                 &key,
                 BALANCE_LIFETIME_THRESHOLD,
@@ -15,12 +21,35 @@ pub fn read_owner(env: &Env, id: i128) -> Address {
             );
             balance
         }
-        None => panic_with_error!(env, Error::NotFound),
+        _ => panic_with_error!(env, Error::NotFound),
     }
 }
 
 pub fn write_owner(env: &Env, id: i128, owner: Option<Address>) {
     let key = DataKey::Owner(id);
+    // keep the owned ids index of the previous and new owner up to date
+    if let Some(Some(prev_owner)) = env
+        .storage()
+        .persistent()
+        .get::<DataKey, Option<Address>>(&key)
+    {
+        remove_id(env, &DataKey::Owned(prev_owner.clone()), id);
+        // a split TC leaving the contract address is no longer waiting for sign off
+        if prev_owner == env.current_contract_address() {
+            if let Some(recipient) = env
+                .storage()
+                .persistent()
+                .get::<DataKey, Address>(&DataKey::Recipient(id))
+            {
+                remove_id(env, &DataKey::Pending(recipient), id);
+            }
+        }
+    }
+    if let Some(new_owner) = &owner {
+        add_id(env, &DataKey::Owned(new_owner.clone()), id);
+    }
+    // approvals do not carry over to the next owner
+    remove_approval(env, id);
     env.storage().persistent().set(&key, &owner);
     env.storage()
         .persistent()
@@ -50,6 +79,7 @@ pub fn read_recipient(env: &Env, id: i128) -> Address {
 
 pub fn write_recipient(env: &Env, id: i128, recipient: &Address) {
     let key = DataKey::Recipient(id);
+    add_id(env, &DataKey::Pending(recipient.clone()), id);
     env.storage().persistent().set(&key, recipient);
     env.storage()
         .persistent()
@@ -57,19 +87,94 @@ pub fn write_recipient(env: &Env, id: i128, recipient: &Address) {
 }
 
 pub fn read_all_owned(env: &Env, address: Address) -> Vec<i128> {
-    let mut ids = Vec::new(&env);
-    let supply = read_supply(&env);
-    if supply > 0 {
-        for n in 0..supply {
-            let owner = read_owner(&env, n);
-            if owner == address && !read_sub_tc_disabled(&env, n) {
-                ids.push_back(n);
+    let mut owned = read_owned(env, &address);
+    if update_and_read_expired(env) {
+        // unclaimed children of the address's split TCs now belong to the address
+        for id in owned.iter() {
+            if read_sub_tc_disabled(env, id) {
+                for child in read_children(env, id).iter() {
+                    update_and_read_owner(env, child);
+                }
             }
         }
+        owned = read_owned(env, &address);
+    }
+
+    let mut ids = Vec::new(env);
+    for id in owned.iter() {
+        if !read_sub_tc_disabled(env, id) {
+            ids.push_back(id);
+        }
     }
     ids
 }
 
+pub fn read_owned(env: &Env, address: &Address) -> Vec<i128> {
+    read_ids(env, &DataKey::Owned(address.clone()))
+}
+
+pub fn read_pending(env: &Env, recipient: &Address) -> Vec<i128> {
+    read_ids(env, &DataKey::Pending(recipient.clone()))
+}
+
+pub fn add_owned(env: &Env, address: &Address, id: i128) {
+    add_id(env, &DataKey::Owned(address.clone()), id);
+}
+
+pub fn add_pending(env: &Env, recipient: &Address, id: i128) {
+    add_id(env, &DataKey::Pending(recipient.clone()), id);
+}
+
+fn read_ids(env: &Env, key: &DataKey) -> Vec<i128> {
+    match env.storage().persistent().get::<DataKey, Vec<i128>>(key) {
+        Some(data) => {
+            env.storage().persistent().extend_ttl(
+                key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            data
+        }
+        None => Vec::new(env),
+    }
+}
+
+fn write_ids(env: &Env, key: &DataKey, ids: &Vec<i128>) {
+    if ids.is_empty() {
+        env.storage().persistent().remove(key);
+        return;
+    }
+    env.storage().persistent().set(key, ids);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+// ids are kept in ascending order
+fn add_id(env: &Env, key: &DataKey, id: i128) {
+    let mut ids = read_ids(env, key);
+    let mut index = ids.len();
+    for (i, existing_id) in ids.iter().enumerate() {
+        if existing_id == id {
+            return;
+        }
+        if existing_id > id {
+            index = i as u32;
+            break;
+        }
+    }
+    ids.insert(index, id);
+    write_ids(env, key, &ids);
+}
+
+fn remove_id(env: &Env, key: &DataKey, id: i128) {
+    let mut ids = read_ids(env, key);
+    if let Some(index) = ids.first_index_of(id) {
+        ids.remove(index);
+        write_ids(env, key, &ids);
+    }
+}
+
 pub fn write_vc(env: &Env, id: i128, vc: Vec<String>) {
     let key = DataKey::VC(id);
     env.storage().persistent().set(&key, &vc);
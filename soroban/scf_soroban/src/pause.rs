@@ -0,0 +1,37 @@
+use crate::errors::Error;
+use crate::storage_types::DataKey;
+use soroban_sdk::{panic_with_error, Env};
+
+pub fn is_paused(env: &Env) -> bool {
+    let key = DataKey::Paused;
+    env.storage().instance().get(&key).unwrap_or(false)
+}
+
+pub fn write_paused(env: &Env, paused: bool) {
+    let key = DataKey::Paused;
+    env.storage().instance().set(&key, &paused);
+}
+
+// exits stay open during a pause unless the guardian closes them
+pub fn read_exits_allowed(env: &Env) -> bool {
+    let key = DataKey::ExitsAllowed;
+    env.storage().instance().get(&key).unwrap_or(true)
+}
+
+pub fn write_exits_allowed(env: &Env, allowed: bool) {
+    let key = DataKey::ExitsAllowed;
+    env.storage().instance().set(&key, &allowed);
+}
+
+pub fn check_not_paused(env: &Env) {
+    if is_paused(env) {
+        panic_with_error!(env, Error::Paused);
+    }
+}
+
+// for entry points that let users withdraw their funds
+pub fn check_exit_allowed(env: &Env) {
+    if is_paused(env) && !read_exits_allowed(env) {
+        panic_with_error!(env, Error::Paused);
+    }
+}
@@ -7,6 +7,31 @@ pub(crate) const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_I
 pub(crate) const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
 pub(crate) const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
+pub(crate) const ROLE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub(crate) const ROLE_LIFETIME_THRESHOLD: u32 = ROLE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// version of the storage layout, to be bumped along with a migration step whenever the layout changes
+pub(crate) const STORAGE_VERSION: u32 = 1;
+
+// default time after end_time before an unpaid order can be settled, in seconds
+pub(crate) const DEFAULT_GRACE_PERIOD: u64 = 30 * 86400;
+
+// split rules used when none are given at initialization: depth 5, 10% minimum, up to 10 children
+pub(crate) const DEFAULT_SPLIT_POLICY: SplitPolicy = SplitPolicy {
+    max_depth: 5,
+    min_split_bps: 1000,
+    max_children: 10,
+    allow_remainder: true,
+};
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub address: Address,
+    // Unix timestamp after which the proposal can no longer be accepted
+    pub expiration_time: Option<u64>,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -21,6 +46,42 @@ pub enum DataKey {
     Paid,
     ExternalToken,
     Recipient(i128),
+    Installments,
+    PaidAmount,
+    Redeemed(i128),
+    GracePeriod,
+    Settled,
+    Shortfall(i128),
+    Owned(Address),
+    Children(i128),
+    Pending(Address),
+    Approval(i128),
+    Operator(Address, Address),
+    SplitPolicy,
+    Version,
+    MigrationCursor,
+    PendingAdmin,
+    Role(Role, Address),
+    Paused,
+    ExitsAllowed,
+    Frozen(i128),
+}
+
+// privileged duties that the admin can hand out to separate accounts
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    // mints and burns TCs
+    Minter = 0,
+    // freezes TCs and attests VCs
+    Compliance = 1,
+    // sets the external token and settles underpaid orders
+    Treasury = 2,
+    // sets the grace period and the split policy
+    Risk = 3,
+    // pauses the contract and controls exits
+    Guardian = 4,
 }
 
 #[derive(Clone)]
@@ -38,9 +99,36 @@ pub struct SplitRequest {
     pub to: Address,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitPolicy {
+    // maximum depth of a split TC, the root-level TC having depth 0
+    pub max_depth: u32,
+    // minimum amount of each split, in basis points of the root-level total_amount
+    pub min_split_bps: u32,
+    // maximum number of TCs created by a single split, including the remainder
+    pub max_children: u32,
+    // whether a split may leave a remainder TC for the original owner
+    pub allow_remainder: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ApprovalData {
+    pub spender: Address,
+    pub expiration_ledger: u32,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ExternalToken {
     pub contract_addr: Address,
     pub decimals: u32,
 }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Installment {
+    pub due_time: u64,
+    pub amount: u32,
+}
@@ -1,6 +1,6 @@
 use crate::errors::Error;
 use crate::storage_types::{DataKey, SubTC, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
-use soroban_sdk::{panic_with_error, Env};
+use soroban_sdk::{panic_with_error, BytesN, Env, Vec};
 
 pub fn read_sub_tc(env: &Env, id: i128) -> SubTC {
     let key = DataKey::SubTCInfo(id);
@@ -59,3 +59,73 @@ pub fn write_sub_tc_disabled(env: &Env, id: i128, disabled: bool) {
         .persistent()
         .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
 }
+
+pub fn read_children(env: &Env, id: i128) -> Vec<i128> {
+    let key = DataKey::Children(id);
+    match env.storage().persistent().get::<DataKey, Vec<i128>>(&key) {
+        Some(data) => {
+            env.storage().persistent().extend_ttl(
+                &key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            data
+        }
+        None => Vec::new(env),
+    }
+}
+
+pub fn write_children(env: &Env, id: i128, children: &Vec<i128>) {
+    let key = DataKey::Children(id);
+    env.storage().persistent().set(&key, children);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn read_frozen(env: &Env, id: i128) -> Option<BytesN<32>> {
+    let key = DataKey::Frozen(id);
+    let reason_hash = env.storage().persistent().get::<DataKey, BytesN<32>>(&key);
+    if reason_hash.is_some() {
+        env.storage().persistent().extend_ttl(
+            &key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+    reason_hash
+}
+
+pub fn write_frozen(env: &Env, id: i128, reason_hash: &BytesN<32>) {
+    let key = DataKey::Frozen(id);
+    env.storage().persistent().set(&key, reason_hash);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn remove_frozen(env: &Env, id: i128) {
+    let key = DataKey::Frozen(id);
+    env.storage().persistent().remove(&key);
+}
+
+// a TC is frozen if it or any of its ancestors in the split tree has been frozen
+pub fn is_frozen(env: &Env, id: i128) -> bool {
+    let mut id = id;
+    loop {
+        if read_frozen(env, id).is_some() {
+            return true;
+        }
+        let sub_tc = read_sub_tc(env, id);
+        if sub_tc.depth == 0 {
+            return false;
+        }
+        id = sub_tc.parent;
+    }
+}
+
+pub fn check_not_frozen(env: &Env, id: i128) {
+    if is_frozen(env, id) {
+        panic_with_error!(env, Error::Frozen);
+    }
+}
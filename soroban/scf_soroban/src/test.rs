@@ -2,11 +2,14 @@
 use crate::contract::{TokenizedCertificate, TokenizedCertificateClient};
 
 use crate::errors::Error as ContractError;
-use crate::storage_types::SplitRequest;
-use crate::test_util::{set_ledger_timestamp, setup_test_token};
+use crate::roles::ALL_ROLES;
+use crate::storage_types::{DataKey, Installment, Role, SplitPolicy, SplitRequest};
+use crate::test_util::{
+    set_ledger_sequence, set_ledger_timestamp, setup_test_token, tc_contract, upload_rebuilt_wasm,
+};
 use soroban_sdk::{
-    testutils::Address as _, token::Client as TokenClient, token::StellarAssetClient, vec, Address,
-    Env, Error, String,
+    symbol_short, testutils::Address as _, testutils::Events, token::Client as TokenClient,
+    token::StellarAssetClient, vec, Address, BytesN, Env, Error, IntoVal, String,
 };
 
 #[test]
@@ -20,7 +23,123 @@ fn test_initialize() {
     let total_amount: u32 = 1000000;
     let end_time = 1672531200; // 2023-01-01 00:00:00 UTC+0
 
-    client.initialize(&admin, &buyer, &total_amount, &end_time);
+    client.initialize(&admin, &buyer, &total_amount, &end_time, &vec![&env], &None);
+    assert_eq!(admin, client.admin());
+}
+
+#[test]
+fn test_admin_handover() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // accepting without a proposal fails
+    let res = client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+
+    // the admin does not change until the proposal is accepted
+    let new_admin = Address::generate(&env);
+    client.propose_admin(&new_admin, &None);
+    match env.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("propose"), admin.clone()).into_val(&env)
+            );
+            let data: (Address, Option<u64>) = data.into_val(&env);
+            assert_eq!(data, (new_admin.clone(), None));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(admin, client.admin());
+    assert_eq!(Some(new_admin.clone()), client.pending_admin());
+
+    client.accept_admin();
+    assert_eq!(
+        env.auths()[0].0,
+        new_admin.clone(),
+        "the proposed admin must authorize the handover"
+    );
+    match env.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("set_admin"), admin.clone()).into_val(&env)
+            );
+            let new_admin_decoded: Address = data.into_val(&env);
+            assert_eq!(new_admin, new_admin_decoded);
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(new_admin, client.admin());
+    assert_eq!(None, client.pending_admin());
+
+    // the roles of the old admin move to the new admin
+    for role in ALL_ROLES {
+        assert!(!client.has_role(&role, &admin));
+        assert!(client.has_role(&role, &new_admin));
+    }
+}
+
+#[test]
+fn test_admin_proposal_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_timestamp(&env, 1640995200); // 2022-01-01 00:00:00 UTC+0
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+    let new_admin = Address::generate(&env);
+
+    // the expiration time must be in the future
+    let res = client.try_propose_admin(&new_admin, &Some(1640995200));
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    client.propose_admin(&new_admin, &Some(1641081600));
+    set_ledger_timestamp(&env, 1641081601);
+    assert_eq!(None, client.pending_admin());
+    let res = client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // a cancelled proposal cannot be accepted
+    client.propose_admin(&new_admin, &None);
+    client.cancel_admin_proposal();
+    match env.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("unpropose"), admin.clone()).into_val(&env)
+            );
+            let data: Address = data.into_val(&env);
+            assert_eq!(data, new_admin.clone());
+        }
+        None => panic!("The event is not published"),
+    }
+    assert_eq!(None, client.pending_admin());
+    let res = client.try_accept_admin();
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
     assert_eq!(admin, client.admin());
 }
 
@@ -37,7 +156,7 @@ fn test_initialize_invalid_end_time() {
     set_ledger_timestamp(&env, timestamp);
 
     let end_time = timestamp - 86400;
-    let res = client.try_initialize(&admin, &buyer, &total_amount, &end_time);
+    let res = client.try_initialize(&admin, &buyer, &total_amount, &end_time, &vec![&env], &None);
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
@@ -46,6 +165,189 @@ fn test_initialize_invalid_end_time() {
     );
 }
 
+#[test]
+fn test_initialize_installments() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, TokenizedCertificate);
+    let client = TokenizedCertificateClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let total_amount: u32 = 1000000;
+    let end_time = 1672531200; // 2023-01-01 00:00:00 UTC+0
+    let installments = vec![
+        &env,
+        Installment {
+            due_time: 1667260800, // 2022-11-01 00:00:00 UTC+0
+            amount: 400000,
+        },
+        Installment {
+            due_time: end_time,
+            amount: 600000,
+        },
+    ];
+
+    client.initialize(
+        &admin,
+        &buyer,
+        &total_amount,
+        &end_time,
+        &installments,
+        &None,
+    );
+    assert_eq!(installments, client.installments());
+}
+
+#[test]
+fn test_initialize_invalid_installments() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, TokenizedCertificate);
+    let client = TokenizedCertificateClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let total_amount: u32 = 1000000;
+    let end_time = 1672531200; // 2023-01-01 00:00:00 UTC+0
+
+    // installment amounts do not add up to the total amount
+    let res = client.try_initialize(
+        &admin,
+        &buyer,
+        &total_amount,
+        &end_time,
+        &vec![
+            &env,
+            Installment {
+                due_time: end_time,
+                amount: 999999,
+            },
+        ],
+        &None,
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidArgs as u32
+        )))
+    );
+
+    // due times are not in increasing order
+    let res = client.try_initialize(
+        &admin,
+        &buyer,
+        &total_amount,
+        &end_time,
+        &vec![
+            &env,
+            Installment {
+                due_time: end_time,
+                amount: 500000,
+            },
+            Installment {
+                due_time: end_time - 86400,
+                amount: 500000,
+            },
+        ],
+        &None,
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidArgs as u32
+        )))
+    );
+
+    // installment is due after the maturity date
+    let res = client.try_initialize(
+        &admin,
+        &buyer,
+        &total_amount,
+        &end_time,
+        &vec![
+            &env,
+            Installment {
+                due_time: end_time + 1,
+                amount: 1000000,
+            },
+        ],
+        &None,
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidArgs as u32
+        )))
+    );
+}
+
+#[test]
+fn test_roles() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // the admin holds every role after initialization
+    assert!(client.has_role(&Role::Minter, &admin));
+    assert!(client.has_role(&Role::Compliance, &admin));
+    assert!(client.has_role(&Role::Treasury, &admin));
+    assert!(client.has_role(&Role::Risk, &admin));
+
+    let minter = Address::generate(&env);
+    let compliance = Address::generate(&env);
+    client.grant_role(&Role::Minter, &minter);
+    match env.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("grant"), Role::Minter, minter.clone()).into_val(&env)
+            );
+            let admin_decoded: Address = data.into_val(&env);
+            assert_eq!(admin, admin_decoded);
+        }
+        None => panic!("The event is not published"),
+    }
+    client.grant_role(&Role::Compliance, &compliance);
+    assert!(client.has_role(&Role::Minter, &minter));
+    assert!(!client.has_role(&Role::Compliance, &minter));
+
+    // each privileged function requires its own role
+    let res = client.try_mint_original(&compliance, &buyer, &String::from_str(&env, "a"));
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.mint_original(&minter, &buyer, &String::from_str(&env, "a"));
+    assert_eq!(
+        env.auths()[0].0,
+        minter.clone(),
+        "the minter must authorize the mint"
+    );
+    let res = client.try_add_vc(&minter, &0, &String::from_str(&env, "b"));
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.add_vc(&compliance, &0, &String::from_str(&env, "b"));
+
+    // the admin can give up a role
+    client.revoke_role(&Role::Minter, &admin);
+    assert!(!client.has_role(&Role::Minter, &admin));
+    let res = client.try_burn(&admin, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.burn(&minter, &0);
+}
+
 #[test]
 fn test_mint_original() {
     let env = Env::default();
@@ -55,7 +357,7 @@ fn test_mint_original() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(to, client.owner(&0));
     assert_eq!(1000000, client.amount(&0));
     assert_eq!(0, client.parent(&0));
@@ -72,10 +374,10 @@ fn test_mint_original_twice() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(to, client.owner(&0));
 
-    let res = client.try_mint_original(&to, &String::from_str(&env, "a"));
+    let res = client.try_mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
@@ -93,11 +395,11 @@ fn test_add_vc() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(client.vc(&0), vec![&env, String::from_str(&env, "a")]);
 
     // add vc successfully
-    client.add_vc(&0, &String::from_str(&env, "b"));
+    client.add_vc(&admin, &0, &String::from_str(&env, "b"));
     assert_eq!(
         client.vc(&0),
         vec![
@@ -110,7 +412,7 @@ fn test_add_vc() {
     // attempt to add vc with string exceeding length limit of 2048: call should fail
     let bytes_data: [u8; 2049] = [b'a'; 2049];
     let long_vc = String::from_bytes(&env, &bytes_data);
-    let res = client.try_add_vc(&0, &long_vc);
+    let res = client.try_add_vc(&admin, &0, &long_vc);
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
@@ -120,12 +422,12 @@ fn test_add_vc() {
 
     // add 8 more vcs, resulting in a total of 10 vcs
     for _ in 0..8 {
-        client.add_vc(&0, &String::from_str(&env, "i"));
+        client.add_vc(&admin, &0, &String::from_str(&env, "i"));
     }
     assert_eq!(client.vc(&0).len(), 10);
 
     // attempt to add vc when vc limit is reached: call should fail
-    let res = client.try_add_vc(&0, &String::from_str(&env, "n"));
+    let res = client.try_add_vc(&admin, &0, &String::from_str(&env, "n"));
     assert_eq!(
         res,
         Err(Ok(Error::from_contract_error(
@@ -143,7 +445,7 @@ fn test_split() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(1000000, client.amount(&0));
 
     client.split(
@@ -161,9 +463,9 @@ fn test_split() {
         ],
     );
 
-    client.add_vc(&1, &String::from_str(&env, "b"));
-    client.add_vc(&3, &String::from_str(&env, "c"));
-    client.add_vc(&3, &String::from_str(&env, "d"));
+    client.add_vc(&admin, &1, &String::from_str(&env, "b"));
+    client.add_vc(&admin, &3, &String::from_str(&env, "c"));
+    client.add_vc(&admin, &3, &String::from_str(&env, "d"));
 
     assert_eq!(300000, client.amount(&1));
     assert_eq!(client.address, client.owner(&1));
@@ -199,7 +501,7 @@ fn test_split_nested() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(1000000, client.amount(&0));
 
     client.split(
@@ -244,7 +546,7 @@ fn test_split_twice() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     client.split(
         &0,
         &vec![
@@ -283,7 +585,7 @@ fn test_split_exceed_total() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(1000000, client.amount(&0));
 
     let res = client.try_split(
@@ -317,7 +619,7 @@ fn test_split_empty() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     let res = client.try_split(&0, &vec![&env]);
     assert_eq!(
         res,
@@ -336,7 +638,7 @@ fn test_split_minimum_amount() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(1000000, client.amount(&0));
 
     let res = client.try_split(
@@ -366,7 +668,7 @@ fn test_split_nested_depth_limit() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
     assert_eq!(1000000, client.amount(&0));
 
     // first 5 splits should succeed, 6th split should fail
@@ -396,117 +698,701 @@ fn test_split_nested_depth_limit() {
 }
 
 #[test]
-fn test_transfer() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let admin = Address::generate(&env);
-    let buyer = Address::generate(&env);
-    let client = setup_test_token(&env, &admin, &buyer);
-
-    let acc1 = Address::generate(&env);
-    let acc2 = Address::generate(&env);
-    client.mint_original(&acc1, &String::from_str(&env, "a"));
-    assert_eq!(acc1, client.owner(&0));
-
-    client.transfer(&acc1, &acc2, &0);
-    assert_eq!(acc2, client.owner(&0));
-}
-
-#[test]
-fn test_burn() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let admin = Address::generate(&env);
-    let buyer = Address::generate(&env);
-    let client = setup_test_token(&env, &admin, &buyer);
-
-    client.mint_original(&admin, &String::from_str(&env, "a"));
-    let res = client.try_owner(&0);
-    assert_eq!(res.is_ok(), true);
-
-    client.burn(&0);
-    let res2 = client.try_owner(&0);
-    assert_eq!(res2.is_ok(), false);
-}
-
-#[test]
-fn test_pay_off() {
+fn test_split_policy() {
     let env = Env::default();
     env.mock_all_auths();
     let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let client = setup_test_token(&env, &admin, &buyer);
+    assert_eq!(
+        SplitPolicy {
+            max_depth: 5,
+            min_split_bps: 1000,
+            max_children: 10,
+            allow_remainder: true,
+        },
+        client.split_policy()
+    );
 
-    // setup fake external token
-    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
-    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
-    ext_admin.mint(&buyer, &10000000000000);
-
-    client.set_external_token_provider(&ext_token_addr, &7);
-    assert_eq!(client.check_paid(), false);
-
-    client.pay_off(&buyer);
-    assert_eq!(client.check_paid(), true);
-}
-
-#[test]
-fn test_check_expired() {
-    let env = Env::default();
-    set_ledger_timestamp(&env, 1640995200); // 2022-01-01 00:00:00 UTC+0
-    let admin = Address::generate(&env);
-    let buyer = Address::generate(&env);
-    let client = setup_test_token(&env, &admin, &buyer);
-
-    assert_eq!(client.check_expired(), false);
-
-    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
-    assert_eq!(client.check_expired(), true);
-}
-
-#[test]
-fn test_expire_auto_transfer() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let admin = Address::generate(&env);
-    let buyer = Address::generate(&env);
-    let client = setup_test_token(&env, &admin, &buyer);
+    let policy = SplitPolicy {
+        max_depth: 1,
+        min_split_bps: 2500,
+        max_children: 2,
+        allow_remainder: false,
+    };
+    client.set_split_policy(&admin, &policy);
+    assert_eq!(policy, client.split_policy());
 
     let to = Address::generate(&env);
-    let to2 = Address::generate(&env);
-    let to3 = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
-    assert_eq!(to, client.owner(&0));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
 
-    client.split(
+    // each split must be at least 25% of the root total_amount
+    let res = client.try_split(
         &0,
         &vec![
             &env,
             SplitRequest {
-                amount: 500000,
-                to: to2.clone(),
+                amount: 249999,
+                to: to.clone(),
             },
             SplitRequest {
-                amount: 100000,
-                to: to2.clone(),
+                amount: 750001,
+                to: to.clone(),
             },
         ],
     );
-    client.sign_off(&1);
-    client.split(
-        &1,
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::SplitAmountTooLow as u32
+        )))
+    );
+
+    // the splits must add up to the parent amount
+    let res = client.try_split(
+        &0,
         &vec![
             &env,
             SplitRequest {
-                amount: 200000,
-                to: to3.clone(),
-            },
-            SplitRequest {
-                amount: 150000,
-                to: to3.clone(),
+                amount: 500000,
+                to: to.clone(),
             },
         ],
     );
-    client.sign_off(&4);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::SplitRemainderNotAllowed as u32
+        )))
+    );
+
+    // at most 2 children per split
+    let res = client.try_split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 250000,
+                to: to.clone(),
+            },
+            SplitRequest {
+                amount: 250000,
+                to: to.clone(),
+            },
+            SplitRequest {
+                amount: 500000,
+                to: to.clone(),
+            },
+        ],
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::SplitChildrenLimitReached as u32
+        )))
+    );
+
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: to.clone(),
+            },
+            SplitRequest {
+                amount: 500000,
+                to: to.clone(),
+            },
+        ],
+    );
+
+    // splits are limited to a depth of 1
+    client.sign_off(&1);
+    let res = client.try_split(
+        &1,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: to.clone(),
+            },
+        ],
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::SplitLimitReached as u32
+        )))
+    );
+
+    // the policy cannot change after the first split
+    let res = client.try_set_split_policy(&admin, &policy);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+}
+
+#[test]
+fn test_split_policy_invalid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let res = client.try_set_split_policy(
+        &admin,
+        &SplitPolicy {
+            max_depth: 5,
+            min_split_bps: 10001,
+            max_children: 10,
+            allow_remainder: true,
+        },
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidArgs as u32
+        )))
+    );
+
+    let res = client.try_set_split_policy(
+        &admin,
+        &SplitPolicy {
+            max_depth: 5,
+            min_split_bps: 1000,
+            max_children: 0,
+            allow_remainder: true,
+        },
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidArgs as u32
+        )))
+    );
+}
+
+#[test]
+fn test_merge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    let to2 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 300000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 1, 2], client.children(&0));
+
+    // the split request was not signed off yet, so the owner of the parent can merge it back
+    client.merge(&0);
+    assert!(!client.is_disabled(&0));
+    assert_eq!(to, client.owner(&0));
+    assert_eq!(vec![&env], client.children(&0));
+    assert!(client.try_owner(&1).is_err());
+    assert!(client.try_owner(&2).is_err());
+    assert_eq!(vec![&env, 0], client.get_all_owned(&to));
+
+    // the merged TC can be split again
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 3, 4], client.children(&0));
+    assert_eq!(500000, client.amount(&3));
+    assert_eq!(0, client.parent(&3));
+}
+
+#[test]
+fn test_merge_siblings_same_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    let to2 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 300000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+
+    // the siblings have different owners
+    let res = client.try_merge(&0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotOwned as u32
+        )))
+    );
+
+    // once one address holds all siblings, merging gives it the parent
+    client.transfer(&to, &to2, &2);
+    client.merge(&0);
+    assert_eq!(to2, client.owner(&0));
+    assert_eq!(vec![&env, 0], client.get_all_owned(&to2));
+    assert_eq!(vec![&env], client.get_all_owned(&to));
+}
+
+#[test]
+fn test_merge_invalid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+
+    // a TC that was not split cannot be merged
+    let res = client.try_merge(&0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // a TC whose children have been split cannot be merged
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 600000,
+                to: to.clone(),
+            },
+        ],
+    );
+    client.split(
+        &2,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 200000,
+                to: to.clone(),
+            },
+        ],
+    );
+    let res = client.try_merge(&0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // after merging the children, the parent can be merged too
+    client.merge(&2);
+    client.merge(&0);
+    assert_eq!(vec![&env, 0], client.get_all_owned(&to));
+
+    // merging is not possible after the maturity date
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 600000,
+                to: to.clone(),
+            },
+        ],
+    );
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    let res = client.try_merge(&0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+}
+
+#[test]
+fn test_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+    assert_eq!(acc1, client.owner(&0));
+
+    client.transfer(&acc1, &acc2, &0);
+    assert_eq!(acc2, client.owner(&0));
+}
+
+#[test]
+fn test_approve_and_transfer_from() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    let spender = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+    assert_eq!(None, client.get_approved(&0));
+
+    // a spender without approval cannot transfer the token
+    let res = client.try_transfer_from(&spender, &acc1, &acc2, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+
+    client.approve(&acc1, &spender, &0, &100);
+    assert_eq!(Some(spender.clone()), client.get_approved(&0));
+    client.transfer_from(&spender, &acc1, &acc2, &0);
+    assert_eq!(acc2, client.owner(&0));
+
+    // the approval is cleared once the token changes owner
+    assert_eq!(None, client.get_approved(&0));
+    let res = client.try_transfer_from(&spender, &acc2, &acc1, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+}
+
+#[test]
+fn test_approve_expired() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    let spender = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+
+    set_ledger_sequence(&env, 100);
+    client.approve(&acc1, &spender, &0, &200);
+    set_ledger_sequence(&env, 200);
+    assert_eq!(Some(spender.clone()), client.get_approved(&0));
+    set_ledger_sequence(&env, 201);
+    assert_eq!(None, client.get_approved(&0));
+    assert!(client
+        .try_transfer_from(&spender, &acc1, &acc2, &0)
+        .is_err());
+
+    // an expiration ledger in the past revokes the approval
+    client.approve(&acc1, &spender, &0, &300);
+    assert_eq!(Some(spender.clone()), client.get_approved(&0));
+    client.approve(&acc1, &spender, &0, &0);
+    assert_eq!(None, client.get_approved(&0));
+
+    // only the owner can approve
+    let res = client.try_approve(&acc2, &spender, &0, &300);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotOwned as u32
+        )))
+    );
+}
+
+#[test]
+fn test_set_approval_for_all() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    let operator = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: acc1.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+    assert!(!client.is_approved_for_all(&acc1, &operator));
+
+    // an operator can transfer any token of the owner
+    client.set_approval_for_all(&acc1, &operator, &true);
+    assert!(client.is_approved_for_all(&acc1, &operator));
+    client.transfer_from(&operator, &acc1, &acc2, &1);
+    client.transfer_from(&operator, &acc1, &acc2, &2);
+    assert_eq!(vec![&env, 1, 2], client.get_all_owned(&acc2));
+
+    // operator approval does not extend to tokens of other owners
+    let res = client.try_transfer_from(&operator, &acc2, &acc1, &1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+
+    client.set_approval_for_all(&acc1, &operator, &false);
+    assert!(!client.is_approved_for_all(&acc1, &operator));
+}
+
+#[test]
+fn test_burn() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    client.mint_original(&admin, &admin, &String::from_str(&env, "a"));
+    let res = client.try_owner(&0);
+    assert_eq!(res.is_ok(), true);
+
+    client.burn(&admin, &0);
+    let res2 = client.try_owner(&0);
+    assert_eq!(res2.is_ok(), false);
+}
+
+#[test]
+fn test_pay_off() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+
+    client.set_external_token_provider(&admin, &ext_token_addr, &7);
+    assert_eq!(client.check_paid(), false);
+
+    client.pay_off(&buyer, &10000000000000);
+    assert_eq!(client.check_paid(), true);
+}
+
+#[test]
+fn test_pay_off_partial() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+    let ext_client = TokenClient::new(&env, ext_token_addr);
+
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+    assert_eq!(client.paid_amount(), 0);
+
+    // pay in two tranches
+    client.pay_off(&buyer, &4000000000000);
+    assert_eq!(client.paid_amount(), 4000000000000);
+    assert!(!client.check_paid());
+    assert_eq!(ext_client.balance(&client.address), 4000000000000);
+
+    client.pay_off(&buyer, &6000000000000);
+    assert_eq!(client.paid_amount(), 10000000000000);
+    assert!(client.check_paid());
+
+    // no further payments are accepted once the order is paid
+    let res = client.try_pay_off(&buyer, &1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotEmpty as u32
+        )))
+    );
+}
+
+#[test]
+fn test_pay_off_invalid_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &20000000000000);
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+
+    let res = client.try_pay_off(&buyer, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidArgs as u32
+        )))
+    );
+
+    client.pay_off(&buyer, &9000000000000);
+    let res = client.try_pay_off(&buyer, &1000000000001);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::AmountTooMuch as u32
+        )))
+    );
+    assert_eq!(client.paid_amount(), 9000000000000);
+}
+
+#[test]
+fn test_amount_due() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_timestamp(&env, 1640995200); // 2022-01-01 00:00:00 UTC+0
+    let contract_id = env.register_contract(None, TokenizedCertificate);
+    let client = TokenizedCertificateClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let end_time = 1672531200; // 2023-01-01 00:00:00 UTC+0
+    client.initialize(
+        &admin,
+        &buyer,
+        &1000000,
+        &end_time,
+        &vec![
+            &env,
+            Installment {
+                due_time: 1667260800, // 2022-11-01 00:00:00 UTC+0
+                amount: 400000,
+            },
+            Installment {
+                due_time: end_time,
+                amount: 600000,
+            },
+        ],
+        &None,
+    );
+
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+
+    // nothing is due before the first installment
+    assert_eq!(client.amount_due(), 0);
+
+    set_ledger_timestamp(&env, 1667260800);
+    assert_eq!(client.amount_due(), 4000000000000);
+    client.pay_off(&buyer, &3000000000000);
+    assert_eq!(client.amount_due(), 1000000000000);
+
+    set_ledger_timestamp(&env, end_time);
+    assert_eq!(client.amount_due(), 7000000000000);
+}
+
+#[test]
+fn test_check_expired() {
+    let env = Env::default();
+    set_ledger_timestamp(&env, 1640995200); // 2022-01-01 00:00:00 UTC+0
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    assert_eq!(client.end_time(), 1672531200);
+    assert_eq!(client.check_expired(), false);
+
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    assert_eq!(client.check_expired(), true);
+}
+
+#[test]
+fn test_expire_auto_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    let to2 = Address::generate(&env);
+    let to3 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    assert_eq!(to, client.owner(&0));
+
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: to2.clone(),
+            },
+            SplitRequest {
+                amount: 100000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+    client.split(
+        &1,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 200000,
+                to: to3.clone(),
+            },
+            SplitRequest {
+                amount: 150000,
+                to: to3.clone(),
+            },
+        ],
+    );
+    client.sign_off(&4);
     assert_eq!(to2, client.owner(&1));
     assert_eq!(client.address, client.owner(&2));
     assert_eq!(to, client.owner(&3));
@@ -514,54 +1400,536 @@ fn test_expire_auto_transfer() {
     assert_eq!(client.address, client.owner(&5));
     assert_eq!(to2, client.owner(&6));
 
-    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
-    assert_eq!(client.check_expired(), true);
-    assert_eq!(to, client.owner(&2));
-    assert_eq!(to2, client.owner(&5));
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    assert_eq!(client.check_expired(), true);
+    assert_eq!(to, client.owner(&2));
+    assert_eq!(to2, client.owner(&5));
+}
+
+#[test]
+fn test_redeem() {
+    // setup env with specific timestamp
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token and pay the contract
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+    let ext_client = TokenClient::new(&env, ext_token_addr);
+    ext_client.mock_all_auths_allowing_non_root_auth();
+
+    let supplier = Address::generate(&env);
+    client.mint_original(&admin, &supplier, &String::from_str(&env, "a"));
+    assert_eq!(supplier, client.owner(&0));
+
+    // setup preconditions, and redeem should fail before all preconditions are met
+    client.set_external_token_provider(&admin, &ext_token_addr, &7);
+    assert_eq!(client.try_redeem(&0).is_err(), true);
+    client.check_paid();
+    assert_eq!(client.try_redeem(&0).is_err(), true);
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    client.check_expired();
+
+    assert_eq!(ext_client.balance(&supplier), 0);
+
+    client.pay_off(&buyer, &10000000000000);
+    client.redeem(&0);
+
+    // check balance was transferred
+    assert_eq!(ext_client.balance(&supplier), 10000000000000);
+
+    // check TC was burned
+    assert_eq!(client.try_owner(&0).is_err(), true)
+}
+
+#[test]
+fn test_pause() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    StellarAssetClient::new(&env, ext_token_addr).mint(&buyer, &10000000000000);
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+
+    // only a guardian can pause
+    let guardian = Address::generate(&env);
+    let res = client.try_pause(&guardian);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+    client.grant_role(&Role::Guardian, &guardian);
+    client.pause(&guardian);
+    assert!(client.is_paused());
+
+    // transfers and splits fail while paused
+    let res = client.try_transfer(&acc1, &acc2, &0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    let res = client.try_split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: acc2.clone(),
+            },
+        ],
+    );
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+
+    // redeem stays available unless the guardian closes the exits
+    client.pay_off(&buyer, &10000000000000);
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    assert!(client.exits_allowed());
+    client.set_exits_allowed(&guardian, &false);
+    let res = client.try_redeem(&0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    client.set_exits_allowed(&guardian, &true);
+    client.redeem(&0);
+    assert!(client.try_owner(&0).is_err());
+
+    client.unpause(&guardian);
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_redeem_partially_paid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+    let ext_client = TokenClient::new(&env, ext_token_addr);
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+
+    // split the root TC 30/70 between two suppliers
+    let supplier = Address::generate(&env);
+    let supplier2 = Address::generate(&env);
+    client.mint_original(&admin, &supplier, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 300000,
+                to: supplier2.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+
+    // buyer pays half of the invoice
+    client.pay_off(&buyer, &5000000000000);
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+
+    // each TC receives its share of what has been collected, and is not burned
+    client.redeem(&1);
+    client.redeem(&2);
+    assert_eq!(ext_client.balance(&supplier2), 1500000000000);
+    assert_eq!(ext_client.balance(&supplier), 3500000000000);
+    assert_eq!(supplier2, client.owner(&1));
+
+    // redeeming again without a new payment should fail
+    let res = client.try_redeem(&1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // after the remaining payment, each TC receives the rest of its share and is burned
+    client.pay_off(&buyer, &5000000000000);
+    client.redeem(&1);
+    client.redeem(&2);
+    assert_eq!(ext_client.balance(&supplier2), 3000000000000);
+    assert_eq!(ext_client.balance(&supplier), 7000000000000);
+    assert_eq!(ext_client.balance(&client.address), 0);
+    assert!(client.try_owner(&1).is_err());
+    assert!(client.try_owner(&2).is_err());
+}
+
+#[test]
+fn test_settle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+    let ext_client = TokenClient::new(&env, ext_token_addr);
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+    client.set_grace_period(&admin, &86400);
+    assert_eq!(client.grace_period(), 86400);
+
+    // split the root TC 30/70 between two suppliers
+    let supplier = Address::generate(&env);
+    let supplier2 = Address::generate(&env);
+    client.mint_original(&admin, &supplier, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 300000,
+                to: supplier2.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+
+    // buyer only pays 60% of the invoice
+    client.pay_off(&buyer, &6000000000000);
+
+    // settling is not possible before the grace period has passed
+    set_ledger_timestamp(&env, 1672531200); // 2023-01-01 00:00:00 UTC+0
+    let res = client.try_settle(&admin);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    client.settle(&admin);
+    assert!(client.check_settled());
+    assert_eq!(client.shortfall(&1), 1200000000000);
+    assert_eq!(client.shortfall(&2), 2800000000000);
+
+    // no further payments are accepted after settlement
+    let res = client.try_pay_off(&buyer, &1000000000000);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+
+    // each TC is redeemed for its share of the paid amount, burned, and the shortfall is recorded
+    client.redeem(&1);
+    client.redeem(&2);
+    assert_eq!(ext_client.balance(&supplier2), 1800000000000);
+    assert_eq!(ext_client.balance(&supplier), 4200000000000);
+    assert_eq!(client.shortfall(&1), 1200000000000);
+    assert_eq!(client.shortfall(&2), 2800000000000);
+    assert!(client.try_owner(&1).is_err());
+    assert!(client.try_owner(&2).is_err());
+}
+
+#[test]
+fn test_settle_without_payment() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+
+    let supplier = Address::generate(&env);
+    client.mint_original(&admin, &supplier, &String::from_str(&env, "a"));
+
+    // the default grace period is 30 days after the maturity date
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    assert!(client.try_settle(&admin).is_err());
+    set_ledger_timestamp(&env, 1675123200); // 2023-01-31 00:00:00 UTC +0
+
+    // a grace period reaching past the end of time never lets the order be settled
+    client.set_grace_period(&admin, &u64::MAX);
+    let res = client.try_settle(&admin);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    client.set_grace_period(&admin, &(30 * 86400));
+
+    client.settle(&admin);
+    assert_eq!(client.shortfall(&0), 10000000000000);
+
+    // settling twice should fail
+    let res = client.try_settle(&admin);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotEmpty as u32
+        )))
+    );
+
+    // the TC can still be redeemed to close it out, recording the whole value as shortfall
+    client.redeem(&0);
+    assert_eq!(client.shortfall(&0), 10000000000000);
+    assert!(client.try_owner(&0).is_err());
+}
+
+#[test]
+fn test_settle_paid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    // setup fake external token
+    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
+    ext_admin.mint(&buyer, &10000000000000);
+    client.set_external_token_provider(&admin, ext_token_addr, &7);
+    client.pay_off(&buyer, &10000000000000);
+
+    // a fully paid order cannot be settled
+    set_ledger_timestamp(&env, 1675123200); // 2023-01-31 00:00:00 UTC +0
+    let res = client.try_settle(&admin);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotEmpty as u32
+        )))
+    );
+}
+
+#[test]
+fn test_sign_off() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    assert_eq!(to, client.owner(&0));
+
+    let split_req = SplitRequest {
+        amount: 600000,
+        to: to.clone(),
+    };
+    client.split(&0, &vec![&env, split_req.clone()]);
+    assert_eq!(client.address, client.owner(&1));
+    assert_eq!(to, client.recipient(&1));
+    client.sign_off(&1);
+    assert_eq!(to, client.owner(&1));
+}
+
+#[test]
+fn test_freeze() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    let split_req = SplitRequest {
+        amount: 600000,
+        to: to.clone(),
+    };
+    client.split(&0, &vec![&env, split_req.clone()]);
+    let reason_hash = BytesN::from_array(&env, &[1; 32]);
+
+    // only a holder of the Compliance role can freeze
+    let res = client.try_freeze(&acc2, &1, &reason_hash);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAuthorized as u32
+        )))
+    );
+
+    // freezing a TC leaves its siblings alone
+    client.freeze(&admin, &1, &reason_hash);
+    match env.events().all().last() {
+        Some((_, topics, data)) => {
+            assert_eq!(
+                topics,
+                (symbol_short!("freeze"), admin.clone()).into_val(&env)
+            );
+            let data_decoded: (i128, BytesN<32>) = data.into_val(&env);
+            assert_eq!(data_decoded, (1, reason_hash.clone()));
+        }
+        None => panic!("The event is not published"),
+    }
+    assert!(client.is_frozen(&1));
+    assert!(!client.is_frozen(&2));
+    for res in [
+        client.try_sign_off(&1),
+        client.try_reject(&1),
+        client.try_cancel_split_request(&1),
+    ] {
+        assert_eq!(
+            res,
+            Err(Ok(Error::from_contract_error(ContractError::Frozen as u32)))
+        );
+    }
+
+    // the parent cannot be merged back while one of its children is frozen
+    let res = client.try_merge(&0);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Frozen as u32)))
+    );
+    client.transfer(&to, &acc2, &2);
+    client.unfreeze(&admin, &1);
+
+    // freezing a TC also freezes its descendants
+    client.freeze(&admin, &0, &reason_hash);
+    assert!(client.is_frozen(&1));
+    assert!(client.is_frozen(&2));
+    let res = client.try_transfer(&acc2, &to, &2);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(ContractError::Frozen as u32)))
+    );
+    let res = client.try_unfreeze(&admin, &2);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotFound as u32
+        )))
+    );
+
+    client.unfreeze(&admin, &0);
+    assert!(!client.is_frozen(&1));
+    client.sign_off(&1);
+    assert_eq!(to, client.owner(&1));
 }
 
 #[test]
-fn test_redeem() {
-    // setup env with specific timestamp
+fn test_reject() {
     let env = Env::default();
     env.mock_all_auths();
     let admin = Address::generate(&env);
     let buyer = Address::generate(&env);
     let client = setup_test_token(&env, &admin, &buyer);
 
-    // setup fake external token and pay the contract
-    let ext_token_addr = &env.register_stellar_asset_contract(admin.clone());
-    let ext_admin = StellarAssetClient::new(&env, ext_token_addr);
-    ext_admin.mint(&buyer, &10000000000000);
-    let ext_client = TokenClient::new(&env, ext_token_addr);
-    ext_client.mock_all_auths_allowing_non_root_auth();
+    let to = Address::generate(&env);
+    let to2 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 600000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 1], client.pending_sign_offs(&to2));
 
-    let supplier = Address::generate(&env);
-    client.mint_original(&supplier, &String::from_str(&env, "a"));
-    assert_eq!(supplier, client.owner(&0));
+    // the recipient rejects the split, and the TC goes to the owner of the parent right away
+    client.reject(&1);
+    assert_eq!(to, client.owner(&1));
+    assert_eq!(vec![&env, 1, 2], client.get_all_owned(&to));
+    assert_eq!(vec![&env], client.pending_sign_offs(&to2));
 
-    // setup preconditions, and redeem should fail before all preconditions are met
-    client.set_external_token_provider(&ext_token_addr, &7);
-    assert_eq!(client.try_redeem(&0).is_err(), true);
-    client.check_paid();
-    assert_eq!(client.try_redeem(&0).is_err(), true);
-    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
-    client.check_expired();
+    // a rejected TC can no longer be signed off
+    let res = client.try_sign_off(&1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+}
 
-    assert_eq!(ext_client.balance(&supplier), 0);
+#[test]
+fn test_cancel_split_request() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
 
-    client.pay_off(&buyer);
-    client.redeem(&0);
+    let to = Address::generate(&env);
+    let to2 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 200000,
+                to: to2.clone(),
+            },
+            SplitRequest {
+                amount: 300000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 1, 2], client.pending_sign_offs(&to2));
+
+    // split requests cannot be merged, signed off, rejected or cancelled while paused
+    client.pause(&admin);
+    for res in [
+        client.try_merge(&0),
+        client.try_sign_off(&1),
+        client.try_reject(&2),
+        client.try_cancel_split_request(&2),
+    ] {
+        assert_eq!(
+            res,
+            Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+        );
+    }
+    client.unpause(&admin);
 
-    // check balance was transferred
-    assert_eq!(ext_client.balance(&supplier), 10000000000000);
+    client.cancel_split_request(&2);
+    assert_eq!(to, client.owner(&2));
+    assert_eq!(vec![&env, 1], client.pending_sign_offs(&to2));
 
-    // check TC was burned
-    assert_eq!(client.try_owner(&0).is_err(), true)
+    // a signed off TC can no longer be cancelled
+    client.sign_off(&1);
+    assert_eq!(vec![&env], client.pending_sign_offs(&to2));
+    let res = client.try_cancel_split_request(&1);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
 }
 
 #[test]
-fn test_sign_off() {
+fn test_pending_sign_offs_after_expiry() {
     let env = Env::default();
     env.mock_all_auths();
     let admin = Address::generate(&env);
@@ -569,18 +1937,23 @@ fn test_sign_off() {
     let client = setup_test_token(&env, &admin, &buyer);
 
     let to = Address::generate(&env);
-    client.mint_original(&to, &String::from_str(&env, "a"));
-    assert_eq!(to, client.owner(&0));
+    let to2 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 200000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 1], client.pending_sign_offs(&to2));
 
-    let split_req = SplitRequest {
-        amount: 600000,
-        to: to.clone(),
-    };
-    client.split(&0, &vec![&env, split_req.clone()]);
-    assert_eq!(client.address, client.owner(&1));
-    assert_eq!(to, client.recipient(&1));
-    client.sign_off(&1);
-    assert_eq!(to, client.owner(&1));
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    assert_eq!(vec![&env], client.pending_sign_offs(&to2));
+    assert!(client.try_reject(&1).is_err());
 }
 
 #[test]
@@ -594,7 +1967,7 @@ fn test_get_all_owned() {
     let to = Address::generate(&env);
     assert_eq!(vec![&env], client.get_all_owned(&to));
 
-    client.mint_original(&to, &String::from_str(&env, "a"));
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
 
     assert_eq!(vec![&env, 0], client.get_all_owned(&to));
 
@@ -618,3 +1991,243 @@ fn test_get_all_owned() {
     client.sign_off(&2);
     assert_eq!(vec![&env, 1, 2, 3], client.get_all_owned(&to));
 }
+
+#[test]
+fn test_get_all_owned_after_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 400000,
+                to: acc2.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 2], client.get_all_owned(&acc1));
+    assert_eq!(vec![&env, 1], client.get_all_owned(&client.address));
+
+    client.sign_off(&1);
+    assert_eq!(vec![&env], client.get_all_owned(&client.address));
+    assert_eq!(vec![&env, 1], client.get_all_owned(&acc2));
+
+    client.transfer(&acc1, &acc2, &2);
+    assert_eq!(vec![&env], client.get_all_owned(&acc1));
+    assert_eq!(vec![&env, 1, 2], client.get_all_owned(&acc2));
+
+    client.burn(&admin, &1);
+    assert_eq!(vec![&env, 2], client.get_all_owned(&acc2));
+}
+
+#[test]
+fn test_get_all_owned_after_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let to = Address::generate(&env);
+    let to2 = Address::generate(&env);
+    let to3 = Address::generate(&env);
+    client.mint_original(&admin, &to, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 500000,
+                to: to2.clone(),
+            },
+            SplitRequest {
+                amount: 100000,
+                to: to2.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+    client.split(
+        &1,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 200000,
+                to: to3.clone(),
+            },
+        ],
+    );
+    assert_eq!(vec![&env, 3], client.get_all_owned(&to));
+    assert_eq!(vec![&env, 5], client.get_all_owned(&to2));
+
+    // unclaimed TCs are only moved to the parent's owner when they are looked up
+    set_ledger_timestamp(&env, 1672617600); // 2023-01-02 00:00:00 UTC +0
+    assert_eq!(vec![&env, 2, 3], client.get_all_owned(&to));
+    assert_eq!(vec![&env, 4, 5], client.get_all_owned(&to2));
+    assert_eq!(vec![&env], client.get_all_owned(&to3));
+    assert_eq!(vec![&env], client.get_all_owned(&client.address));
+}
+
+#[test]
+fn test_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+    assert_eq!(1, client.version());
+
+    // storage written by the current version needs no migration
+    let res = client.try_migrate(&0, &10);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+}
+
+#[test]
+fn test_upgrade() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let ext_token_addr = env.register_stellar_asset_contract(admin.clone());
+    let ext_token = TokenClient::new(&env, &ext_token_addr);
+    let ext_admin = StellarAssetClient::new(&env, &ext_token_addr);
+    ext_admin.mint(&buyer, &1000000);
+    client.set_external_token_provider(&admin, &ext_token_addr, &0);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 300000,
+                to: acc2.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+    client.pay_off(&buyer, &400000);
+
+    // move to the release build, then to a build with another hash
+    let wasm_hash = env.deployer().upload_contract_wasm(tc_contract::WASM);
+    client.upgrade(&wasm_hash);
+    let rebuilt_hash = upload_rebuilt_wasm(&env, tc_contract::WASM);
+    assert_ne!(wasm_hash, rebuilt_hash);
+    client.upgrade(&rebuilt_hash);
+
+    // existing certificates and balances survive the upgrades
+    assert_eq!(1, client.version());
+    assert_eq!(acc2, client.owner(&1));
+    assert_eq!(300000, client.amount(&1));
+    assert_eq!(acc1, client.owner(&2));
+    assert_eq!(700000, client.amount(&2));
+    assert_eq!(vec![&env, 2], client.get_all_owned(&acc1));
+    assert_eq!(vec![&env, 1, 2], client.children(&0));
+    assert_eq!(400000, client.paid_amount());
+    assert_eq!(400000, ext_token.balance(&client.address));
+    assert_eq!(600000, ext_token.balance(&buyer));
+
+    // the order can still be paid off
+    client.pay_off(&buyer, &600000);
+    assert!(client.check_paid());
+}
+
+#[test]
+fn test_migrate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let client = setup_test_token(&env, &admin, &buyer);
+
+    let ext_token_addr = env.register_stellar_asset_contract(admin.clone());
+    let ext_admin = StellarAssetClient::new(&env, &ext_token_addr);
+    ext_admin.mint(&buyer, &1000000);
+    client.set_external_token_provider(&admin, &ext_token_addr, &0);
+
+    let acc1 = Address::generate(&env);
+    let acc2 = Address::generate(&env);
+    let acc3 = Address::generate(&env);
+    client.mint_original(&admin, &acc1, &String::from_str(&env, "a"));
+    client.split(
+        &0,
+        &vec![
+            &env,
+            SplitRequest {
+                amount: 300000,
+                to: acc2.clone(),
+            },
+            SplitRequest {
+                amount: 200000,
+                to: acc3.clone(),
+            },
+        ],
+    );
+    client.sign_off(&1);
+    client.pay_off(&buyer, &1000000);
+
+    // rewrite the storage to the layout used before versioning
+    env.as_contract(&client.address, || {
+        env.storage().instance().remove(&DataKey::Version);
+        for role in ALL_ROLES {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Role(role, admin.clone()));
+        }
+        env.storage().persistent().remove(&DataKey::PaidAmount);
+        env.storage().persistent().remove(&DataKey::Children(0));
+        for acc in [&acc1, &acc2, &acc3, &client.address] {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Owned(acc.clone()));
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Pending(acc.clone()));
+        }
+    });
+    assert_eq!(0, client.version());
+    assert_eq!(vec![&env], client.get_all_owned(&acc2));
+
+    assert!(!client.has_role(&Role::Guardian, &admin));
+    assert_eq!(3, client.migrate(&0, &3));
+    for role in ALL_ROLES {
+        assert!(client.has_role(&role, &admin));
+    }
+    // the version only changes after the last batch
+    assert_eq!(0, client.version());
+    assert_eq!(vec![&env, 1], client.get_all_owned(&acc2));
+    assert_eq!(vec![&env], client.get_all_owned(&acc1));
+
+    // the next batch has to start where the previous one stopped
+    let res = client.try_migrate(&0, &3);
+    assert_eq!(
+        res,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotPermitted as u32
+        )))
+    );
+    assert_eq!(4, client.migrate(&3, &3));
+    assert_eq!(1, client.version());
+    assert_eq!(vec![&env, 3], client.get_all_owned(&acc1));
+    assert_eq!(vec![&env, 1], client.get_all_owned(&acc2));
+    assert_eq!(vec![&env, 2], client.pending_sign_offs(&acc3));
+    assert_eq!(vec![&env, 1, 2, 3], client.children(&0));
+    assert_eq!(1000000, client.paid_amount());
+}
@@ -1,7 +1,11 @@
 #![cfg(any(test, feature = "testutils"))]
 
 use crate::contract::{TokenizedCertificate, TokenizedCertificateClient};
-use soroban_sdk::{testutils::Ledger as _, Address, Env};
+use soroban_sdk::{testutils::Ledger as _, Address, Bytes, BytesN, Env, Vec};
+
+pub mod tc_contract {
+    soroban_sdk::contractimport!(file = "target/wasm32-unknown-unknown/release/scf_soroban.wasm");
+}
 
 pub fn setup_test_token<'a>(
     env: &Env,
@@ -14,10 +18,28 @@ pub fn setup_test_token<'a>(
     let total_amount: u32 = 1000000;
     let end_time = 1672531200; // 2023-01-01 00:00:00 UTC+0
 
-    client.initialize(admin, buyer, &total_amount, &end_time);
+    client.initialize(
+        admin,
+        buyer,
+        &total_amount,
+        &end_time,
+        &Vec::new(env),
+        &None,
+    );
     client
 }
 
 pub fn set_ledger_timestamp(e: &Env, timestamp: u64) {
     e.ledger().with_mut(|li| li.timestamp = timestamp);
 }
+
+pub fn set_ledger_sequence(e: &Env, sequence: u32) {
+    e.ledger().with_mut(|li| li.sequence_number = sequence);
+}
+
+// uploads "wasm" with an extra custom section, so that the same code gets a new hash and an upgrade to it swaps the executable
+pub fn upload_rebuilt_wasm(env: &Env, wasm: &[u8]) -> BytesN<32> {
+    let mut rebuilt = Bytes::from_slice(env, wasm);
+    rebuilt.extend_from_slice(&[0, 9, 7, b'u', b'p', b'g', b'r', b'a', b'd', b'e', 1]);
+    env.deployer().upload_contract_wasm(rebuilt)
+}
@@ -0,0 +1,89 @@
+use crate::balance::read_supply;
+use crate::errors::Error;
+use crate::metadata::read_external_token;
+use crate::order_info::read_order_info;
+use crate::order_state::{read_paid, read_paid_amount, write_paid_amount};
+use crate::owner::{add_owned, add_pending};
+use crate::storage_types::{DataKey, STORAGE_VERSION};
+use crate::sub_tc::{read_children, read_sub_tc, read_sub_tc_disabled, write_children};
+use soroban_sdk::{panic_with_error, Address, Env};
+
+// contracts deployed before versioning was introduced have no stored version
+pub fn read_version(env: &Env) -> u32 {
+    let key = DataKey::Version;
+    env.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_version(env: &Env, version: u32) {
+    let key = DataKey::Version;
+    env.storage().instance().set(&key, &version);
+}
+
+// the next TC to migrate and the supply when the migration started, TCs created since then are already in the current layout
+fn read_migration_cursor(env: &Env) -> Option<(i128, i128)> {
+    let key = DataKey::MigrationCursor;
+    env.storage().instance().get(&key)
+}
+
+// migrates the TCs from "start" on, at most "limit" of them, and returns where the next batch starts
+pub fn migrate(env: &Env, start: i128, limit: u32) -> i128 {
+    let (cursor, end) = read_migration_cursor(env).unwrap_or((0, read_supply(env)));
+    if start != cursor {
+        panic_with_error!(env, Error::NotPermitted);
+    }
+    let batch_end = end.min(start.saturating_add(i128::from(limit)));
+    let version = read_version(env);
+    if version < 1 {
+        migrate_v1(env, start, batch_end);
+    }
+
+    let key = DataKey::MigrationCursor;
+    if batch_end < end {
+        env.storage().instance().set(&key, &(batch_end, end));
+    } else {
+        if version < 1 {
+            migrate_v1_paid_amount(env);
+        }
+        env.storage().instance().remove(&key);
+        write_version(env, STORAGE_VERSION);
+    }
+    batch_end
+}
+
+// version 1 added the owned, pending and children indexes, and tracks the cumulative paid amount instead of a paid flag
+fn migrate_v1(env: &Env, start: i128, end: i128) {
+    let contract_addr = env.current_contract_address();
+    for id in start..end {
+        if let Some(Some(owner)) = env
+            .storage()
+            .persistent()
+            .get::<DataKey, Option<Address>>(&DataKey::Owner(id))
+        {
+            add_owned(env, &owner, id);
+            if owner == contract_addr && !read_sub_tc_disabled(env, id) {
+                if let Some(recipient) = env
+                    .storage()
+                    .persistent()
+                    .get::<DataKey, Address>(&DataKey::Recipient(id))
+                {
+                    add_pending(env, &recipient, id);
+                }
+            }
+        }
+        // only one split per TC was possible, so every TC with a parent is one of its children
+        if id > 0 {
+            let parent = read_sub_tc(env, id).parent;
+            let mut children = read_children(env, parent);
+            children.push_back(id);
+            write_children(env, parent, &children);
+        }
+    }
+}
+
+fn migrate_v1_paid_amount(env: &Env) {
+    if read_paid(env) && read_paid_amount(env) == 0 {
+        let decimals = read_external_token(env).decimals;
+        let total_amount = i128::from(read_order_info(env).total_amount) * 10i128.pow(decimals);
+        write_paid_amount(env, total_amount);
+    }
+}